@@ -0,0 +1,139 @@
+//!
+//! Sound effects for what happens in play. Playing them needs the `audio` feature, without it
+//! or without an audio device the game runs silently.
+//!
+
+use std::path::Path;
+
+/// Directory the sound effects are loaded from
+pub const SOUNDS_DIR: &str = "assets/sounds";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Slide,  // starting to slide over ice
+    Boing,  // landing on a bounce tile
+    Whoosh, // jumping through a portal or back to a bookmark
+    Thud,   // running into a wall
+}
+
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub const ALL_SOUND_EFFECTS: &[SoundEffect] = &[
+    SoundEffect::Slide,
+    SoundEffect::Boing,
+    SoundEffect::Whoosh,
+    SoundEffect::Thud,
+];
+
+impl SoundEffect {
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SoundEffect::Slide => "slide.wav",
+            SoundEffect::Boing => "boing.wav",
+            SoundEffect::Whoosh => "whoosh.wav",
+            SoundEffect::Thud => "thud.wav",
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod output {
+    use super::{ALL_SOUND_EFFECTS, SoundEffect};
+
+    use rodio::source::Buffered;
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
+    use std::collections::HashMap;
+    use std::io::Cursor;
+    use std::path::Path;
+
+    type Sound = Buffered<Decoder<Cursor<Vec<u8>>>>;
+
+    /// The default audio device, with every sound effect decoded up front
+    pub struct Output {
+        _stream: OutputStream, // Sound stops once this is dropped
+        handle: OutputStreamHandle,
+        sounds: HashMap<SoundEffect, Sound>,
+    }
+
+    impl Output {
+        pub fn open(sounds_dir: &Path) -> Option<Output> {
+            let (stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(err) => {
+                    eprintln!("Warning: no audio output, playing without sound: {err}");
+                    return None;
+                }
+            };
+
+            let mut sounds = HashMap::new();
+            for sound in ALL_SOUND_EFFECTS {
+                let decoded = std::fs::read(sounds_dir.join(sound.file_name()))
+                    .map_err(|err| err.to_string())
+                    .and_then(|bytes| {
+                        Decoder::new(Cursor::new(bytes)).map_err(|err| err.to_string())
+                    });
+                match decoded {
+                    Ok(decoder) => {
+                        sounds.insert(*sound, decoder.buffered());
+                    }
+                    Err(err) => {
+                        eprintln!("Warning: failed to load sound {}: {err}", sound.file_name())
+                    }
+                }
+            }
+
+            Some(Output {
+                _stream: stream,
+                handle,
+                sounds,
+            })
+        }
+
+        pub fn play(&self, sound: SoundEffect, volume: f32) {
+            if let Some(source) = self.sounds.get(&sound) {
+                // A sound effect that fails to play isn't worth interrupting the game for
+                let _ = self
+                    .handle
+                    .play_raw(source.clone().convert_samples::<f32>().amplify(volume));
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod output {
+    use super::SoundEffect;
+
+    use std::path::Path;
+
+    /// Built without the `audio` feature, so there's never a device to play on
+    pub struct Output;
+
+    impl Output {
+        pub fn open(_sounds_dir: &Path) -> Option<Output> {
+            None
+        }
+
+        pub fn play(&self, _sound: SoundEffect, _volume: f32) {}
+    }
+}
+
+/// Plays sound effects, or does nothing when there's no audio output
+pub struct Audio {
+    output: Option<output::Output>,
+}
+
+impl Audio {
+    /// Open the default audio device and load every sound effect from `sounds_dir`
+    pub fn new(sounds_dir: &Path) -> Self {
+        Audio {
+            output: output::Output::open(sounds_dir),
+        }
+    }
+
+    pub fn play_sound(&self, sound: SoundEffect, volume: f32) {
+        if let Some(output) = &self.output {
+            output.play(sound, volume);
+        }
+    }
+}
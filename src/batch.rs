@@ -0,0 +1,120 @@
+//!
+//! Validating a whole folder of levels at once, from the command line.
+//!
+
+use super::editing_model::EditingModel;
+use super::error::FoamError;
+
+use std::path::{Path, PathBuf};
+
+/// Extension of saved board files
+const BOARD_EXTENSION: &str = "fg";
+
+/// Outcome of checking one level file
+#[derive(Debug)]
+pub struct LevelCheck {
+    pub path: PathBuf,
+    pub result: Result<(usize, u32), Vec<String>>, // optimal move count and difficulty, or why the level fails
+}
+
+/// Check every board file in a directory, in file name order. A file that can't be loaded
+/// fails on its own without stopping the rest of the batch
+pub fn check_levels(dir: &Path) -> Result<Vec<LevelCheck>, FoamError> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == BOARD_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let result = check_level(&path);
+            LevelCheck { path, result }
+        })
+        .collect())
+}
+
+fn check_level(path: &Path) -> Result<(usize, u32), Vec<String>> {
+    let model = EditingModel::load_board(&path.to_string_lossy())
+        .map_err(|err| vec![format!("Couldn't load the board. {err}")])?;
+
+    let issues = model.validation_report();
+    if !issues.is_empty() {
+        return Err(issues.iter().map(ToString::to_string).collect());
+    }
+    let moves = model
+        .solution_length()
+        .ok_or_else(|| vec!["The solver found no way to win.".to_string()])?;
+    Ok((moves, model.difficulty_score()))
+}
+
+/// Print a pass or fail line for each level, with the reasons failed levels fail. Returns
+/// whether every level passed
+pub fn print_summary(checks: &[LevelCheck]) -> bool {
+    for check in checks {
+        match &check.result {
+            Ok((moves, difficulty)) => println!(
+                "PASS {} (optimal: {moves} moves, difficulty {difficulty})",
+                check.path.display()
+            ),
+            Err(reasons) => {
+                println!("FAIL {}", check.path.display());
+                for reason in reasons {
+                    println!("    {reason}");
+                }
+            }
+        }
+    }
+
+    let passed = checks.iter().filter(|check| check.result.is_ok()).count();
+    println!("{passed} of {} levels passed", checks.len());
+    passed == checks.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_levels_reports_each_file() {
+        let dir = std::env::temp_dir().join("foam_game_batch_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+
+        let mut solvable = EditingModel::from_ascii("SCE\n").unwrap();
+        solvable
+            .save_board(dir.join("a.fg").to_str().unwrap())
+            .unwrap();
+        let mut no_start = EditingModel::from_ascii("CCE\n").unwrap();
+        no_start
+            .save_board(dir.join("b.fg").to_str().unwrap())
+            .unwrap();
+        std::fs::write(dir.join("c.fg"), "not a board").unwrap();
+        let off_board_start = EditingModel::from_ascii("SE\n")
+            .unwrap()
+            .to_json()
+            .unwrap()
+            .replace("\"start_pos\":[0,0]", "\"start_pos\":[5,5]");
+        std::fs::write(dir.join("d.fg"), off_board_start).unwrap();
+        std::fs::write(dir.join("notes.txt"), "skipped").unwrap();
+
+        let checks = check_levels(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(checks.len(), 4);
+        assert!(matches!(checks[0].result, Ok((1, _)))); // one run move
+        assert_eq!(
+            checks[1].result.as_ref().unwrap_err()[0],
+            "The board has no start space."
+        );
+        assert!(checks[2].result.as_ref().unwrap_err()[0].starts_with("Couldn't load"));
+        assert_eq!(
+            checks[3].result.as_ref().unwrap_err()[0],
+            "Couldn't load the board. Invalid board: start space is off the board"
+        );
+    }
+}
@@ -1,14 +1,194 @@
-use super::game_ui::{self, PlayerMovementData};
-use super::item::KeyItem;
-use super::tile::{Tile, TileData};
+use super::error::FoamError;
+use super::game_ui::{self, ALL_DIRECTIONS, DirectionKey, PlayerMovementData};
+use super::item::{
+    KeyItem, KeyOnBounce, KeyOnEmpty, KeyOnEquip, KeyOnGet, KeyOnUse, KeyOnWall, step_letter,
+};
+use super::playing_model::{MovementPopupData, PlayingModel, SolverStats};
+use super::tile::{
+    BOUNCE_RANGE, CardinalDirectionsAllowed, DiagonalDirectionsAllowed, SPEED_GATE_RANGE,
+    SPEED_PAD_RANGE, SWITCH_RANGE, Tile, TileData,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Current version of the saved board format
+pub const BOARD_FORMAT_VERSION: u32 = 1;
+
+/// Tiles moved per run move, holding space, on boards that don't set their own
+pub const DEFAULT_RUN_SPEED: usize = 2;
+pub const RUN_SPEED_RANGE: RangeInclusive<usize> = 2..=5;
+
+/// Most rows or columns a board may have, so a corrupt board size can't allocate a huge
+/// board
+pub const MAX_BOARD_SIDE: usize = 256;
+
+/// Most distinct game states the solver explores before giving up, so boards with huge or
+/// looping state spaces can't hang the editor
+const MAX_SOLVER_STATES: usize = 20_000;
+
+/// Source of board revisions, shared by every board so no two boards' revisions collide
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Tile each simulated move ended on, None once off the board
+pub type SimulatedPath = Vec<Option<(usize, usize)>>;
+
+/// Directions slides can carry the player over each ice tile
+pub type IceMomentum = HashMap<(usize, usize), CardinalDirectionsAllowed>;
+
+/// Envelope around the saved board, recording the format version
+#[derive(Serialize, JsonSchema)]
+struct BoardFile<'a> {
+    version: u32,
+    board: &'a EditingModel,
+}
+
+/// Board saved by the legacy editor as `.fgb`, a plain grid of tiles without keys. Its other
+/// fields are worked out again from the tiles, so they're ignored
+#[derive(Deserialize)]
+struct LegacyBoard {
+    board: Vec<Vec<Tile>>,
+}
+
+/// Migrate board data from one format version to the next
+fn migrate_board(
+    from_version: u64,
+    board: serde_json::Value,
+) -> Result<serde_json::Value, FoamError> {
+    match from_version {
+        // Version 1 only added the envelope, the board data is unchanged
+        0 => Ok(board),
+        _ => Err(FoamError::InvalidBoard(format!(
+            "No migration from board format version {from_version}"
+        ))),
+    }
+}
+
+/// Axes the editor mirrors edits across
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Horizontal, // Mirrored left to right
+    Vertical,   // Mirrored top to bottom
+    Both,
+}
+
+/// What is painted behind the board, in place of the empty tile texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum BackgroundStyle {
+    #[default]
+    Texture, // The empty tile texture
+    Solid([u8; 3]),                 // One RGB color
+    Checkerboard([u8; 3], [u8; 3]), // Two RGB colors, alternating between neighboring tiles
+}
+
+impl BackgroundStyle {
+    /// Background color of the tile at a position, None to draw the empty tile texture
+    pub fn color_at(&self, pos: (usize, usize)) -> Option<egui::Color32> {
+        let [r, g, b] = match self {
+            BackgroundStyle::Texture => return None,
+            BackgroundStyle::Solid(color) => *color,
+            BackgroundStyle::Checkerboard(even, odd) => {
+                if (pos.0 + pos.1).is_multiple_of(2) {
+                    *even
+                } else {
+                    *odd
+                }
+            }
+        };
+        Some(egui::Color32::from_rgb(r, g, b))
+    }
+
+    /// Text form used by the ASCII format, e.g. `#1b1b1b` or `#1b1b1b,#303030`
+    fn to_ascii(self) -> Option<String> {
+        let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+        match self {
+            BackgroundStyle::Texture => None,
+            BackgroundStyle::Solid(color) => Some(hex(color)),
+            BackgroundStyle::Checkerboard(even, odd) => Some(format!("{},{}", hex(even), hex(odd))),
+        }
+    }
+
+    fn from_ascii(text: &str) -> Option<Self> {
+        let color = |hex: &str| {
+            let hex = hex.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+            let channel = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+            Some([channel(0)?, channel(2)?, channel(4)?])
+        };
+        match text.split_once(',') {
+            Some((even, odd)) => Some(BackgroundStyle::Checkerboard(color(even)?, color(odd)?)),
+            None => Some(BackgroundStyle::Solid(color(text)?)),
+        }
+    }
+}
+
+/// A problem that keeps a board from being played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    MissingStart,
+    MissingEnd,
+    UnpairedPortal(char),
+    InvalidTile((usize, usize)),
+    Unsolvable,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::MissingStart => write!(f, "The board has no start space."),
+            ValidationIssue::MissingEnd => write!(f, "The board has no end space."),
+            ValidationIssue::UnpairedPortal(c) => {
+                write!(f, "Portal {c} must appear exactly twice.")
+            }
+            ValidationIssue::InvalidTile((row, col)) => {
+                write!(f, "The tile at row {row}, column {col} is invalid.")
+            }
+            ValidationIssue::Unsolvable => write!(f, "The end space can never be reached."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EditingModel {
     board: Vec<Vec<TileData>>,         // rows then columns
     board_size: (usize, usize),        // size of the board (width, height)
     start_pos: Option<(usize, usize)>, // position of unique start tile
     end_pos: Option<(usize, usize)>,   // position of unique end tile
+    #[serde(default)]
+    move_limit: Option<usize>, // maximum number of moves allowed to solve the board
+    #[serde(default = "default_run_speed")]
+    run_speed: usize, // tiles moved per run move
+    #[serde(default)]
+    name: String, // level name shown to players, empty if unnamed
+    #[serde(default)]
+    next_level: Option<String>, // board file played after winning, relative to this one
+    #[serde(default)]
+    background: BackgroundStyle, // painted behind the tiles
+    #[serde(default)]
+    gravity: Option<DirectionKey>, // direction the player is pulled one tile after every move
+    #[serde(skip)]
+    dirty: bool,     // edited since it was last saved or loaded
+    #[serde(skip, default = "next_revision")]
+    revision: u64, // changes with every edit to how the board plays or looks
+}
+
+fn default_run_speed() -> usize {
+    DEFAULT_RUN_SPEED
+}
+
+impl Default for EditingModel {
+    fn default() -> Self {
+        EditingModel::new((0, 0))
+    }
 }
 
 impl EditingModel {
@@ -19,45 +199,491 @@ impl EditingModel {
             board_size,
             start_pos: None,
             end_pos: None,
+            move_limit: None,
+            run_speed: DEFAULT_RUN_SPEED,
+            name: String::new(),
+            next_level: None,
+            background: BackgroundStyle::default(),
+            gravity: None,
+            dirty: false,
+            revision: next_revision(),
         }
     }
 
-    pub fn load_board(file: &str) -> Result<Self, String> {
-        let model_raw = std::fs::read_to_string(file)
-            .map_err(|err| format!("Error reading board file: {err}"))?;
-        let model: EditingModel = serde_json::from_str(&model_raw)
-            .map_err(|err| format!("Error deserializing board data: {err}"))?;
+    /// Model for an existing grid of tiles, finding its start and end spaces
+    pub fn from_board(board: Vec<Vec<TileData>>) -> Self {
+        let mut model = EditingModel::new((board.len(), board.first().map_or(0, Vec::len)));
+        for (row_idx, row) in board.into_iter().enumerate() {
+            for (col_idx, tile_data) in row.into_iter().enumerate() {
+                model.set_tile((row_idx, col_idx), tile_data.tile);
+                model.board[row_idx][col_idx].key = tile_data.key;
+            }
+        }
+        model
+    }
+
+    pub fn load_board(file: &str) -> Result<Self, FoamError> {
+        let model_raw = std::fs::read_to_string(file)?;
+        EditingModel::from_json(&model_raw)
+    }
+
+    /// Load a board saved as `.fgb` by the legacy editor
+    pub fn import_fgb(file: &str) -> Result<Self, FoamError> {
+        let board_raw = std::fs::read_to_string(file)?;
+        EditingModel::from_fgb_json(&board_raw)
+    }
+
+    fn from_fgb_json(board_raw: &str) -> Result<Self, FoamError> {
+        let legacy: LegacyBoard = serde_json::from_str(board_raw)?;
+        let board: Vec<Vec<TileData>> = legacy
+            .board
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|tile| TileData {
+                        tile,
+                        key: KeyItem::None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Check the tiles' shape before laying them out, a ragged board can't be
+        let unchecked = EditingModel {
+            board_size: (board.len(), board.first().map_or(0, Vec::len)),
+            board,
+            ..EditingModel::default()
+        };
+        unchecked.check_size()?;
+        Ok(EditingModel::from_board(unchecked.board))
+    }
+
+    pub fn save_board(&mut self, file: &str) -> Result<(), FoamError> {
+        write_atomically(Path::new(file), &self.to_json()?)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Whether the board has been edited since it was last saved or loaded
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Identifies the board as it is now, for results worked out from it to be kept until
+    /// it changes. Cheaper than `content_hash`, and not changed by the level name or next
+    /// level, which don't affect play or how the tiles look
+    pub fn get_revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn mark_edited(&mut self) {
+        self.dirty = true;
+        self.revision = next_revision();
+    }
+
+    /// Serialize the board wrapped in a versioned envelope
+    pub fn to_json(&self) -> Result<String, FoamError> {
+        Ok(serde_json::to_string(&BoardFile {
+            version: BOARD_FORMAT_VERSION,
+            board: self,
+        })?)
+    }
+
+    /// JSON Schema of the board files `to_json` writes, for external tools to validate them.
+    /// Derived from the same types and serde attributes, so it always matches the format
+    pub fn json_schema() -> Result<String, FoamError> {
+        let schema = schemars::schema_for!(BoardFile<'static>);
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+
+    /// Deserialize a board saved in any format version, migrating it to the current one
+    pub fn from_json(model_raw: &str) -> Result<Self, FoamError> {
+        let value: serde_json::Value = serde_json::from_str(model_raw)?;
+
+        // Version 0 is the bare model, without an envelope
+        let (version, mut board) = match value {
+            serde_json::Value::Object(mut envelope) if envelope.contains_key("version") => {
+                let version = envelope
+                    .get("version")
+                    .and_then(serde_json::Value::as_u64)
+                    .ok_or(FoamError::InvalidBoard("invalid version".to_string()))?;
+                let board = envelope
+                    .remove("board")
+                    .ok_or(FoamError::InvalidBoard("missing board".to_string()))?;
+                (version, board)
+            }
+            value => (0, value),
+        };
+
+        if version > BOARD_FORMAT_VERSION as u64 {
+            return Err(FoamError::InvalidBoard(format!(
+                "Board file version {version} is newer than supported version {BOARD_FORMAT_VERSION}"
+            )));
+        }
+        for version in version..BOARD_FORMAT_VERSION as u64 {
+            board = migrate_board(version, board)?;
+        }
+
+        let model: EditingModel = serde_json::from_value(board)?;
+        model.check_structure()?;
         Ok(model)
     }
 
-    pub fn save_board(&self, file: &str) -> Result<(), String> {
-        let model_data = serde_json::to_string(&self)
-            .map_err(|err| format!("Error serializing board data: {err}"))?;
-        std::fs::write(file, model_data)
-            .map_err(|err| format!("Error writing board file: {err}"))?;
+    /// Check that a deserialized board is consistent with itself: its size is within bounds
+    /// and matches its tiles, and its start and end are on the board on their tiles. A corrupt
+    /// file fails here instead of panicking later on an out of range position
+    fn check_structure(&self) -> Result<(), FoamError> {
+        self.check_size()?;
+        let (rows, cols) = self.board_size;
+        for (pos, tile, name) in [
+            (self.start_pos, Tile::StartSpace, "start"),
+            (self.end_pos, Tile::EndSpace, "end"),
+        ] {
+            let Some(pos) = pos else {
+                continue;
+            };
+            if pos.0 >= rows || pos.1 >= cols {
+                return Err(FoamError::InvalidBoard(format!(
+                    "{name} space is off the board"
+                )));
+            }
+            if self.board[pos.0][pos.1].tile != tile {
+                return Err(FoamError::InvalidBoard(format!(
+                    "{name} position isn't on the {name} space"
+                )));
+            }
+        }
         Ok(())
     }
 
-    pub fn board_is_playable(&mut self) -> bool {
-        if !(self.start_pos.is_some() && self.end_pos.is_some()) {
-            return false;
+    /// Check that the board size is within bounds and matches its tiles, see `check_structure`
+    pub fn check_size(&self) -> Result<(), FoamError> {
+        let (rows, cols) = self.board_size;
+        if rows == 0 || cols == 0 {
+            return Err(FoamError::InvalidBoard("board is empty".to_string()));
+        }
+        if rows > MAX_BOARD_SIDE || cols > MAX_BOARD_SIDE {
+            return Err(FoamError::InvalidBoard(format!(
+                "board size {rows}x{cols} is over the limit of {MAX_BOARD_SIDE}x{MAX_BOARD_SIDE}"
+            )));
         }
+        if self.board.len() != rows || self.board.iter().any(|row| row.len() != cols) {
+            return Err(FoamError::InvalidBoard(
+                "board size doesn't match its tiles".to_string(),
+            ));
+        }
+        Ok(())
+    }
 
-        let mut portal_positions = std::collections::HashMap::<char, Vec<(usize, usize)>>::new();
+    /// Export the board in a compact text format: one character per tile, followed by a
+    /// legend of `row,col field=value` lines for tile parameters and keys
+    pub fn to_ascii(&self) -> String {
+        let mut grid = String::new();
+        let mut legend = Vec::new();
+
+        if let Some(move_limit) = self.move_limit {
+            legend.push(format!("move_limit={move_limit}"));
+        }
+        if self.run_speed != DEFAULT_RUN_SPEED {
+            legend.push(format!("run_speed={}", self.run_speed));
+        }
+        if !self.name.is_empty() {
+            legend.push(format!("name={}", self.name));
+        }
+        if let Some(next_level) = &self.next_level {
+            legend.push(format!("next_level={next_level}"));
+        }
+        if let Some(background) = self.background.to_ascii() {
+            legend.push(format!("background={background}"));
+        }
+        if let Some(gravity) = self.gravity {
+            legend.push(format!("gravity={}", gravity.code()));
+        }
 
         for (row_idx, row) in self.board.iter().enumerate() {
-            for (col_idx, tile) in row.iter().enumerate() {
-                let TileData { tile, key: _ } = &tile;
+            for (col_idx, TileData { tile, key }) in row.iter().enumerate() {
+                grid.push(tile.ascii_char());
 
-                if !tile.is_valid() {
-                    return false; // Invalid tile found
+                let param = match tile {
+                    Tile::MoveCardinal(directions) | Tile::Cloud(directions) => Some(format!(
+                        "dirs={}",
+                        [
+                            (directions.up, "U"),
+                            (directions.right, "R"),
+                            (directions.down, "D"),
+                            (directions.left, "L"),
+                        ]
+                        .iter()
+                        .filter(|(allowed, _)| *allowed)
+                        .map(|(_, code)| *code)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                    )),
+                    Tile::MoveDiagonal(directions) => Some(format!(
+                        "dirs={}",
+                        [
+                            (directions.up_right, "UR"),
+                            (directions.down_right, "DR"),
+                            (directions.down_left, "DL"),
+                            (directions.up_left, "UL"),
+                        ]
+                        .iter()
+                        .filter(|(allowed, _)| *allowed)
+                        .map(|(_, code)| *code)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                    )),
+                    Tile::Bounce(amount) => Some(format!("bounce={amount}")),
+                    Tile::SpeedPad(speed) | Tile::SpeedGate(speed) => {
+                        Some(format!("speed={speed}"))
+                    }
+                    Tile::Switch(count) => Some(format!("count={count}")),
+                    Tile::Blinker(phase) => Some(format!("phase={}", *phase as u8)),
+                    Tile::Arrow(direction) => Some(format!("arrow={}", direction.code())),
+                    Tile::ResetTrap(resets_keys) => {
+                        Some(format!("reset_keys={}", *resets_keys as u8))
+                    }
+                    Tile::Portal(c, _) => Some(format!("portal={c}")),
+                    _ => None,
+                };
+                if let Some(param) = param {
+                    legend.push(format!("{row_idx},{col_idx} {param}"));
+                }
+                if *key != KeyItem::None {
+                    legend.push(format!("{row_idx},{col_idx} key={}", key.ascii_name()));
                 }
+            }
+            grid.push('\n');
+        }
 
-                if let Tile::Portal(c, _) = tile {
-                    portal_positions
-                        .entry(*c)
-                        .or_default()
-                        .push((row_idx, col_idx));
+        if !legend.is_empty() {
+            grid.push('\n');
+            grid.push_str(&legend.join("\n"));
+            grid.push('\n');
+        }
+
+        grid
+    }
+
+    /// Import a board from the text format produced by `to_ascii`
+    pub fn from_ascii(text: &str) -> Result<Self, String> {
+        let mut lines = text.trim_start().lines();
+
+        // Board grid, up to the first blank line
+        let mut board: Vec<Vec<TileData>> = Vec::new();
+        for line in lines.by_ref() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+
+            let row = line
+                .chars()
+                .map(|c| {
+                    Tile::from_ascii_char(c)
+                        .map(|tile| TileData {
+                            tile,
+                            key: KeyItem::None,
+                        })
+                        .ok_or(format!("Unknown tile character '{c}'"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(first) = board.first()
+                && first.len() != row.len()
+            {
+                return Err(format!(
+                    "Row {} has width {}, expected {}",
+                    board.len(),
+                    row.len(),
+                    first.len()
+                ));
+            }
+            board.push(row);
+        }
+
+        let mut model = EditingModel::new((board.len(), board.first().map_or(0, Vec::len)));
+        model.check_size().map_err(|err| err.to_string())?;
+
+        for (row_idx, row) in board.into_iter().enumerate() {
+            for (col_idx, tile_data) in row.into_iter().enumerate() {
+                match tile_data.tile {
+                    Tile::StartSpace if model.start_pos.is_some() => {
+                        return Err("Board has more than one start space".to_string());
+                    }
+                    Tile::EndSpace if model.end_pos.is_some() => {
+                        return Err("Board has more than one end space".to_string());
+                    }
+                    _ => {}
+                }
+                model.set_tile((row_idx, col_idx), tile_data.tile);
+            }
+        }
+
+        // Legend of tile parameters and keys
+        for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(move_limit) = line.strip_prefix("move_limit=") {
+                model.move_limit = Some(
+                    move_limit
+                        .parse()
+                        .map_err(|err| format!("Invalid move limit '{move_limit}': {err}"))?,
+                );
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("name=") {
+                model.name = name.to_string();
+                continue;
+            }
+            if let Some(next_level) = line.strip_prefix("next_level=") {
+                model.next_level = Some(next_level.to_string());
+                continue;
+            }
+            if let Some(gravity) = line.strip_prefix("gravity=") {
+                model.gravity = Some(
+                    DirectionKey::from_code(gravity)
+                        .ok_or(format!("Invalid gravity '{gravity}'"))?,
+                );
+                continue;
+            }
+            if let Some(background) = line.strip_prefix("background=") {
+                model.background = BackgroundStyle::from_ascii(background)
+                    .ok_or(format!("Invalid background '{background}'"))?;
+                continue;
+            }
+            if let Some(run_speed) = line.strip_prefix("run_speed=") {
+                model.run_speed = run_speed
+                    .parse()
+                    .ok()
+                    .filter(|run_speed| RUN_SPEED_RANGE.contains(run_speed))
+                    .ok_or(format!("Invalid run speed '{run_speed}'"))?;
+                continue;
+            }
+
+            let (pos, entry) = line
+                .split_once(' ')
+                .ok_or(format!("Invalid legend line '{line}'"))?;
+            let (row, col): (usize, usize) = pos
+                .split_once(',')
+                .and_then(|(row, col)| Some((row.parse().ok()?, col.parse().ok()?)))
+                .ok_or(format!("Invalid position '{pos}'"))?;
+            let (field, value) = entry
+                .split_once('=')
+                .ok_or(format!("Invalid legend entry '{entry}'"))?;
+
+            let tile_data = model
+                .board
+                .get_mut(row)
+                .and_then(|row| row.get_mut(col))
+                .ok_or(format!("Position {pos} is outside the board"))?;
+            let invalid = || format!("Invalid {field} '{value}' at {pos}");
+
+            match (field, &mut tile_data.tile) {
+                ("dirs", Tile::MoveCardinal(directions) | Tile::Cloud(directions)) => {
+                    *directions = CardinalDirectionsAllowed {
+                        up: false,
+                        right: false,
+                        down: false,
+                        left: false,
+                    };
+                    for code in value.split(',').filter(|code| !code.is_empty()) {
+                        match code {
+                            "U" => directions.up = true,
+                            "R" => directions.right = true,
+                            "D" => directions.down = true,
+                            "L" => directions.left = true,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                }
+                ("dirs", Tile::MoveDiagonal(directions)) => {
+                    *directions = DiagonalDirectionsAllowed {
+                        up_right: false,
+                        down_right: false,
+                        down_left: false,
+                        up_left: false,
+                    };
+                    for code in value.split(',').filter(|code| !code.is_empty()) {
+                        match code {
+                            "UR" => directions.up_right = true,
+                            "DR" => directions.down_right = true,
+                            "DL" => directions.down_left = true,
+                            "UL" => directions.up_left = true,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                }
+                ("bounce", Tile::Bounce(amount)) => {
+                    *amount = value.parse().map_err(|_| invalid())?;
+                }
+                ("speed", Tile::SpeedPad(speed) | Tile::SpeedGate(speed)) => {
+                    *speed = value.parse().map_err(|_| invalid())?;
+                }
+                ("count", Tile::Switch(count)) => {
+                    *count = value.parse().map_err(|_| invalid())?;
+                }
+                ("phase", Tile::Blinker(phase)) => {
+                    *phase = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    };
+                }
+                ("reset_keys", Tile::ResetTrap(resets_keys)) => {
+                    *resets_keys = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    };
+                }
+                ("arrow", Tile::Arrow(direction)) => {
+                    *direction = DirectionKey::from_code(value).ok_or_else(invalid)?;
+                }
+                ("portal", Tile::Portal(c, _)) => {
+                    *c = match value.chars().collect::<Vec<_>>()[..] {
+                        [c @ 'A'..='Z'] => c,
+                        _ => return Err(invalid()),
+                    };
+                }
+                ("key", tile) if *tile != Tile::Empty => {
+                    tile_data.key = KeyItem::from_ascii_name(value).ok_or_else(invalid)?;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        model.dirty = false; // Nothing edited yet
+        Ok(model)
+    }
+
+    /// Stable 64-bit hash of the board's content, for spotting duplicate levels. It covers the
+    /// tiles, keys and level settings through the ASCII export, so it doesn't depend on edit
+    /// history, and uses FNV-1a so it stays the same across builds
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        self.to_ascii()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// Every problem that keeps the board from being played
+    pub fn validation_report(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.start_pos.is_none() {
+            issues.push(ValidationIssue::MissingStart);
+        }
+        if self.end_pos.is_none() {
+            issues.push(ValidationIssue::MissingEnd);
+        }
+
+        for (row_idx, row) in self.board.iter().enumerate() {
+            for (col_idx, tile_data) in row.iter().enumerate() {
+                if !tile_data.tile.is_valid() {
+                    issues.push(ValidationIssue::InvalidTile((row_idx, col_idx)));
                 }
             }
 
@@ -66,21 +692,454 @@ impl EditingModel {
         }
 
         // Check that all portal letters appear exactly twice
-        for (_, positions) in portal_positions.iter() {
-            if positions.len() != 2 {
-                return false; // Portal letter appears more or less than twice
-            }
+        let mut unpaired_portals: Vec<char> = self
+            .portal_positions()
+            .into_iter()
+            .filter(|(_, positions)| positions.len() != 2)
+            .map(|(letter, _)| letter)
+            .collect();
+        unpaired_portals.sort();
+        issues.extend(
+            unpaired_portals
+                .into_iter()
+                .map(ValidationIssue::UnpairedPortal),
+        );
+
+        if let Some(end_pos) = self.end_pos
+            && self.start_pos.is_some()
+            && !self.reachable_tiles().contains(&end_pos)
+        {
+            issues.push(ValidationIssue::Unsolvable);
         }
 
-        // Verify that portals are properly linked to each other
-        for (letter, positions) in portal_positions.iter() {
-            self.board[positions[0].0][positions[0].1].tile = Tile::Portal(*letter, positions[1]); // Link first portal to second
-            self.board[positions[1].0][positions[1].1].tile = Tile::Portal(*letter, positions[0]); // Link second portal to first
+        issues
+    }
+
+    pub fn board_is_playable(&mut self) -> bool {
+        if !self.validation_report().is_empty() {
+            return false;
         }
 
+        self.link_portals();
         true
     }
 
+    /// Point each pair of portals at each other. Letters without exactly two portals are left
+    /// alone
+    fn link_portals(&mut self) {
+        for (letter, positions) in self.portal_positions() {
+            if let [first, second] = positions[..] {
+                self.board[first.0][first.1].tile = Tile::Portal(letter, second); // Link first portal to second
+                self.board[second.0][second.1].tile = Tile::Portal(letter, first); // Link second portal to first
+            }
+        }
+    }
+
+    /// Positions of every portal, by letter
+    fn portal_positions(&self) -> HashMap<char, Vec<(usize, usize)>> {
+        let mut portal_positions = HashMap::<char, Vec<(usize, usize)>>::new();
+        for (row_idx, row) in self.board.iter().enumerate() {
+            for (col_idx, tile_data) in row.iter().enumerate() {
+                if let Tile::Portal(c, _) = tile_data.tile {
+                    portal_positions
+                        .entry(c)
+                        .or_default()
+                        .push((row_idx, col_idx));
+                }
+            }
+        }
+        portal_positions
+    }
+
+    /// Tiles the player can stand on when starting from the start space. Keys count as held
+    /// for good once any tile holding them is reachable and clouds never disappear, so this
+    /// over-approximates what can be reached in a single playthrough.
+    pub fn reachable_tiles(&self) -> HashSet<(usize, usize)> {
+        let Some(start_pos) = self.start_pos else {
+            return HashSet::new();
+        };
+        self.reachable_tiles_from(start_pos, &HashSet::new())
+    }
+
+    /// Where a player standing on `from` ends up after one move, played out with the real game
+    /// rules. None if the move leaves the board
+    pub fn trace_move(
+        &self,
+        from: (usize, usize),
+        movement: PlayerMovementData,
+    ) -> Option<(usize, usize)> {
+        let mut model = self.clone();
+        model.link_portals();
+        model.start_pos = Some(from); // The player starts on the tile as it is, not a start space
+        let mut playing = PlayingModel::new(&model).ok()?;
+        playing.simulate(&[movement]);
+
+        // offset by 1 to account for padding
+        let (row, col) = playing.get_stopped_pos();
+        (row >= 1 && col >= 1 && row <= self.board_size.0 && col <= self.board_size.1)
+            .then(|| (row - 1, col - 1))
+    }
+
+    /// Play a sequence of moves from the start space with the real game rules. Returns the
+    /// tile each played move ended on, None once off the board, and how the last one ended.
+    /// Stops at the same moves `PlayingModel::simulate` does
+    pub fn simulate_moves(
+        &self,
+        moves: &[PlayerMovementData],
+    ) -> Result<(SimulatedPath, MovementPopupData), FoamError> {
+        let mut model = self.clone();
+        if !model.board_is_playable() {
+            return Err(FoamError::InvalidBoard(
+                "fix the board's problems before simulating".to_string(),
+            ));
+        }
+        let mut playing = PlayingModel::new(&model)?;
+        let mut path = Vec::new();
+        let mut result = MovementPopupData::None;
+        for movement in moves {
+            result = playing.simulate(std::slice::from_ref(movement));
+
+            // offset by 1 to account for padding
+            let (row, col) = playing.get_stopped_pos();
+            path.push(
+                (row >= 1 && col >= 1 && row <= self.board_size.0 && col <= self.board_size.1)
+                    .then(|| (row - 1, col - 1)),
+            );
+            if !matches!(result, MovementPopupData::None) {
+                break;
+            }
+        }
+        Ok((path, result))
+    }
+
+    /// Fewest moves needed to win the board, by playing it out with the real game rules.
+    /// None if the board can't be played, the end can't be reached, or finding out would
+    /// take exploring too many states
+    pub fn solution_length(&self) -> Option<usize> {
+        self.solution_stats().map(|stats| stats.moves)
+    }
+
+    /// Everything the solver found out about winning the board, see `solution_length`
+    pub fn solution_stats(&self) -> Option<SolverStats> {
+        let mut model = self.clone();
+        if !model.board_is_playable() {
+            return None;
+        }
+        PlayingModel::new(&model).ok()?.solve(MAX_SOLVER_STATES)
+    }
+
+    /// Tiles the player lands on in every fewest-move win, the chokepoints the level can't be
+    /// won without. Empty when the solver finds no win, see `solution_length`
+    pub fn critical_tiles(&self) -> HashSet<(usize, usize)> {
+        let mut model = self.clone();
+        if !model.board_is_playable() {
+            return HashSet::new();
+        }
+        let Some(tiles) = PlayingModel::new(&model)
+            .ok()
+            .and_then(|playing| playing.critical_tiles(MAX_SOLVER_STATES))
+        else {
+            return HashSet::new();
+        };
+
+        // offset by 1 to account for padding
+        tiles
+            .into_iter()
+            .filter(|&(row, col)| {
+                row >= 1 && col >= 1 && row <= self.board_size.0 && col <= self.board_size.1
+            })
+            .map(|(row, col)| (row - 1, col - 1))
+            .collect()
+    }
+
+    /// Automatic difficulty estimate from how the solver won the board, 0 if it couldn't.
+    /// The heuristic is documented on `SolverStats::difficulty_score`
+    pub fn difficulty_score(&self) -> u32 {
+        self.solution_stats()
+            .map_or(0, |stats| stats.difficulty_score())
+    }
+
+    /// Tiles the player can stand on when starting from a position already holding some keys,
+    /// with the same over-approximation as `reachable_tiles`
+    pub fn reachable_tiles_from(
+        &self,
+        start_pos: (usize, usize),
+        held_keys: &HashSet<KeyItem>,
+    ) -> HashSet<(usize, usize)> {
+        // Reachable tiles only grow as more keys are collected, so repeat until no new keys
+        let mut keys = held_keys.clone();
+        loop {
+            let reachable = self.reachable_tiles_with_keys(start_pos, &keys);
+            let collected: HashSet<KeyItem> = reachable
+                .iter()
+                .map(|&(row, col)| self.board[row][col].key.clone())
+                .filter(|key| *key != KeyItem::None)
+                .chain(held_keys.iter().cloned())
+                .collect();
+
+            if collected.len() == keys.len() {
+                return reachable;
+            }
+            keys = collected;
+        }
+    }
+
+    fn reachable_tiles_with_keys(
+        &self,
+        start_pos: (usize, usize),
+        keys: &HashSet<KeyItem>,
+    ) -> HashSet<(usize, usize)> {
+        let has_key = |key: KeyItem| keys.contains(&key);
+        let portal_positions = self.portal_positions();
+
+        // Search nodes are a position, plus the direction, speed and ice momentum while mid-slide
+        let mut reachable = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([(start_pos, None::<(DirectionKey, usize, bool)>)]);
+        let can_jump_walls = has_key(KeyItem::OnEquip(KeyOnEquip::OnWall(KeyOnWall::Wall)));
+        let can_open_checkpoints = has_key(KeyItem::OnGet(KeyOnGet::FinishKey));
+
+        while let Some(node) = queue.pop_front() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let (pos, slide) = node;
+            reachable.insert(pos);
+
+            let tile = &self.board[pos.0][pos.1].tile;
+            let moves = match slide {
+                Some(slide) => vec![slide],
+                None if *tile == Tile::EndSpace => continue, // Game is over
+                None => {
+                    // Gravity pulls the player one tile onto solid ground after a move
+                    if let Some(gravity) = self.gravity
+                        && let Some(pulled) = self.move_target(pos, gravity, 1)
+                        && match self.board[pulled.0][pulled.1].tile {
+                            Tile::Empty | Tile::Wall | Tile::Gap | Tile::Door | Tile::Block => {
+                                false
+                            }
+                            Tile::Checkpoint => can_open_checkpoints,
+                            Tile::SpeedGate(speed) => speed == 1,
+                            _ => true,
+                        }
+                    {
+                        queue.push_back((pulled, None));
+                    }
+                    if let Tile::Portal(c, _) = tile {
+                        queue.extend(portal_positions[c].iter().map(|&portal| (portal, None)));
+                    }
+                    for (&c, positions) in portal_positions.iter() {
+                        if has_key(KeyItem::OnUse(KeyOnUse::TeleportKey(c))) {
+                            queue.extend(positions.iter().map(|&portal| (portal, None)));
+                        }
+                    }
+
+                    ALL_DIRECTIONS
+                        .iter()
+                        .filter(|direction| {
+                            tile.can_move_in_direction_with_keys(direction, has_key)
+                        })
+                        .flat_map(|&direction| {
+                            [(direction, 1, false), (direction, self.run_speed, false)]
+                        })
+                        .collect()
+                }
+            };
+
+            for (direction, speed, momentum) in moves {
+                let Some(landing) = self.move_target(pos, direction, speed) else {
+                    continue; // Left the board
+                };
+
+                if self.board[landing.0][landing.1].tile == Tile::Wall
+                    || (self.wall_between(pos, landing) && !can_jump_walls)
+                {
+                    // A slide stops in front of the wall. Bounces are treated like reversed
+                    // moves here, which may stop rather than hitting the wall in play
+                    if momentum || slide.is_some() {
+                        queue.push_back((pos, None));
+                    }
+                    continue;
+                }
+                if (self.board[landing.0][landing.1].tile == Tile::Checkpoint
+                    && !can_open_checkpoints)
+                    || self.gap_blocks(pos, landing, speed)
+                    || self.speed_gate_blocks(pos, landing, speed)
+                {
+                    queue.push_back((pos, None)); // Blocked, stays where it is
+                    continue;
+                }
+
+                match self.board[landing.0][landing.1].tile {
+                    Tile::Bounce(amount) => {
+                        let mut amounts = vec![amount];
+                        if has_key(KeyItem::OnEquip(KeyOnEquip::OnBounce(
+                            KeyOnBounce::BounceLess,
+                        ))) {
+                            amounts.push(amount - 1);
+                        }
+                        if has_key(KeyItem::OnEquip(KeyOnEquip::OnBounce(
+                            KeyOnBounce::BounceMore,
+                        ))) {
+                            amounts.push(amount + 1);
+                        }
+
+                        let mut directions = vec![direction];
+                        if has_key(KeyItem::OnEquip(KeyOnEquip::OnBounce(
+                            KeyOnBounce::BounceChange,
+                        ))) {
+                            directions.push(direction.reversed());
+                        }
+
+                        for &amount in &amounts {
+                            for &direction in &directions {
+                                let speed = speed.checked_add_signed(amount).unwrap_or(0);
+                                queue.push_back((
+                                    landing,
+                                    (speed > 0).then_some((direction, speed, false)),
+                                ));
+                            }
+                        }
+                    }
+                    Tile::SpeedPad(speed) => {
+                        queue
+                            .push_back((landing, (speed > 0).then_some((direction, speed, false))));
+                    }
+                    Tile::Ice => queue.push_back((landing, Some((direction, 1, true)))),
+                    Tile::Arrow(arrow) => {
+                        queue.push_back((landing, Some((arrow, 1, false))));
+                        queue.push_back((landing, None)); // Stopped after too many pushes
+                    }
+                    Tile::Reverse => {
+                        queue.push_back((landing, Some((direction.reversed(), speed, momentum))));
+                        queue.push_back((landing, None)); // Stopped after too many reversals
+                    }
+                    Tile::ResetTrap(_) => {
+                        // Sent back to the start, keys already collected count as kept
+                        reachable.insert(landing);
+                        if let Some(start_pos) = self.start_pos {
+                            queue.push_back((start_pos, None));
+                        }
+                    }
+                    Tile::Empty if momentum => {
+                        queue.push_back((landing, Some((direction, speed, true))));
+                    }
+                    Tile::Empty => {
+                        if has_key(KeyItem::OnEquip(KeyOnEquip::OnEmpty(KeyOnEmpty::CloudKey))) {
+                            queue.push_back((landing, None));
+                        }
+                    }
+                    _ => queue.push_back((landing, None)),
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Where a single movement step lands, or None if it leaves the board
+    fn move_target(
+        &self,
+        pos: (usize, usize),
+        direction: DirectionKey,
+        speed: usize,
+    ) -> Option<(usize, usize)> {
+        let (row_offset, col_offset) = direction.offset();
+        let target = (
+            pos.0.checked_add_signed(row_offset * speed as isize)?,
+            pos.1.checked_add_signed(col_offset * speed as isize)?,
+        );
+        (target.0 < self.board_size.0 && target.1 < self.board_size.1).then_some(target)
+    }
+
+    /// Same wall check as in play: any wall in the rectangle between two positions
+    fn wall_between(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        (from.0.min(to.0)..=from.0.max(to.0)).any(|row| {
+            (from.1.min(to.1)..=from.1.max(to.1)).any(|col| self.board[row][col].tile == Tile::Wall)
+        })
+    }
+
+    /// Same gap check as in play: a gap in the rectangle stops anything but a run, and a
+    /// run can't land in one
+    fn gap_blocks(&self, from: (usize, usize), to: (usize, usize), speed: usize) -> bool {
+        (from.0.min(to.0)..=from.0.max(to.0)).any(|row| {
+            (from.1.min(to.1)..=from.1.max(to.1)).any(|col| {
+                self.board[row][col].tile == Tile::Gap
+                    && (row, col) != from
+                    && (speed < 2 || (row, col) == to)
+            })
+        })
+    }
+
+    /// Same speed gate check as in play: a gate in the rectangle stops anything not moving at
+    /// exactly its speed
+    fn speed_gate_blocks(&self, from: (usize, usize), to: (usize, usize), speed: usize) -> bool {
+        (from.0.min(to.0)..=from.0.max(to.0)).any(|row| {
+            (from.1.min(to.1)..=from.1.max(to.1)).any(|col| {
+                matches!(self.board[row][col].tile, Tile::SpeedGate(gate) if gate != speed)
+                    && (row, col) != from
+            })
+        })
+    }
+
+    /// Directions a slide can carry the player over each ice tile, found by walking back along
+    /// the ice run to the tile the player would step onto the ice from
+    pub fn ice_momentum(&self) -> IceMomentum {
+        let mut momentum = HashMap::new();
+        for (row_idx, row) in self.board.iter().enumerate() {
+            for (col_idx, tile_data) in row.iter().enumerate() {
+                if tile_data.tile != Tile::Ice {
+                    continue;
+                }
+
+                // Cardinal directions come first, in mask order
+                let mut mask = 0;
+                for (bit, direction) in ALL_DIRECTIONS
+                    .iter()
+                    .filter(|direction| direction.is_cardinal())
+                    .enumerate()
+                {
+                    let mut pos = (row_idx, col_idx);
+                    while let Some(behind) = self.move_target(pos, direction.reversed(), 1) {
+                        let tile = &self.board[behind.0][behind.1].tile;
+                        if *tile != Tile::Ice {
+                            if !matches!(tile, Tile::Empty | Tile::Wall | Tile::Gap)
+                                && tile.can_move_in_direction(direction)
+                            {
+                                mask |= 1 << bit;
+                            }
+                            break;
+                        }
+                        pos = behind;
+                    }
+                }
+                momentum.insert(
+                    (row_idx, col_idx),
+                    CardinalDirectionsAllowed::from_mask(mask),
+                );
+            }
+        }
+        momentum
+    }
+
+    /// Reset every tile to empty, keeping the board size
+    pub fn clear(&mut self) {
+        self.mark_edited();
+        for tile_data in self.board.iter_mut().flatten() {
+            *tile_data = TileData::empty();
+        }
+        self.start_pos = None;
+        self.end_pos = None;
+    }
+
+    /// Remove every key, keeping the tiles
+    pub fn clear_keys(&mut self) {
+        self.mark_edited();
+        for tile_data in self.board.iter_mut().flatten() {
+            tile_data.key = KeyItem::None;
+        }
+    }
+
     pub fn get_board_size(&self) -> (usize, usize) {
         self.board_size
     }
@@ -93,7 +1152,83 @@ impl EditingModel {
         self.start_pos
     }
 
+    pub fn get_end_pos(&self) -> Option<(usize, usize)> {
+        self.end_pos
+    }
+
+    /// Number of tiles of each variant on the board, keyed by `Tile::label`
+    pub fn tile_counts(&self) -> HashMap<&'static str, usize> {
+        let mut counts = HashMap::new();
+        for tile_data in self.board.iter().flatten() {
+            *counts.entry(tile_data.tile.label()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn get_move_limit(&self) -> Option<usize> {
+        self.move_limit
+    }
+
+    pub fn set_move_limit(&mut self, move_limit: Option<usize>) {
+        self.mark_edited();
+        self.move_limit = move_limit;
+    }
+
+    pub fn get_run_speed(&self) -> usize {
+        self.run_speed
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.dirty = true; // The board itself is unchanged, see `get_revision`
+        self.name = name;
+    }
+
+    pub fn get_next_level(&self) -> Option<&str> {
+        self.next_level.as_deref()
+    }
+
+    pub fn set_next_level(&mut self, next_level: Option<String>) {
+        self.dirty = true; // The board itself is unchanged, see `get_revision`
+        self.next_level = next_level;
+    }
+
+    pub fn get_background(&self) -> BackgroundStyle {
+        self.background
+    }
+
+    pub fn set_background(&mut self, background: BackgroundStyle) {
+        self.mark_edited();
+        self.background = background;
+    }
+
+    pub fn get_gravity(&self) -> Option<DirectionKey> {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Option<DirectionKey>) {
+        self.mark_edited();
+        self.gravity = gravity;
+    }
+
+    pub fn set_run_speed(&mut self, run_speed: usize) {
+        self.mark_edited();
+        self.run_speed = run_speed.clamp(*RUN_SPEED_RANGE.start(), *RUN_SPEED_RANGE.end());
+    }
+
     pub fn set_tile(&mut self, pos: (usize, usize), tile: Tile) {
+        self.mark_edited();
+        // Overwriting the start or end space removes it
+        if self.start_pos == Some(pos) && tile != Tile::StartSpace {
+            self.start_pos = None;
+        }
+        if self.end_pos == Some(pos) && tile != Tile::EndSpace {
+            self.end_pos = None;
+        }
+
         if matches!(tile, Tile::StartSpace) {
             if let Some(old) = self.start_pos.take() {
                 self.board[old.0][old.1].tile = Tile::Empty; // Remove old start tile
@@ -109,7 +1244,95 @@ impl EditingModel {
         self.board[pos.0][pos.1].tile = tile;
     }
 
+    /// Positions mirroring `pos` across the symmetry axes, with whether each is flipped left to
+    /// right and top to bottom. Mirrors landing back on `pos` (or on each other) on the center
+    /// row or column are left out
+    pub fn mirrored_positions(
+        &self,
+        pos: (usize, usize),
+        symmetry: Symmetry,
+    ) -> Vec<((usize, usize), bool, bool)> {
+        let flipped_row = self.board_size.0 - 1 - pos.0;
+        let flipped_col = self.board_size.1 - 1 - pos.1;
+        let flips: &[(bool, bool)] = match symmetry {
+            Symmetry::None => &[],
+            Symmetry::Horizontal => &[(true, false)],
+            Symmetry::Vertical => &[(false, true)],
+            Symmetry::Both => &[(true, false), (false, true), (true, true)],
+        };
+
+        let mut positions: Vec<((usize, usize), bool, bool)> = Vec::new();
+        for &(flip_h, flip_v) in flips {
+            let mirror = (
+                if flip_v { flipped_row } else { pos.0 },
+                if flip_h { flipped_col } else { pos.1 },
+            );
+            if mirror != pos && !positions.iter().any(|(other, ..)| *other == mirror) {
+                positions.push((mirror, flip_h, flip_v));
+            }
+        }
+        positions
+    }
+
+    /// Set a tile and its mirror images, with directional tiles mirrored to match. Start and
+    /// end spaces are unique, so they are only set at `pos`
+    pub fn set_tile_mirrored(&mut self, pos: (usize, usize), tile: Tile, symmetry: Symmetry) {
+        if !matches!(tile, Tile::StartSpace | Tile::EndSpace) {
+            for (mirror, flip_h, flip_v) in self.mirrored_positions(pos, symmetry) {
+                let mut mirrored = tile.clone();
+                if flip_h {
+                    mirrored.flip_horizontal();
+                }
+                if flip_v {
+                    mirrored.flip_vertical();
+                }
+                self.set_tile(mirror, mirrored);
+            }
+        }
+        self.set_tile(pos, tile);
+    }
+
+    /// Set both the tile and the key at a position. Keys are left off empty tiles
+    pub fn set_tile_data(&mut self, pos: (usize, usize), tile_data: TileData) {
+        self.set_tile(pos, tile_data.tile);
+        self.set_key(pos, tile_data.key);
+    }
+
+    /// Set a tile and key together at a position and its mirror images
+    pub fn set_tile_data_mirrored(
+        &mut self,
+        pos: (usize, usize),
+        tile_data: TileData,
+        symmetry: Symmetry,
+    ) {
+        self.set_tile_mirrored(pos, tile_data.tile.clone(), symmetry);
+        self.set_key_mirrored(pos, tile_data.key, symmetry);
+    }
+
+    /// Clear both the tile and the key at a position
+    pub fn erase_tile(&mut self, pos: (usize, usize)) {
+        self.set_key(pos, KeyItem::None); // Keys can only be set on non-empty tiles
+        self.set_tile(pos, Tile::Empty);
+    }
+
+    /// Erase a position and its mirror images
+    pub fn erase_tile_mirrored(&mut self, pos: (usize, usize), symmetry: Symmetry) {
+        for (mirror, ..) in self.mirrored_positions(pos, symmetry) {
+            self.erase_tile(mirror);
+        }
+        self.erase_tile(pos);
+    }
+
+    /// Set a key and its mirror images
+    pub fn set_key_mirrored(&mut self, pos: (usize, usize), key: KeyItem, symmetry: Symmetry) {
+        for (mirror, ..) in self.mirrored_positions(pos, symmetry) {
+            self.set_key(mirror, key.clone());
+        }
+        self.set_key(pos, key);
+    }
+
     pub fn set_key(&mut self, pos: (usize, usize), key: KeyItem) {
+        self.mark_edited();
         if let Some(tile_data) = self.board.get_mut(pos.0).and_then(|row| row.get_mut(pos.1))
             && tile_data.tile != Tile::Empty
         {
@@ -117,7 +1340,79 @@ impl EditingModel {
         }
     }
 
+    /// Copy of the rectangle of tiles between two corners, in any order, rows then columns
+    pub fn copy_region(&self, start: (usize, usize), end: (usize, usize)) -> Vec<Vec<TileData>> {
+        // A stale selection can lie wholly past the board, e.g. after loading a smaller one
+        if start.0.min(end.0) >= self.board_size.0 || start.1.min(end.1) >= self.board_size.1 {
+            return Vec::new();
+        }
+        let rows = start.0.min(end.0)..=start.0.max(end.0).min(self.board_size.0 - 1);
+        let cols = start.1.min(end.1)..=start.1.max(end.1).min(self.board_size.1 - 1);
+        self.board[rows]
+            .iter()
+            .map(|row| row[cols.clone()].to_vec())
+            .collect()
+    }
+
+    /// Stamp a copied region with its top left corner at a position. Anything past the board
+    /// edge is clipped, and start/end spaces are skipped if the board already has one elsewhere
+    pub fn paste_region(&mut self, pos: (usize, usize), region: &[Vec<TileData>]) {
+        for (row_offset, row) in region.iter().enumerate() {
+            for (col_offset, tile_data) in row.iter().enumerate() {
+                let target = (pos.0 + row_offset, pos.1 + col_offset);
+                if target.0 >= self.board_size.0 || target.1 >= self.board_size.1 {
+                    continue;
+                }
+
+                let duplicate = match tile_data.tile {
+                    Tile::StartSpace => self.start_pos.is_some_and(|start| start != target),
+                    Tile::EndSpace => self.end_pos.is_some_and(|end| end != target),
+                    _ => false,
+                };
+                if duplicate {
+                    continue;
+                }
+
+                self.set_tile_data(target, tile_data.clone());
+            }
+        }
+    }
+
+    /// Mirror the whole board left to right. Portal links are redone by `board_is_playable`
+    pub fn flip_horizontal(&mut self) {
+        self.mark_edited();
+        for row in self.board.iter_mut() {
+            row.reverse();
+            for tile_data in row.iter_mut() {
+                tile_data.tile.flip_horizontal();
+            }
+        }
+        let flip = |(row, col): (usize, usize)| (row, self.board_size.1 - 1 - col);
+        self.start_pos = self.start_pos.map(flip);
+        self.end_pos = self.end_pos.map(flip);
+    }
+
+    /// Mirror the whole board top to bottom. Portal links are redone by `board_is_playable`
+    pub fn flip_vertical(&mut self) {
+        self.mark_edited();
+        self.board.reverse();
+        for tile_data in self.board.iter_mut().flatten() {
+            tile_data.tile.flip_vertical();
+        }
+        let flip = |(row, col): (usize, usize)| (self.board_size.0 - 1 - row, col);
+        self.start_pos = self.start_pos.map(flip);
+        self.end_pos = self.end_pos.map(flip);
+    }
+
+    pub fn rotate_tile(&mut self, pos: (usize, usize)) {
+        self.mark_edited();
+        if let Some(tile_data) = self.board.get_mut(pos.0).and_then(|row| row.get_mut(pos.1)) {
+            tile_data.tile.rotate_cw();
+        }
+    }
+
     pub fn edit_tile(&mut self, pos: (usize, usize), keypress: &PlayerMovementData) {
+        self.mark_edited();
         let (key_up, key_right, key_down, key_left) =
             game_ui::direction_key_into_bools(&keypress.direction);
         if let Some(tile_data) = self.board.get_mut(pos.0).and_then(|row| row.get_mut(pos.1)) {
@@ -165,29 +1460,661 @@ impl EditingModel {
                     }
                 }
                 Tile::Bounce(val) => {
-                    if key_up && *val < 1 {
+                    if key_up && *val < *BOUNCE_RANGE.end() {
                         *val += 1;
-                    } else if key_down && *val > -1 {
+                    } else if key_down && *val > *BOUNCE_RANGE.start() {
                         *val -= 1;
                     }
                 }
-                Tile::Portal(c, _) => {
-                    if key_up {
-                        *c = match *c {
-                            'A'..='Y' => (*c as u8 + 1) as char,
-                            'Z' => 'A',
-                            _ => 'A',
-                        };
-                    } else if key_down {
-                        *c = match *c {
-                            'B'..='Z' => (*c as u8 - 1) as char,
-                            'A' => 'Z',
-                            _ => 'Z',
-                        };
+                Tile::SpeedPad(speed) => {
+                    if key_up && *speed < *SPEED_PAD_RANGE.end() {
+                        *speed += 1;
+                    } else if key_down && *speed > *SPEED_PAD_RANGE.start() {
+                        *speed -= 1;
+                    }
+                }
+                Tile::SpeedGate(speed) => {
+                    if key_up && *speed < *SPEED_GATE_RANGE.end() {
+                        *speed += 1;
+                    } else if key_down && *speed > *SPEED_GATE_RANGE.start() {
+                        *speed -= 1;
+                    }
+                }
+                Tile::Blinker(phase) if key_up => *phase = !*phase,
+                Tile::ResetTrap(resets_keys) if key_up => *resets_keys = !*resets_keys,
+                Tile::Arrow(direction) if !keypress.direction.is_none() => {
+                    *direction = keypress.direction;
+                }
+                Tile::Switch(count) => {
+                    if key_up && *count < *SWITCH_RANGE.end() {
+                        *count += 1;
+                    } else if key_down && *count > *SWITCH_RANGE.start() {
+                        *count -= 1;
                     }
                 }
+                Tile::Portal(c, _) if key_up || key_down => *c = step_letter(*c, key_up),
                 _ => {}
             }
         }
     }
 }
+
+/// Write a file through a temporary file next to it, renamed into place once fully written,
+/// so a crash mid-write never leaves a half-written file where a good one used to be
+pub fn write_atomically(path: &Path, contents: &str) -> Result<(), FoamError> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    if let Err(err) = std::fs::write(&temp_path, contents) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err.into());
+    }
+    if let Err(err) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_round_trip() {
+        let text = "#####\n#S.E#\n#..##\n#####\n";
+        let model = EditingModel::from_ascii(text).unwrap();
+
+        assert_eq!(model.get_board_size(), (4, 5));
+        assert_eq!(model.get_start_pos(), Some((1, 1)));
+        assert_eq!(model.end_pos, Some((1, 3)));
+        assert_eq!(model.get_board()[0][0].tile, Tile::Wall);
+        assert_eq!(model.get_board()[2][2].tile, Tile::Empty);
+        assert_eq!(model.to_ascii(), text);
+    }
+
+    #[test]
+    fn ascii_round_trip_parametric() {
+        let text = "SCXOBPPE\n\n\
+                    move_limit=12\n\
+                    run_speed=3\n\
+                    name=Tutorial 1\n\
+                    next_level=tutorial_2.fg\n\
+                    background=#1b1b1b,#30a0ff\n\
+                    gravity=D\n\
+                    0,1 dirs=U,L\n\
+                    0,2 dirs=DR\n\
+                    0,3 dirs=R\n\
+                    0,3 key=door:Q\n\
+                    0,4 bounce=-1\n\
+                    0,5 portal=Q\n\
+                    0,6 portal=Q\n\
+                    0,6 key=bounce_change\n";
+        let model = EditingModel::from_ascii(text).unwrap();
+
+        assert_eq!(model.get_move_limit(), Some(12));
+        assert_eq!(model.get_run_speed(), 3);
+        assert_eq!(model.get_name(), "Tutorial 1");
+        assert_eq!(model.get_next_level(), Some("tutorial_2.fg"));
+        assert_eq!(model.get_gravity(), Some(DirectionKey::Down));
+        assert_eq!(
+            model.get_background(),
+            BackgroundStyle::Checkerboard([0x1b, 0x1b, 0x1b], [0x30, 0xa0, 0xff])
+        );
+        assert_eq!(model.get_board()[0][4].tile, Tile::Bounce(-1));
+        assert_eq!(model.to_ascii(), text);
+    }
+
+    #[test]
+    fn critical_tiles_are_shared_by_every_shortest_win() {
+        // Equally short wins over the top and bottom rows meet at the middle of the board
+        let model = EditingModel::from_ascii("CCC..\nS.CCE\nCCC..\n").unwrap();
+        assert_eq!(
+            model.critical_tiles(),
+            HashSet::from([(1, 0), (1, 2), (1, 4)])
+        );
+
+        assert!(
+            EditingModel::from_ascii("SC#E\n")
+                .unwrap()
+                .critical_tiles()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn reachable_tiles_follow_movement() {
+        let model = EditingModel::from_ascii("SC#CE\n.C...\n").unwrap();
+        let reachable = model.reachable_tiles();
+
+        assert!(reachable.contains(&(0, 1)));
+        assert!(reachable.contains(&(1, 1)));
+        assert!(!reachable.contains(&(0, 3)));
+        assert!(!reachable.contains(&(0, 4)));
+    }
+
+    #[test]
+    fn reachable_tiles_with_collected_key() {
+        let model = EditingModel::from_ascii("SC#CE\n.C...\n\n1,1 key=wall\n").unwrap();
+        let reachable = model.reachable_tiles();
+
+        assert!(reachable.contains(&(0, 3)));
+        assert!(reachable.contains(&(0, 4)));
+    }
+
+    #[test]
+    fn reachable_tiles_slide_over_empty_on_ice() {
+        let model = EditingModel::from_ascii("S~..C#E\n").unwrap();
+        let reachable = model.reachable_tiles();
+
+        assert!(reachable.contains(&(0, 4)));
+        assert!(!reachable.contains(&(0, 6)));
+    }
+
+    #[test]
+    fn checkerboard_background_alternates() {
+        let background = BackgroundStyle::Checkerboard([0, 0, 0], [255, 255, 255]);
+        assert_eq!(background.color_at((0, 0)), Some(egui::Color32::BLACK));
+        assert_eq!(background.color_at((0, 1)), Some(egui::Color32::WHITE));
+        assert_eq!(background.color_at((1, 1)), Some(egui::Color32::BLACK));
+        assert_eq!(BackgroundStyle::Texture.color_at((0, 0)), None);
+        assert!(EditingModel::from_ascii("SE\n\nbackground=#12345\n").is_err());
+    }
+
+    #[test]
+    fn trace_matches_played_move() {
+        let mut model = EditingModel::from_ascii("S~~C#\nPCB.P\n.C.CE\n").unwrap();
+        assert!(model.board_is_playable());
+        let right = PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 1,
+            use_tile: false,
+        };
+
+        // From the start it matches playing the move for real
+        let mut playing = PlayingModel::new(&model).unwrap();
+        playing.simulate(&[right]);
+        let played = playing.get_player_pos();
+        assert_eq!(
+            model.trace_move((0, 0), right),
+            Some((played.0 - 1, played.1 - 1))
+        );
+        assert_eq!(model.trace_move((0, 0), right), Some((0, 3))); // slides over the ice
+
+        // Traces can start anywhere: over a bounce tile onto empty space, onto the end, off
+        // the board, and through a portal
+        assert_eq!(model.trace_move((1, 1), right), Some((1, 3)));
+        assert_eq!(model.trace_move((2, 3), right), Some((2, 4)));
+        assert_eq!(model.trace_move((1, 4), right), None);
+        let use_portal = PlayerMovementData {
+            direction: DirectionKey::None,
+            move_speed: 1,
+            use_tile: true,
+        };
+        assert_eq!(model.trace_move((1, 0), use_portal), Some((1, 4)));
+
+        // A wall stops the player where they stood, whether walking into it or running past it
+        let model = EditingModel::from_ascii("SC#CE\n.CCC.\n").unwrap();
+        assert_eq!(model.trace_move((0, 1), right), Some((0, 1)));
+        let run = PlayerMovementData {
+            move_speed: 2,
+            ..right
+        };
+        assert_eq!(model.trace_move((0, 1), run), Some((0, 1)));
+        let (path, result) = model.simulate_moves(&[right, right]).unwrap();
+        assert_eq!(path, vec![Some((0, 1)), Some((0, 1))]);
+        assert!(matches!(result, MovementPopupData::Wall));
+    }
+
+    #[test]
+    fn harder_levels_score_higher() {
+        let straight = EditingModel::from_ascii("SE\n").unwrap();
+        let winding =
+            EditingModel::from_ascii("SCCCC\n####C\nCCCCC\nC####\nCCCCE\n\n0,4 key=finish\n")
+                .unwrap();
+        assert!(straight.difficulty_score() > 0);
+        assert!(winding.difficulty_score() > straight.difficulty_score());
+
+        let unsolvable = EditingModel::from_ascii("SC#CE\n").unwrap();
+        assert_eq!(unsolvable.difficulty_score(), 0);
+    }
+
+    #[test]
+    fn solution_length_finds_fewest_moves() {
+        let model = EditingModel::from_ascii("SCCCCE\n").unwrap();
+        assert_eq!(model.solution_length(), Some(3)); // run, run, walk
+
+        let model = EditingModel::from_ascii("SC#CE\n").unwrap();
+        assert_eq!(model.solution_length(), None);
+    }
+
+    #[test]
+    fn ice_momentum_follows_entry_tiles() {
+        let model = EditingModel::from_ascii("S~~#\n.~..\n").unwrap();
+        let momentum = model.ice_momentum();
+
+        assert_eq!(
+            momentum[&(0, 1)],
+            CardinalDirectionsAllowed::from_mask(0b0010)
+        );
+        assert_eq!(
+            momentum[&(0, 2)],
+            CardinalDirectionsAllowed::from_mask(0b0010)
+        );
+        assert_eq!(momentum[&(1, 1)], CardinalDirectionsAllowed::from_mask(0));
+    }
+
+    #[test]
+    fn flip_horizontal_mirrors_directions() {
+        let mut model = EditingModel::from_ascii("SC.\n.XE\n\n0,1 dirs=L\n1,1 dirs=UL\n").unwrap();
+        model.flip_horizontal();
+
+        assert_eq!(model.to_ascii(), ".CS\nEX.\n\n0,1 dirs=R\n1,1 dirs=UR\n");
+        assert_eq!(model.get_start_pos(), Some((0, 2)));
+        assert_eq!(model.get_end_pos(), Some((1, 0)));
+    }
+
+    #[test]
+    fn flip_vertical_mirrors_directions() {
+        let mut model =
+            EditingModel::from_ascii("SC.\n.XE\n\n0,1 dirs=U,L\n1,1 dirs=UL\n").unwrap();
+        model.flip_vertical();
+
+        assert_eq!(model.to_ascii(), ".XE\nSC.\n\n0,1 dirs=DL\n1,1 dirs=D,L\n");
+        assert_eq!(model.get_start_pos(), Some((1, 0)));
+        assert_eq!(model.get_end_pos(), Some((0, 2)));
+    }
+
+    #[test]
+    fn mirrored_edits_flip_directions() {
+        let mut model = EditingModel::from_ascii("C...\n....\n....\n\n0,0 dirs=U,L\n").unwrap();
+        let tile = model.get_board()[0][0].tile.clone();
+        model.set_tile_mirrored((0, 0), tile, Symmetry::Both);
+
+        assert_eq!(
+            model.to_ascii(),
+            "C..C\n....\nC..C\n\n0,0 dirs=U,L\n0,3 dirs=U,R\n2,0 dirs=D,L\n2,3 dirs=R,D\n"
+        );
+    }
+
+    #[test]
+    fn mirrored_edits_skip_center_and_unique_tiles() {
+        let model = EditingModel::new((3, 3));
+        assert!(model.mirrored_positions((1, 1), Symmetry::Both).is_empty());
+        assert_eq!(
+            model.mirrored_positions((1, 0), Symmetry::Both),
+            vec![((1, 2), true, false)]
+        );
+
+        let mut model = EditingModel::new((1, 3));
+        model.set_tile_mirrored((0, 0), Tile::StartSpace, Symmetry::Horizontal);
+        assert_eq!(model.get_start_pos(), Some((0, 0)));
+        assert_eq!(model.get_board()[0][2].tile, Tile::Empty);
+    }
+
+    #[test]
+    fn set_tile_data_places_tile_and_key() {
+        let mut model = EditingModel::new((1, 2));
+        let door = TileData {
+            tile: Tile::Door,
+            key: KeyItem::OnGet(KeyOnGet::FinishKey),
+        };
+        model.set_tile_data((0, 0), door.clone());
+        assert_eq!(model.get_board()[0][0], door);
+
+        // Keys can't go on empty tiles
+        model.set_tile_data(
+            (0, 1),
+            TileData {
+                tile: Tile::Empty,
+                key: KeyItem::OnGet(KeyOnGet::FinishKey),
+            },
+        );
+        assert_eq!(model.get_board()[0][1], TileData::empty());
+    }
+
+    #[test]
+    fn erase_clears_tile_and_key() {
+        let mut model = EditingModel::from_ascii("SCE\n\n0,1 key=finish\n").unwrap();
+        model.erase_tile((0, 1));
+
+        assert_eq!(model.get_board()[0][1], TileData::empty());
+        model.erase_tile((0, 0));
+        assert_eq!(model.get_start_pos(), None);
+    }
+
+    #[test]
+    fn tile_counts_by_variant() {
+        let model = EditingModel::from_ascii("SBB\n#.E\n\n0,2 bounce=-1\n").unwrap();
+        let counts = model.tile_counts();
+
+        assert_eq!(counts["Bounce"], 2);
+        assert_eq!(counts["Start"], 1);
+        assert_eq!(counts["Empty"], 1);
+        assert_eq!(counts.get("Ice"), None);
+        assert_eq!(counts.values().sum::<usize>(), 6);
+    }
+
+    #[test]
+    fn edits_mark_board_dirty_until_saved() {
+        let mut model = EditingModel::from_ascii("S.E\n").unwrap();
+        assert!(!model.is_dirty());
+
+        model.set_tile((0, 1), Tile::Wall);
+        assert!(model.is_dirty());
+
+        let file = std::env::temp_dir().join("foam_game_dirty_test.fg");
+        model.save_board(file.to_str().unwrap()).unwrap();
+        assert!(!model.is_dirty());
+
+        let loaded = EditingModel::load_board(file.to_str().unwrap()).unwrap();
+        std::fs::remove_file(file).unwrap();
+        assert!(!loaded.is_dirty());
+    }
+
+    #[test]
+    fn clear_keeps_board_size() {
+        let mut model = EditingModel::from_ascii("S.C\n#.E\n\n0,2 key=wall\n").unwrap();
+
+        model.clear_keys();
+        assert_eq!(
+            model.get_board()[0][2].tile,
+            Tile::MoveCardinal(CardinalDirectionsAllowed {
+                up: true,
+                right: true,
+                down: true,
+                left: true
+            })
+        );
+        assert_eq!(model.get_board()[0][2].key, KeyItem::None);
+
+        model.clear();
+        assert_eq!(model.get_board_size(), (2, 3));
+        assert_eq!(model.get_start_pos(), None);
+        assert!(
+            model
+                .get_board()
+                .iter()
+                .flatten()
+                .all(|tile_data| *tile_data == TileData::empty())
+        );
+    }
+
+    #[test]
+    fn paste_region_clips_and_keeps_unique_start() {
+        let mut model = EditingModel::from_ascii("SC.\n.#E\n...\n\n0,1 key=wall\n").unwrap();
+        let region = model.copy_region((1, 1), (0, 0));
+        assert_eq!(region.len(), 2);
+        assert_eq!(region[0].len(), 2);
+
+        model.paste_region((1, 1), &region);
+        model.paste_region((2, 1), &region); // clipped to the bottom row
+        assert_eq!(
+            model.to_ascii(),
+            "SC.\n.#C\n..C\n\n\
+             0,1 dirs=U,R,D,L\n0,1 key=wall\n\
+             1,2 dirs=U,R,D,L\n1,2 key=wall\n\
+             2,2 dirs=U,R,D,L\n2,2 key=wall\n"
+        );
+        assert_eq!(model.get_start_pos(), Some((0, 0)));
+        assert_eq!(model.end_pos, None); // overwritten by the pasted tile
+
+        // Nothing is copied from past the board, or from an empty one
+        assert!(model.copy_region((10, 1), (4, 2)).is_empty());
+        assert!(model.copy_region((1, 10), (2, 4)).is_empty());
+        assert!(
+            EditingModel::new((0, 0))
+                .copy_region((0, 0), (0, 0))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn validation_reports_every_issue() {
+        let model =
+            EditingModel::from_ascii("PP.\nP#.\nCB.\n\n0,1 portal=B\n2,0 dirs=\n2,1 bounce=5\n")
+                .unwrap();
+
+        assert_eq!(
+            model.validation_report(),
+            vec![
+                ValidationIssue::MissingStart,
+                ValidationIssue::MissingEnd,
+                ValidationIssue::InvalidTile((2, 0)),
+                ValidationIssue::UnpairedPortal('B'),
+            ]
+        );
+    }
+
+    #[test]
+    fn validation_reports_unsolvable() {
+        let mut model = EditingModel::from_ascii("S..E\n").unwrap();
+
+        assert_eq!(model.validation_report(), vec![ValidationIssue::Unsolvable]);
+        assert!(!model.board_is_playable());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let model = EditingModel::from_ascii("S.C\n#.E\n\n0,2 key=wall\n").unwrap();
+        let loaded = EditingModel::from_json(&model.to_json().unwrap()).unwrap();
+
+        assert_eq!(loaded.to_ascii(), model.to_ascii());
+    }
+
+    #[test]
+    fn board_files_match_the_json_schema() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&EditingModel::json_schema().unwrap()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let board_file = r#"{"version": 1, "board": {
+            "board": [[
+                {"tile": "StartSpace", "key": "None"},
+                {"tile": {"Portal": ["Q", [0, 2]]}, "key": {"OnEquip": {"OnWall": {"DoorKey": "Q"}}}},
+                {"tile": {"Portal": ["Q", [0, 1]]}, "key": {"OnUse": {"TeleportKey": "Q"}}},
+                {"tile": {"SpeedGate": 2}, "key": "None"},
+                {"tile": "EndSpace", "key": "None"}
+            ]],
+            "board_size": [1, 5],
+            "start_pos": [0, 0],
+            "end_pos": [0, 4],
+            "move_limit": 8,
+            "run_speed": 3,
+            "name": "Portals",
+            "next_level": null,
+            "background": {"Solid": [27, 27, 27]},
+            "gravity": "Down"
+        }}"#;
+        assert!(EditingModel::from_json(board_file).is_ok());
+        let value: serde_json::Value = serde_json::from_str(board_file).unwrap();
+        assert!(validator.is_valid(&value));
+
+        // Boards the editor writes validate too
+        let model = EditingModel::from_ascii("SCXOBPPE\n\n0,3 key=door:Q\n0,5 portal=Q\n").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&model.to_json().unwrap()).unwrap();
+        assert!(validator.is_valid(&value));
+
+        // Portal letters are single characters
+        let value: serde_json::Value =
+            serde_json::from_str(&board_file.replace(r#"["Q", [0, 2]]"#, r#"["QQ", [0, 2]]"#))
+                .unwrap();
+        assert!(!validator.is_valid(&value));
+    }
+
+    #[test]
+    fn json_loads_version_0() {
+        let v0 = r#"{
+            "board": [[
+                {"tile": "StartSpace", "key": "None"},
+                {"tile": {"Bounce": 1}, "key": {"OnEquip": {"OnWall": "Wall"}}},
+                {"tile": "EndSpace", "key": "None"}
+            ]],
+            "board_size": [1, 3],
+            "start_pos": [0, 0],
+            "end_pos": [0, 2]
+        }"#;
+        let model = EditingModel::from_json(v0).unwrap();
+
+        assert_eq!(model.to_ascii(), "SBE\n\n0,1 bounce=1\n0,1 key=wall\n");
+        assert_eq!(model.get_move_limit(), None);
+    }
+
+    #[test]
+    fn json_rejects_newer_version() {
+        let model = EditingModel::new((1, 1));
+        let json = model.to_json().unwrap().replacen(
+            &format!("\"version\":{BOARD_FORMAT_VERSION}"),
+            "\"version\":999",
+            1,
+        );
+
+        assert!(matches!(
+            EditingModel::from_json(&json),
+            Err(FoamError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn content_hash_survives_save_and_load() {
+        let mut model =
+            EditingModel::from_ascii("SCB\n#OE\n\n0,1 dirs=R\n0,2 bounce=2\n0,1 key=finish\n")
+                .unwrap();
+        let hash = model.content_hash();
+
+        let file = std::env::temp_dir().join("foam_game_hash_test.fg");
+        model.save_board(file.to_str().unwrap()).unwrap();
+        let loaded = EditingModel::load_board(file.to_str().unwrap()).unwrap();
+        std::fs::remove_file(file).unwrap();
+        assert_eq!(loaded.content_hash(), hash);
+
+        model.set_tile((1, 0), Tile::Ice);
+        assert_ne!(model.content_hash(), hash);
+    }
+
+    #[test]
+    fn revision_changes_with_board_edits() {
+        let mut model = EditingModel::from_ascii("SCE\n").unwrap();
+        let revision = model.get_revision();
+        assert_ne!(EditingModel::new((1, 3)).get_revision(), revision);
+
+        // The level name doesn't change how the board plays or looks
+        model.set_name("Tutorial".to_string());
+        assert_eq!(model.get_revision(), revision);
+        assert!(model.is_dirty());
+
+        model.set_tile((0, 1), Tile::Ice);
+        assert_ne!(model.get_revision(), revision);
+    }
+
+    #[test]
+    fn atomic_write_replaces_file_and_cleans_up() {
+        let file = std::env::temp_dir().join("foam_game_atomic_test.fg");
+        let temp = std::env::temp_dir().join("foam_game_atomic_test.fg.tmp");
+        std::fs::write(&file, "old level").unwrap();
+
+        write_atomically(&file, "new level").unwrap();
+        let contents = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(contents, "new level");
+        assert!(!temp.exists());
+    }
+
+    #[test]
+    fn malformed_board_files_fail_to_load() {
+        let file = std::env::temp_dir().join("foam_game_malformed_board.fg");
+        let json = EditingModel::from_ascii("SE\n").unwrap().to_json().unwrap();
+        for malformed in [
+            json.replace("\"start_pos\":[0,0]", "\"start_pos\":[5,5]"),
+            json.replace("\"end_pos\":[0,1]", "\"end_pos\":[0,0]"),
+            json.replace("\"board_size\":[1,2]", "\"board_size\":[1,3]"),
+            json.replace("\"board_size\":[1,2]", "\"board_size\":[1000000,1000000]"),
+        ] {
+            assert_ne!(malformed, json);
+            std::fs::write(&file, &malformed).unwrap();
+            assert!(
+                matches!(
+                    EditingModel::load_board(file.to_str().unwrap()),
+                    Err(FoamError::InvalidBoard(_))
+                ),
+                "{malformed}"
+            );
+        }
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn empty_and_oversized_boards_are_rejected() {
+        let empty = EditingModel::new((0, 0)).to_json().unwrap();
+        assert!(matches!(
+            EditingModel::from_json(&empty),
+            Err(FoamError::InvalidBoard(_))
+        ));
+
+        let row = ".".repeat(MAX_BOARD_SIDE);
+        for oversized in [
+            format!("S{row}\nE{row}\n"),
+            "..\n".repeat(MAX_BOARD_SIDE + 1),
+        ] {
+            let err = EditingModel::from_ascii(&oversized).unwrap_err();
+            assert!(err.contains("over the limit"), "{err}");
+        }
+    }
+
+    #[test]
+    fn legacy_board_is_imported() {
+        let model = EditingModel::from_fgb_json(
+            r#"{"board":[["StartSpace","Wall",{"Bounce":2}],["Ice","Empty","EndSpace"]],"board_size":[2,3]}"#,
+        )
+        .unwrap();
+        assert_eq!(model.get_board_size(), (2, 3));
+        assert_eq!(model.get_start_pos(), Some((0, 0)));
+        assert_eq!(model.get_end_pos(), Some((1, 2)));
+        assert_eq!(model.get_board()[0][2].tile, Tile::Bounce(2));
+        assert!(
+            model
+                .get_board()
+                .iter()
+                .flatten()
+                .all(|tile_data| tile_data.key == KeyItem::None)
+        );
+
+        assert!(matches!(
+            EditingModel::from_fgb_json(r#"{"board":[["Wall"],["Wall","Ice"]]}"#),
+            Err(FoamError::InvalidBoard(_))
+        ));
+        let oversized = format!(
+            r#"{{"board":[[{}]]}}"#,
+            vec![r#""Empty""#; MAX_BOARD_SIDE + 1].join(",")
+        );
+        assert!(matches!(
+            EditingModel::from_fgb_json(&oversized),
+            Err(FoamError::InvalidBoard(_))
+        ));
+        for empty in [r#"{"board":[]}"#, r#"{"board":[[]]}"#] {
+            assert!(matches!(
+                EditingModel::from_fgb_json(empty),
+                Err(FoamError::InvalidBoard(message)) if message == "board is empty"
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_board_file_is_io_error() {
+        let file = std::env::temp_dir().join("foam_game_missing_board.fg");
+        assert!(matches!(
+            EditingModel::load_board(file.to_str().unwrap()),
+            Err(FoamError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn ascii_rejects_bad_input() {
+        assert!(EditingModel::from_ascii("").is_err());
+        assert!(EditingModel::from_ascii("S.E\n..\n").is_err());
+        assert!(EditingModel::from_ascii("S?E\n").is_err());
+        assert!(EditingModel::from_ascii("SSE\n").is_err());
+        assert!(EditingModel::from_ascii("S.E\n\n0,1 key=finish\n").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//!
+//! Errors from loading and saving boards, replays and textures.
+//!
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum FoamError {
+    Io(std::io::Error),       // Reading or writing a file failed
+    Serde(serde_json::Error), // (De)serializing JSON data failed
+    Image(image::ImageError), // Decoding a texture failed
+    InvalidBoard(String),     // The board data is well formed but can't be used
+}
+
+impl fmt::Display for FoamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoamError::Io(err) => write!(f, "I/O error: {err}"),
+            FoamError::Serde(err) => write!(f, "Invalid data: {err}"),
+            FoamError::Image(err) => write!(f, "Image error: {err}"),
+            FoamError::InvalidBoard(reason) => write!(f, "Invalid board: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for FoamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FoamError::Io(err) => Some(err),
+            FoamError::Serde(err) => Some(err),
+            FoamError::Image(err) => Some(err),
+            FoamError::InvalidBoard(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FoamError {
+    fn from(err: std::io::Error) -> Self {
+        FoamError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for FoamError {
+    fn from(err: serde_json::Error) -> Self {
+        FoamError::Serde(err)
+    }
+}
+
+impl From<image::ImageError> for FoamError {
+    fn from(err: image::ImageError) -> Self {
+        FoamError::Image(err)
+    }
+}
@@ -2,17 +2,51 @@
 //! Logic for displaying the game UI and handling user input
 //!
 
-use super::editing_model::EditingModel;
-use super::item::{ALL_KEYS, KeyItem, KeyOnUse};
-use super::playing_model::{MovementPopupData, PlayingModel};
-use super::tile::{ALL_TILES, Tile};
+use super::audio::{Audio, SOUNDS_DIR};
+use super::editing_model::{
+    BackgroundStyle, EditingModel, IceMomentum, RUN_SPEED_RANGE, SimulatedPath, Symmetry,
+    write_atomically,
+};
+use super::error::FoamError;
+use super::gamepad::{Gamepad, GamepadState};
+use super::input_queue::InputQueue;
+use super::item::{ALL_KEYS, KeyItem};
+use super::playing_model::{MovementPopupData, PlayingModel, SolverStats, Teleport};
+use super::progress::Progress;
+use super::replay::{Ghost, Replay, parse_moves};
+use super::settings::{
+    ALL_COLOR_PALETTES, ALL_PLAYER_SHAPES, ALL_TOUCH_CONTROLS, AppSettings, KEY_REPEAT_DELAY_RANGE,
+    KEY_REPEAT_INTERVAL_RANGE, MAX_ANIMATION_SPEED, MIN_ANIMATION_SPEED, OverlayPalette,
+    PlayerShape,
+};
+use super::tile::{
+    ALL_TILES, BOUNCE_RANGE, CardinalDirectionsAllowed, SPEED_GATE_RANGE, SPEED_PAD_RANGE,
+    SWITCH_RANGE, Tile, TileData,
+};
 use eframe::egui;
 use native_dialog::FileDialog;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 const TILE_IMG_SIDE: u32 = 32;
 const KEY_IMG_SIDE: u32 = 8;
+const TILE_PX: f32 = 32.0; // on-screen tile size at 1x zoom
+const MIN_FIT_ZOOM: f32 = 0.5; // smallest readable zoom when fitting the board to the window
+const COLLISION_FLASH_SECONDS: f64 = 0.2;
+const BLOCKED_MOVE_SECONDS: f64 = 0.3; // how long a disallowed move's arrow stays on the player
+const TELEPORT_EFFECT_SECONDS: f64 = 0.4; // how long the rings at both ends of a portal jump last
+const JUMP_HIGHLIGHT_SECONDS: f64 = 1.0; // how long the tile jumped to with Go to Start/End stays outlined
+const GOAL_PULSE_REPAINT: Duration = Duration::from_millis(33); // redraw rate of the goal pulse, well under the display's
+const KEY_WARNING_DURATION: Duration = Duration::from_secs(3); // how long a warning about a misplaced key stays up
+const UNWINNABLE_CHECK_STATES: usize = 5_000; // states searched before a move is assumed to keep the level winnable
+const TOUCH_BUTTON_SIZE: f32 = 48.0; // width and height of the on-screen d-pad's buttons, about a fingertip
+const GHOST_OPACITY: f32 = 0.35; // how faintly the ghost racing a replay is drawn
+const GAMEPAD_POLL: Duration = Duration::from_millis(16); // how often a connected controller is read in play, which sends no events
 
 #[derive(Debug, Clone)]
 pub struct KeyState {
@@ -24,6 +58,7 @@ pub struct KeyState {
     pub enter: bool,
     pub last_update: f64,
     pub keys_pressed_this_frame: bool, // Track if any keys were pressed this frame
+    pub repeat_at: Option<f64>, // When held direction keys next repeat, if hold to repeat is on
 }
 
 impl Default for KeyState {
@@ -37,10 +72,34 @@ impl Default for KeyState {
             enter: false,
             last_update: 0.0,
             keys_pressed_this_frame: false,
+            repeat_at: None,
         }
     }
 }
 
+/// What the arrow keys do in editing mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorFocus {
+    EditTile, // Arrow keys edit the selected tile
+    Navigate, // Arrow keys move the cursor, Enter places the brush
+}
+
+/// A move traced from a tile in the editor, showing where it ends up without playing
+#[derive(Debug, Clone, Copy)]
+pub struct BranchPreview {
+    from: (usize, usize),
+    movement: PlayerMovementData,
+    dest: Option<(usize, usize)>, // None if the move leaves the board
+    board_revision: u64,          // board the move was traced on, so edits trace it again
+}
+
+/// How the moves typed into the editor's simulate panel played out
+struct MoveSimulation {
+    start: Option<(usize, usize)>, // Start space the moves were played from
+    path: SimulatedPath,           // Where each played move ended
+    outcome: MovementPopupData,    // How the last played move ended
+}
+
 #[derive(Debug, Clone)]
 pub enum AppMode {
     Startup,
@@ -48,23 +107,122 @@ pub enum AppMode {
     Playing,
 }
 
+/// A board open for editing in its own tab
+struct BoardTab {
+    model: EditingModel,
+    path: Option<PathBuf>, // File the board was last loaded from or saved to
+}
+
+impl BoardTab {
+    fn new(model: EditingModel) -> Self {
+        BoardTab { model, path: None }
+    }
+
+    /// The level's name, or else its file's
+    fn name(&self) -> String {
+        match (self.model.get_name(), &self.path) {
+            (name, _) if !name.is_empty() => name.to_string(),
+            (_, Some(path)) => path.file_stem().map_or_else(
+                || path.display().to_string(),
+                |stem| stem.to_string_lossy().to_string(),
+            ),
+            (_, None) => "Untitled".to_string(),
+        }
+    }
+
+    /// Name shown on the tab, marked while the board has unsaved changes
+    fn title(&self) -> String {
+        if self.model.is_dirty() {
+            format!("{} •", self.name())
+        } else {
+            self.name()
+        }
+    }
+}
+
+/// The editing board's tiles drawn into meshes, so they're only drawn again when the board
+/// or its look changes rather than every frame
+struct EditingBoardCache {
+    key: u64,                        // Hash of what the meshes were drawn from
+    meshes: Vec<egui::epaint::Mesh>, // One per texture, relative to the board's top left corner
+}
+
+/// File a save will write to, and what to do once it has been saved
+type PendingSave = (PathBuf, fn(&mut App));
+
 pub struct App {
-    editing_model: EditingModel, // Struct that contains actual game data and logic
+    tabs: Vec<BoardTab>,         // Boards open for editing, never empty
+    active_tab: usize,           // Tab being edited, and played from
     playing_model: PlayingModel, // Struct that contains game data and logic for playing mode
 
     mode: AppMode,
     selected_type: Option<Tile>,
     selected_key: Option<KeyItem>, // Currently selected key/item for editing
+    previous_selected_type: Option<Tile>, // Tile brush used before the current one, swapped to with X
+    previous_selected_key: Option<KeyItem>, // Key brush used before the current one, swapped to with Z
+    brush_is_eraser: bool,                  // The brush clears both the tile and the key
     selected_tile_pos: Option<(usize, usize)>, // Currently selected tile position for editing
-    width_slider: usize,           // Width slider for board size
-    height_slider: usize,          // Height slider for board size
+    cursor_pos: Option<(usize, usize)>,     // Keyboard cursor position for editing
+    editor_focus: EditorFocus,              // Whether arrow keys edit the tile or move the cursor
+    width_slider: usize,                    // Width slider for board size
+    height_slider: usize,                   // Height slider for board size
+    show_unreachable: bool,                 // Shade tiles the player can never reach while editing
+    show_ice_momentum: bool,                // Draw the directions slides carry the player over ice
+    show_critical: bool, // Outline tiles every shortest win lands on while editing
+    allow_incompatible_keys: bool, // Place keys where the player can't use them, instead of only warning
+    key_warning: Option<(&'static str, Instant)>, // Why the last key couldn't go where it was placed, and when
+    branch_preview: Option<BranchPreview>,        // Move traced from the inspected tile
+    preview_run: bool,                            // Trace run moves instead of single steps
+    symmetry: Symmetry,                           // Axes brush edits are mirrored across
+    palette_filter: String,                       // Text filter for the tile and key palette
+    selection: Option<((usize, usize), (usize, usize))>, // Corners of the shift-drag selection
+    clipboard: Vec<Vec<TileData>>, // Tiles copied from the selection, rows then columns
+    scroll_target: Option<(usize, usize)>, // Tile to scroll the editing board to, set by the minimap and Go to Start/End
+    jump_highlight: Option<((usize, usize), f64)>, // Tile jumped to with Go to Start/End, and when
+    editor_viewport: Option<egui::Rect>,   // Visible part of the editing board, in tiles
+    editing_board_cache: Option<EditingBoardCache>, // Tiles of the editing board, drawn once per change
+    random_seed: u64,                               // Seed for the next randomized board
+    sandbox: bool,                                  // Start play from the editor holding every key
+    solution: Option<(u64, Option<SolverStats>)>, // Solver result, and the board revision it was found for
+    critical_tiles: Option<(u64, HashSet<(usize, usize)>)>, // Tiles every shortest win lands on, and the board revision they were found for
+    reachable_tiles: Option<(u64, HashSet<(usize, usize)>)>, // Tiles the player can reach, and the board revision they were found for
+    ice_momentum: Option<(u64, IceMomentum)>, // Directions slides carry the player over ice, and the board revision they were found for
+    simulation_text: String, // Moves typed into the simulate panel, e.g. "U U R* D"
+    simulation: Option<Result<MoveSimulation, String>>, // Result of simulating them, or why they couldn't be
 
     key_state: KeyState,
     last_animation_update: f64,
+    zoom: f32,       // Playing board zoom factor, adjusted with ctrl+scroll
+    fit_board: bool, // Zoom the playing board to fit the window, until zoomed by hand
+    replay_moves: VecDeque<PlayerMovementData>, // Moves left to play back from a loaded replay
+    ghost: Option<Ghost>, // Replay raced against the live run, drawn as a faint player
+    pending_move: Option<PlayerMovementData>, // Move that would leave the level unwinnable, waiting to be confirmed
+    winnable_cache: HashMap<u64, bool>, // Whether the end is in reach from each game state checked
+    input_queue: InputQueue,            // Moves pressed mid-animation, made once it finishes
+    show_debug: bool, // Show the last movement input and its result in play, toggled with F3
+    step_mode: bool,  // Moves advance one tile per press of the period key, instead of on a timer
+    collision_time: Option<f64>, // When the player last ran into a wall, for the collision flash
+    blocked_move: Option<(DirectionKey, f64)>, // Direction the player's tile last refused, and when
+    teleport: Option<(Teleport, f64)>, // Last portal jump (from, to), and when
+    camera_pos: Option<(usize, usize)>, // Player position the playing board last scrolled to
+    frame_times: VecDeque<f64>, // When each frame in the last second was drawn, for the frame rate
+    frame_cpu_time: Duration, // Time spent building the last frame
 
+    audio: Audio,                // Sound effects, silent without an audio device
+    gamepad: Gamepad,            // Controller input, none without a connected controller
+    gamepad_state: GamepadState, // Controller buttons held last frame, to tell presses from holds
+    touch_detected: bool,        // The screen has been touched, so the d-pad shows automatically
+    touch_run: bool,             // The d-pad's run toggle is on, so its moves are runs
     texture_cache: HashMap<String, egui::TextureHandle>,
+    theme_dir: PathBuf, // Directory textures are loaded from
+
+    settings: AppSettings,
+    show_settings: bool, // Whether the settings window is open
+    show_help: bool,     // Whether the tile and key legend is open
 
     popup_data: Option<PopupData>,
+    exit_requested: bool, // A board was saved or discarded on the way out, check the others
+    pending_save: Option<PendingSave>, // Save waiting on overwrite confirmation
 }
 
 #[derive(Debug, Clone)]
@@ -73,6 +231,9 @@ pub struct PopupData {
     pub popup_type: PopupType,
 }
 
+/// Labelled button in a popup, and what it does when clicked
+pub type PopupAction = (&'static str, fn(&mut App));
+
 #[derive(Debug, Clone)]
 pub enum PopupType {
     Ok,
@@ -80,23 +241,47 @@ pub enum PopupType {
         on_yes: fn(&mut App),
         on_no: Option<fn(&mut App)>,
     },
+    Action {
+        actions: Vec<PopupAction>,
+    }, // OK plus extra action buttons, each with its label
+}
+
+/// Theme used for textures missing from the selected theme
+const DEFAULT_THEME_DIR: &str = "assets";
+/// Directory holding one subdirectory per extra theme
+const THEMES_DIR: &str = "assets/themes";
+
+/// Path of a texture in a theme, falling back to the default theme if the file is missing
+fn resolve_texture_path(theme_dir: &Path, file_name: &str) -> PathBuf {
+    let path = theme_dir.join(file_name);
+    if path.exists() {
+        path
+    } else {
+        Path::new(DEFAULT_THEME_DIR).join(file_name)
+    }
+}
+
+/// Names of the theme subdirectories, sorted
+fn list_themes() -> Vec<String> {
+    let mut themes: Vec<String> = std::fs::read_dir(THEMES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    themes.sort();
+    themes
 }
 
 // Add method to load image data from file
-pub fn load_tile_image(tile: &Tile) -> Result<egui::ColorImage, String> {
-    let image = image::ImageReader::open(tile.file_name())
-        .map_err(|err| {
-            format!(
-                "Error loading texture file at {}: {}",
-                tile.file_name(),
-                err
-            )
-        })?
-        .decode()
-        .map_err(|err| format!("Error decoding image at {}: {}", tile.file_name(), err))?;
+pub fn load_tile_image(path: &Path) -> Result<egui::ColorImage, FoamError> {
+    let image = image::ImageReader::open(path)?.decode()?;
 
     // Resize the image to 32x32
-    let image = image.resize(
+    let image = image.resize_exact(
         TILE_IMG_SIDE,
         TILE_IMG_SIDE,
         image::imageops::FilterType::Nearest,
@@ -111,14 +296,11 @@ pub fn load_tile_image(tile: &Tile) -> Result<egui::ColorImage, String> {
     ))
 }
 
-pub fn load_key_image(key_item: &KeyItem) -> Result<egui::ColorImage, String> {
-    let image = image::ImageReader::open(key_item.file_name())
-        .map_err(|err| format!("Error loading key texture file: {err}"))?
-        .decode()
-        .map_err(|err| format!("Error decoding key image: {err}"))?;
+pub fn load_key_image(path: &Path) -> Result<egui::ColorImage, FoamError> {
+    let image = image::ImageReader::open(path)?.decode()?;
 
     // Resize the image to 8x8
-    let image = image.resize(
+    let image = image.resize_exact(
         KEY_IMG_SIDE,
         KEY_IMG_SIDE,
         image::imageops::FilterType::Nearest,
@@ -134,79 +316,370 @@ pub fn load_key_image(key_item: &KeyItem) -> Result<egui::ColorImage, String> {
 }
 
 // Add method to get cached texture
-fn load_tile_texture(ctx: &egui::Context, tile: &Tile) -> Result<egui::TextureHandle, String> {
-    let image = load_tile_image(tile).map_err(|err| format!("Error loading texture: {err}"))?;
+fn load_tile_texture(
+    ctx: &egui::Context,
+    theme_dir: &Path,
+    tile: &Tile,
+) -> Result<egui::TextureHandle, FoamError> {
+    let image = load_tile_image(&resolve_texture_path(theme_dir, tile.file_name()))?;
 
-    let texture = ctx.load_texture(tile.file_name(), image, egui::TextureOptions::default());
+    let texture = ctx.load_texture(tile.file_name(), image, egui::TextureOptions::NEAREST);
 
     Ok(texture)
 }
 
 fn load_key_texture(
     ctx: &egui::Context,
+    theme_dir: &Path,
     key_item: &KeyItem,
-) -> Result<egui::TextureHandle, String> {
-    let image =
-        load_key_image(key_item).map_err(|err| format!("Error loading key texture: {err}"))?;
+) -> Result<egui::TextureHandle, FoamError> {
+    let image = load_key_image(&resolve_texture_path(theme_dir, key_item.file_name()))?;
 
-    let texture = ctx.load_texture(key_item.file_name(), image, egui::TextureOptions::default());
+    let texture = ctx.load_texture(key_item.file_name(), image, egui::TextureOptions::NEAREST);
 
     Ok(texture)
 }
 
-impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let mut texture_cache = HashMap::new();
+/// Texture cache key of the custom player sprite
+const PLAYER_SPRITE_TEXTURE: &str = "player_sprite";
 
-        // Pre-load all textures at startup
-        for tile in ALL_TILES {
-            if let Ok(texture) = load_tile_texture(&cc.egui_ctx, tile) {
+/// Load the custom player sprite into the texture cache, or drop the old one if there's none
+fn load_player_sprite(
+    ctx: &egui::Context,
+    sprite: Option<&Path>,
+    texture_cache: &mut HashMap<String, egui::TextureHandle>,
+) -> Result<(), FoamError> {
+    let Some(path) = sprite else {
+        texture_cache.remove(PLAYER_SPRITE_TEXTURE);
+        return Ok(());
+    };
+    let image = load_tile_image(path)?;
+    let texture = ctx.load_texture(PLAYER_SPRITE_TEXTURE, image, egui::TextureOptions::NEAREST);
+    texture_cache.insert(PLAYER_SPRITE_TEXTURE.to_string(), texture);
+    Ok(())
+}
+
+/// Load every tile and key texture from a theme, keyed by file name
+fn load_textures(ctx: &egui::Context, theme_dir: &Path) -> HashMap<String, egui::TextureHandle> {
+    let mut texture_cache = HashMap::new();
+    for error in reload_textures(ctx, theme_dir, &mut texture_cache) {
+        eprintln!("Warning: failed to load texture {error}");
+    }
+    texture_cache
+}
+
+/// Load every tile and key texture from a theme again, e.g. after editing the images. A
+/// texture that fails to load keeps its old version, and the failures are returned
+fn reload_textures(
+    ctx: &egui::Context,
+    theme_dir: &Path,
+    texture_cache: &mut HashMap<String, egui::TextureHandle>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for tile in ALL_TILES {
+        match load_tile_texture(ctx, theme_dir, tile) {
+            Ok(texture) => {
                 texture_cache.insert(tile.file_name().to_string(), texture);
-            } else {
-                eprintln!(
-                    "Warning: failed to load texture for tile: {}",
-                    tile.file_name()
-                );
             }
+            Err(err) => errors.push(format!("{}: {err}", tile.file_name())),
         }
+    }
 
-        for key in ALL_KEYS {
-            if let Ok(texture) = load_key_texture(&cc.egui_ctx, key) {
+    for key in ALL_KEYS {
+        match load_key_texture(ctx, theme_dir, key) {
+            Ok(texture) => {
                 texture_cache.insert(key.file_name().to_string(), texture);
-            } else {
-                eprintln!(
-                    "Warning: failed to load texture for key/item: {}",
-                    key.file_name()
-                );
             }
+            Err(err) => errors.push(format!("{}: {err}", key.file_name())),
+        }
+    }
+
+    errors.sort();
+    errors.dedup(); // Variants of a tile or key share a texture
+    errors
+}
+
+impl App {
+    /// App on the startup screen, or straight into editing (or playing, if `play`) a board
+    /// file given on the command line
+    pub fn new(cc: &eframe::CreationContext<'_>, board_path: Option<PathBuf>, play: bool) -> Self {
+        // Pre-load all textures at startup
+        let theme_dir = PathBuf::from(DEFAULT_THEME_DIR);
+        let texture_cache = load_textures(&cc.egui_ctx, &theme_dir);
+
+        let mut app = App::with_textures(texture_cache, theme_dir, AppSettings::load());
+        if let Err(err) = load_player_sprite(
+            &cc.egui_ctx,
+            app.settings.player_sprite.as_deref(),
+            &mut app.texture_cache,
+        ) {
+            eprintln!("Warning: failed to load player sprite: {err}");
+        }
+        if let Some(board_path) = board_path {
+            app.open_initial_board(board_path, play);
         }
+        app
+    }
 
+    /// App on the startup screen with textures already loaded
+    fn with_textures(
+        texture_cache: HashMap<String, egui::TextureHandle>,
+        theme_dir: PathBuf,
+        settings: AppSettings,
+    ) -> Self {
         App {
-            editing_model: Default::default(),
+            tabs: vec![BoardTab::new(EditingModel::default())],
+            active_tab: 0,
             playing_model: Default::default(),
             mode: AppMode::Startup,
             selected_type: None,
             selected_key: None,
+            previous_selected_type: None,
+            previous_selected_key: None,
+            brush_is_eraser: false,
             selected_tile_pos: None,
+            cursor_pos: None,
+            editor_focus: EditorFocus::EditTile,
             width_slider: 0,
             height_slider: 0,
+            show_unreachable: false,
+            show_ice_momentum: false,
+            show_critical: false,
+            allow_incompatible_keys: false,
+            key_warning: None,
+            branch_preview: None,
+            preview_run: false,
+            palette_filter: String::new(),
+            selection: None,
+            clipboard: Vec::new(),
+            scroll_target: None,
+            jump_highlight: None,
+            symmetry: Symmetry::None,
+            editor_viewport: None,
+            editing_board_cache: None,
+            random_seed: 0,
+            sandbox: false,
+            solution: None,
+            critical_tiles: None,
+            reachable_tiles: None,
+            ice_momentum: None,
+            simulation_text: String::new(),
+            simulation: None,
+            audio: Audio::new(Path::new(SOUNDS_DIR)),
+            gamepad: Gamepad::new(),
+            gamepad_state: GamepadState::default(),
+            touch_detected: false,
+            touch_run: false,
             texture_cache,
+            theme_dir,
             key_state: KeyState::default(),
             last_animation_update: 0.0,
+            zoom: 1.0,
+            fit_board: true,
+            replay_moves: VecDeque::new(),
+            ghost: None,
+            pending_move: None,
+            winnable_cache: HashMap::new(),
+            input_queue: Default::default(),
+            show_debug: false,
+            step_mode: false,
+            collision_time: None,
+            blocked_move: None,
+            teleport: None,
+            camera_pos: None,
+            frame_times: VecDeque::new(),
+            frame_cpu_time: Duration::ZERO,
+            settings,
+            show_settings: false,
+            show_help: false,
             popup_data: None,
+            exit_requested: false,
+            pending_save: None,
+        }
+    }
+
+    /// Open the board given on the command line, staying on the startup screen if it can't
+    /// be loaded
+    fn open_initial_board(&mut self, path: PathBuf, play: bool) {
+        let model = match EditingModel::load_board(&path.to_string_lossy()) {
+            Ok(model) => model,
+            Err(err) => {
+                self.popup_data = Some(PopupData {
+                    message: format!("Error loading {}: {err}", path.display()),
+                    popup_type: PopupType::Ok,
+                });
+                return;
+            }
+        };
+        *self.editing_model_mut() = model;
+        self.set_board_path(path);
+        self.mode = AppMode::Editing;
+
+        if play {
+            if self.editing_model_mut().board_is_playable() {
+                self.start_playing(false);
+            } else {
+                self.popup_data = Some(PopupData {
+                    message: "The board can't be played yet, it's open for editing.".to_string(),
+                    popup_type: PopupType::Ok,
+                });
+            }
+        }
+    }
+
+    /// Play an ASCII board pasted from the clipboard, opened in a new tab
+    fn play_pasted_board(&mut self, text: &str) {
+        let mut model = match EditingModel::from_ascii(text) {
+            Ok(model) => model,
+            Err(err) => {
+                self.popup_data = Some(PopupData {
+                    message: format!("The clipboard doesn't hold an ASCII board: {err}"),
+                    popup_type: PopupType::Ok,
+                });
+                return;
+            }
+        };
+        if !model.board_is_playable() {
+            self.popup_data = Some(PopupData {
+                message: "The pasted board can't be played yet.".to_string(),
+                popup_type: PopupType::Ok,
+            });
+            return;
+        }
+
+        self.open_tab(model);
+        self.replay_moves.clear();
+        self.start_playing(false);
+    }
+
+    /// How long until the next frame is needed without any input, or None to wait for input.
+    /// Only a move or replay stepping on its timer, and an input queued behind a finished move,
+    /// need frames of their own. Fades and the goal pulse request theirs while they're drawn,
+    /// and everything else, e.g. the editor and the start screen, redraws on input events alone.
+    /// A connected controller sends no events, so play keeps polling it
+    fn next_repaint_delay(&self, time: f64) -> Option<Duration> {
+        // A winning or losing move is left unfinished when play ends, so only play mode counts
+        if !matches!(self.mode, AppMode::Playing) || self.popup_data.is_some() {
+            return None;
+        }
+        let ghost_move = self
+            .ghost
+            .as_ref()
+            .and_then(Ghost::next_move_at)
+            .map(|due| Duration::from_secs_f64((due - time).max(0.0)));
+        let delay = [self.next_step_delay(time), ghost_move]
+            .into_iter()
+            .flatten()
+            .min();
+        if self.gamepad.is_connected() {
+            return Some(delay.map_or(GAMEPAD_POLL, |delay| delay.min(GAMEPAD_POLL)));
+        }
+        delay
+    }
+
+    /// How long until the next move step, queued input or key repeat is due in play
+    fn next_step_delay(&self, time: f64) -> Option<Duration> {
+        let timed_step = (self.playing_model.animation_state.is_some() && !self.step_mode)
+            || !self.replay_moves.is_empty();
+        if timed_step {
+            let until_step = self.settings.animation_speed - (time - self.last_animation_update);
+            Some(Duration::from_secs_f64(until_step.max(0.0)))
+        } else if !self.input_queue.is_empty() || self.pending_move.is_some() {
+            Some(Duration::ZERO)
+        } else {
+            // Held direction keys waiting to repeat
+            self.key_state
+                .repeat_at
+                .map(|repeat_at| Duration::from_secs_f64((repeat_at - time).max(0.0)))
+        }
+    }
+
+    /// Track frame timings for the frame rate display, and draw it if enabled
+    fn record_frame(&mut self, ctx: &egui::Context, frame_start: Instant) {
+        if !self.settings.show_frame_rate {
+            self.frame_times.clear();
+            return;
+        }
+        let time = ctx.input(|i| i.time);
+        self.frame_times.push_back(time);
+        while self
+            .frame_times
+            .front()
+            .is_some_and(|&first| time - first > 1.0)
+        {
+            self.frame_times.pop_front();
+        }
+
+        // Figures are as of the last frame, when idle they hold still until the next one
+        egui::Area::new(egui::Id::new("frame_rate"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            .show(ctx, |ui| {
+                ui.monospace(format!(
+                    "{} frames/s, {:.1} ms/frame",
+                    self.frame_times.len(),
+                    self.frame_cpu_time.as_secs_f64() * 1000.0
+                ));
+            });
+        self.frame_cpu_time = frame_start.elapsed();
+    }
+
+    /// Switch to playing the editing board, returning whether it could be played. A board
+    /// that can't be played stays open for editing with a popup saying why
+    fn start_playing(&mut self, sandbox: bool) -> bool {
+        let model = if sandbox {
+            PlayingModel::new_sandbox(self.editing_model())
+        } else {
+            PlayingModel::new(self.editing_model())
+        };
+        match model {
+            Ok(model) => {
+                self.playing_model = model;
+                self.pending_move = None;
+                self.winnable_cache.clear();
+                // A ghost races again from the start, as long as it's for this board
+                let board_hash = self.editing_model().content_hash();
+                self.ghost = self
+                    .ghost
+                    .take()
+                    .filter(|ghost| ghost.board_hash() == board_hash);
+                if let Some(ghost) = &mut self.ghost {
+                    ghost.restart();
+                }
+                self.mode = AppMode::Playing;
+                self.fit_board = true;
+                self.camera_pos = None;
+                true
+            }
+            Err(err) => {
+                self.popup_data = Some(PopupData {
+                    message: format!("The board can't be played: {err}"),
+                    popup_type: PopupType::Ok,
+                });
+                false
+            }
         }
     }
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Request continuous repaints during animation
-        if self.playing_model.animation_state.is_some() {
-            ctx.request_repaint();
-        }
+        let frame_start = Instant::now();
 
         egui::CentralPanel::default().show(ctx, |ui| {
             update_key_state(ui, self);
+            ui.horizontal(|ui| {
+                if ui.button("Settings").clicked() {
+                    self.show_settings = !self.show_settings;
+                }
+                if ui
+                    .button("?")
+                    .on_hover_text("What each tile and key does (F1)")
+                    .clicked()
+                {
+                    self.show_help = !self.show_help;
+                }
+            });
             match self.mode {
                 AppMode::Startup => startup_screen(ui, self),
                 AppMode::Editing => editing_screen(ui, self),
@@ -214,6 +687,64 @@ impl eframe::App for App {
             }
         });
 
+        if ctx.input(|i| i.key_pressed(egui::Key::F1)) {
+            self.show_help = !self.show_help;
+        }
+
+        display_settings_window(ctx, self);
+        display_help_window(ctx, self);
+
+        // Ctrl+Shift+V plays an ASCII board straight from the clipboard, to quickly try out
+        // levels shared as text
+        let pasted_board = ctx.input(|i| {
+            i.modifiers
+                .shift
+                .then(|| {
+                    i.events.iter().find_map(|event| match event {
+                        egui::Event::Paste(text) => Some(text.clone()),
+                        _ => None,
+                    })
+                })
+                .flatten()
+        });
+        if let Some(text) = pasted_board
+            && !ctx.wants_keyboard_input()
+        {
+            self.play_pasted_board(&text);
+        }
+
+        // Ask to save unsaved edits before the window closes, one board at a time. Each board
+        // saved or discarded is closed, and the next one with unsaved edits is asked about
+        let close_requested = ctx.input(|i| i.viewport().close_requested())
+            || std::mem::take(&mut self.exit_requested);
+        if close_requested {
+            match self.tabs.iter().position(|tab| tab.model.is_dirty()) {
+                Some(index) => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    self.switch_tab(index);
+                    self.popup_data = Some(PopupData {
+                        message: format!(
+                            "{} has unsaved changes. Save them before exiting?",
+                            self.tabs[index].name()
+                        ),
+                        popup_type: PopupType::YesNo {
+                            on_yes: |app| {
+                                save_board_dialog(app, |app| {
+                                    app.close_tab();
+                                    app.exit_requested = true;
+                                })
+                            },
+                            on_no: Some(|app| {
+                                app.close_tab();
+                                app.exit_requested = true;
+                            }),
+                        },
+                    });
+                }
+                None => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            }
+        }
+
         if let Some(PopupData {
             message,
             popup_type,
@@ -226,27 +757,48 @@ impl eframe::App for App {
                 .show(ctx, |ui| {
                     ui.label(&message);
 
+                    // Escape dismisses the popup the same way OK or No would
+                    let escape = ui.input(|i| i.key_pressed(egui::Key::Escape));
+
                     match popup_type {
                         PopupType::Ok => {
-                            if ui.button("OK").clicked() {
+                            if ui.button("OK").clicked() || escape {
                                 self.popup_data = None;
                             }
                         }
+                        PopupType::Action { actions } => {
+                            ui.horizontal(|ui| {
+                                if ui.button("OK").clicked() || escape {
+                                    self.popup_data = None;
+                                }
+                                for (label, on_action) in actions {
+                                    if ui.button(label).clicked() {
+                                        self.popup_data = None; // Cleared first so the action can open a new popup
+                                        on_action(self);
+                                    }
+                                }
+                            });
+                        }
                         PopupType::YesNo { on_yes, on_no } => {
                             if ui.button("Yes").clicked() {
+                                self.popup_data = None; // Cleared first so the answer can open a new popup
                                 on_yes(self);
-                                self.popup_data = None;
                             }
-                            if ui.button("No").clicked() {
+                            if ui.button("No").clicked() || escape {
+                                self.popup_data = None;
                                 if let Some(on_no_fn) = on_no {
                                     on_no_fn(self);
                                 }
-                                self.popup_data = None;
                             }
                         }
                     }
                 });
         }
+
+        if let Some(delay) = self.next_repaint_delay(ctx.input(|i| i.time)) {
+            ctx.request_repaint_after(delay);
+        }
+        self.record_frame(ctx, frame_start);
     }
 }
 
@@ -254,7 +806,7 @@ impl eframe::App for App {
     Key enum & key logic
 */
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum DirectionKey {
     Up,
     Right,
@@ -267,13 +819,27 @@ pub enum DirectionKey {
     None,
 }
 
+pub const ALL_DIRECTIONS: &[DirectionKey] = &[
+    DirectionKey::Up,
+    DirectionKey::Right,
+    DirectionKey::Down,
+    DirectionKey::Left,
+    DirectionKey::UpRight,
+    DirectionKey::DownRight,
+    DirectionKey::DownLeft,
+    DirectionKey::UpLeft,
+];
+
 impl DirectionKey {
-    // pub fn is_diagonal(&self) -> bool {
-    //     matches!(
-    //         self,
-    //         DirectionKey::UpRight | DirectionKey::DownRight | DirectionKey::DownLeft | DirectionKey::UpLeft
-    //     )
-    // }
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self,
+            DirectionKey::UpRight
+                | DirectionKey::DownRight
+                | DirectionKey::DownLeft
+                | DirectionKey::UpLeft
+        )
+    }
     pub fn is_cardinal(&self) -> bool {
         matches!(
             self,
@@ -283,9 +849,93 @@ impl DirectionKey {
     pub fn is_none(&self) -> bool {
         matches!(self, DirectionKey::None)
     }
+
+    /// One-tile (row, column) offset of this direction
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            DirectionKey::Up => (-1, 0),
+            DirectionKey::Right => (0, 1),
+            DirectionKey::Down => (1, 0),
+            DirectionKey::Left => (0, -1),
+            DirectionKey::UpRight => (-1, 1),
+            DirectionKey::DownRight => (1, 1),
+            DirectionKey::DownLeft => (1, -1),
+            DirectionKey::UpLeft => (-1, -1),
+            DirectionKey::None => (0, 0),
+        }
+    }
+
+    /// Short code for this direction in the ASCII board format
+    pub fn code(&self) -> &'static str {
+        match self {
+            DirectionKey::Up => "U",
+            DirectionKey::Right => "R",
+            DirectionKey::Down => "D",
+            DirectionKey::Left => "L",
+            DirectionKey::UpRight => "UR",
+            DirectionKey::DownRight => "DR",
+            DirectionKey::DownLeft => "DL",
+            DirectionKey::UpLeft => "UL",
+            DirectionKey::None => "",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<DirectionKey> {
+        ALL_DIRECTIONS
+            .iter()
+            .find(|direction| direction.code() == code)
+            .copied()
+    }
+
+    /// This direction turned 90 degrees clockwise
+    pub fn rotated_cw(&self) -> DirectionKey {
+        match self {
+            DirectionKey::Up => DirectionKey::Right,
+            DirectionKey::Right => DirectionKey::Down,
+            DirectionKey::Down => DirectionKey::Left,
+            DirectionKey::Left => DirectionKey::Up,
+            DirectionKey::UpRight => DirectionKey::DownRight,
+            DirectionKey::DownRight => DirectionKey::DownLeft,
+            DirectionKey::DownLeft => DirectionKey::UpLeft,
+            DirectionKey::UpLeft => DirectionKey::UpRight,
+            DirectionKey::None => DirectionKey::None,
+        }
+    }
+
+    /// This direction mirrored left to right
+    pub fn flipped_horizontal(&self) -> DirectionKey {
+        match self {
+            DirectionKey::Right => DirectionKey::Left,
+            DirectionKey::Left => DirectionKey::Right,
+            DirectionKey::UpRight => DirectionKey::UpLeft,
+            DirectionKey::DownRight => DirectionKey::DownLeft,
+            DirectionKey::DownLeft => DirectionKey::DownRight,
+            DirectionKey::UpLeft => DirectionKey::UpRight,
+            direction => *direction,
+        }
+    }
+
+    /// This direction mirrored top to bottom
+    pub fn flipped_vertical(&self) -> DirectionKey {
+        self.flipped_horizontal().reversed()
+    }
+
+    pub fn reversed(&self) -> DirectionKey {
+        match self {
+            DirectionKey::Up => DirectionKey::Down,
+            DirectionKey::Right => DirectionKey::Left,
+            DirectionKey::Down => DirectionKey::Up,
+            DirectionKey::Left => DirectionKey::Right,
+            DirectionKey::UpRight => DirectionKey::DownLeft,
+            DirectionKey::DownRight => DirectionKey::UpLeft,
+            DirectionKey::DownLeft => DirectionKey::UpRight,
+            DirectionKey::UpLeft => DirectionKey::DownRight,
+            DirectionKey::None => DirectionKey::None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PlayerMovementData {
     pub direction: DirectionKey,
     pub move_speed: usize, // Number of tiles to move in the given direction
@@ -357,6 +1007,218 @@ pub fn direction_key_into_bools(direction: &DirectionKey) -> (bool, bool, bool,
 }
 
 impl App {
+    /// Make the eraser the brush, remembering the tile and key brushes it replaces
+    fn select_eraser_brush(&mut self) {
+        if let Some(old) = self.selected_type.take() {
+            self.previous_selected_type = Some(old);
+        }
+        if let Some(old) = self.selected_key.take() {
+            self.previous_selected_key = Some(old);
+        }
+        self.brush_is_eraser = true;
+    }
+
+    /// Make a tile the brush, remembering the tile brush it replaces. When combining, the key
+    /// brush is kept so both are placed together
+    fn select_tile_brush(&mut self, tile: Tile, combine: bool) {
+        self.brush_is_eraser = false;
+        if self.selected_type.as_ref() != Some(&tile)
+            && let Some(old) = self.selected_type.replace(tile)
+        {
+            self.previous_selected_type = Some(old);
+        }
+        // Clear selected key when selecting a tile
+        if !combine && let Some(old) = self.selected_key.take() {
+            self.previous_selected_key = Some(old);
+        }
+    }
+
+    /// Make a key the brush, remembering the key brush it replaces. When combining, the tile
+    /// brush is kept so both are placed together
+    fn select_key_brush(&mut self, key: KeyItem, combine: bool) {
+        self.brush_is_eraser = false;
+        if self.selected_key.as_ref() != Some(&key)
+            && let Some(old) = self.selected_key.replace(key)
+        {
+            self.previous_selected_key = Some(old);
+        }
+        // Clear selected tile when selecting a key
+        if !combine && let Some(old) = self.selected_type.take() {
+            self.previous_selected_type = Some(old);
+        }
+    }
+
+    /// Remember the file the editing board was loaded from or saved to, and add it to the
+    /// recent files list
+    fn set_board_path(&mut self, path: PathBuf) {
+        self.settings.add_recent_file(path.clone());
+        if let Err(err) = self.settings.save() {
+            eprintln!("Error saving settings: {err}");
+        }
+        self.tabs[self.active_tab].path = Some(path);
+    }
+
+    fn editing_model(&self) -> &EditingModel {
+        &self.tabs[self.active_tab].model
+    }
+
+    fn editing_model_mut(&mut self) -> &mut EditingModel {
+        &mut self.tabs[self.active_tab].model
+    }
+
+    /// File the editing board was last loaded from or saved to
+    fn board_path(&self) -> Option<&PathBuf> {
+        self.tabs[self.active_tab].path.as_ref()
+    }
+
+    /// Open a blank board the size of the current one in a new tab
+    fn new_tab(&mut self) {
+        let size = self.editing_model().get_board_size();
+        self.open_tab(EditingModel::new(size));
+    }
+
+    /// Open a board in a new tab, e.g. imported or from a replay, leaving the board being
+    /// edited and any unsaved changes to it alone. The empty board the startup screen keeps
+    /// open is replaced rather than left as a tab of its own
+    fn open_tab(&mut self, model: EditingModel) {
+        if self.tabs.len() == 1 && self.editing_model().get_board_size() == (0, 0) {
+            self.tabs[0] = BoardTab::new(model);
+            self.reset_board_view();
+            return;
+        }
+        self.tabs.push(BoardTab::new(model));
+        self.switch_tab(self.tabs.len() - 1);
+    }
+
+    /// Edit another open board
+    fn switch_tab(&mut self, index: usize) {
+        if index != self.active_tab && index < self.tabs.len() {
+            self.active_tab = index;
+            self.reset_board_view();
+        }
+    }
+
+    /// Close the current tab, asking first if that would throw away unsaved edits
+    fn request_close_tab(&mut self) {
+        if self.editing_model().is_dirty() {
+            self.popup_data = Some(PopupData {
+                message: "The board has unsaved changes. Save them before closing it?".to_string(),
+                popup_type: PopupType::YesNo {
+                    on_yes: |app| save_board_dialog(app, App::close_tab),
+                    on_no: Some(App::close_tab),
+                },
+            });
+        } else {
+            self.close_tab();
+        }
+    }
+
+    /// Close the current tab without saving. Closing the last one goes back to the startup
+    /// screen
+    fn close_tab(&mut self) {
+        self.tabs.remove(self.active_tab);
+        if self.tabs.is_empty() {
+            self.tabs.push(BoardTab::new(EditingModel::default()));
+            self.mode = AppMode::Startup;
+        }
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        self.reset_board_view();
+    }
+
+    /// Forget the editor state that belonged to the board that was being edited
+    fn reset_board_view(&mut self) {
+        self.selected_tile_pos = None;
+        self.cursor_pos = None;
+        self.selection = None;
+        self.branch_preview = None;
+        self.simulation = None;
+        self.scroll_target = None;
+        self.jump_highlight = None;
+        self.editor_viewport = None;
+    }
+
+    /// Fewest moves that win the editing board, only solving again once the board changes
+    fn cached_solution(&mut self) -> Option<SolverStats> {
+        let revision = self.editing_model().get_revision();
+        match self.solution {
+            Some((solved_revision, stats)) if solved_revision == revision => stats,
+            _ => {
+                let stats = self.editing_model().solution_stats();
+                self.solution = Some((revision, stats));
+                stats
+            }
+        }
+    }
+
+    /// Tiles every shortest win of the editing board lands on, only solving again once the
+    /// board changes
+    fn cached_critical_tiles(&mut self) -> HashSet<(usize, usize)> {
+        let revision = self.editing_model().get_revision();
+        match &self.critical_tiles {
+            Some((solved_revision, tiles)) if *solved_revision == revision => tiles.clone(),
+            _ => {
+                let tiles = self.editing_model().critical_tiles();
+                self.critical_tiles = Some((revision, tiles.clone()));
+                tiles
+            }
+        }
+    }
+
+    /// Tiles the player can reach on the editing board, only searching again once the board
+    /// changes
+    fn cached_reachable_tiles(&mut self) -> HashSet<(usize, usize)> {
+        let revision = self.editing_model().get_revision();
+        match &self.reachable_tiles {
+            Some((searched_revision, tiles)) if *searched_revision == revision => tiles.clone(),
+            _ => {
+                let tiles = self.editing_model().reachable_tiles();
+                self.reachable_tiles = Some((revision, tiles.clone()));
+                tiles
+            }
+        }
+    }
+
+    /// Directions slides carry the player over each ice tile of the editing board, only
+    /// working them out again once the board changes
+    fn cached_ice_momentum(&mut self) -> IceMomentum {
+        let revision = self.editing_model().get_revision();
+        match &self.ice_momentum {
+            Some((found_revision, momentum)) if *found_revision == revision => momentum.clone(),
+            _ => {
+                let momentum = self.editing_model().ice_momentum();
+                self.ice_momentum = Some((revision, momentum.clone()));
+                momentum
+            }
+        }
+    }
+
+    /// Whether a move in play leaves the level winnable. Only a level that's winnable to begin
+    /// with can be made unwinnable, so once the player goes ahead with such a move the later
+    /// ones aren't questioned
+    fn move_keeps_win(&mut self, movement: PlayerMovementData) -> bool {
+        !self
+            .playing_model
+            .is_winnable(UNWINNABLE_CHECK_STATES, &mut self.winnable_cache)
+            || self.playing_model.move_keeps_win(
+                movement,
+                UNWINNABLE_CHECK_STATES,
+                &mut self.winnable_cache,
+            )
+    }
+
+    /// Whether a key may be placed on a tile. A key the player couldn't use there shows a
+    /// warning saying why, and is only placed if incompatible keys are allowed. Nothing can
+    /// force a key onto an empty tile
+    fn allow_key_placement(&mut self, key: &KeyItem, tile: &Tile) -> bool {
+        match key.incompatibility(tile) {
+            None => true,
+            Some(reason) => {
+                self.key_warning = Some((reason, Instant::now()));
+                self.allow_incompatible_keys && *tile != Tile::Empty
+            }
+        }
+    }
+
     pub fn get_movement_data(&mut self) -> Option<PlayerMovementData> {
         if !self.key_state.keys_pressed_this_frame {
             return None;
@@ -367,8 +1229,12 @@ impl App {
             self.key_state.right,
             self.key_state.down,
             self.key_state.left,
-            if self.key_state.space { 2 } else { 1 }, // move_speed
-            self.key_state.enter,                     // use_tile
+            if self.key_state.space {
+                self.playing_model.get_run_speed()
+            } else {
+                1
+            }, // move_speed
+            self.key_state.enter, // use_tile
         );
 
         // Clear the key state after consuming it
@@ -386,7 +1252,18 @@ impl App {
 
 fn update_key_state(ui: &mut egui::Ui, app: &mut App) {
     let current_time = ui.input(|i| i.time);
+    let repeat = app.settings.key_repeat && matches!(app.mode, AppMode::Playing);
     let mut any_key_pressed = false;
+
+    // Controller buttons act as the keys they're mapped to in play, a press being a button
+    // that wasn't held the frame before
+    let previous_gamepad = std::mem::replace(&mut app.gamepad_state, app.gamepad.poll());
+    let gamepad = if matches!(app.mode, AppMode::Playing) {
+        app.gamepad_state
+    } else {
+        GamepadState::default()
+    };
+
     app.key_state.up = false;
     app.key_state.right = false;
     app.key_state.down = false;
@@ -394,47 +1271,109 @@ fn update_key_state(ui: &mut egui::Ui, app: &mut App) {
     app.key_state.space = false;
 
     ui.input(|i| {
-        // Check for key presses (not just key down)
-        if i.key_pressed(egui::Key::ArrowUp) {
+        if i
+            .events
+            .iter()
+            .any(|event| matches!(event, egui::Event::Touch { .. }))
+        {
+            app.touch_detected = true;
+        }
+
+        // Check for key presses (not just key down). With hold to repeat on, the system's own
+        // key repeats are left out so they can't move the player on top of the repeat timer
+        let pressed = |key| {
+            i.events.iter().any(|event| {
+                matches!(event, egui::Event::Key { key: k, pressed: true, repeat: system_repeat, .. }
+                    if *k == key && !(repeat && *system_repeat))
+            })
+        };
+        if pressed(egui::Key::ArrowUp) {
             app.key_state.up = true;
             any_key_pressed = true;
         }
-        if i.key_pressed(egui::Key::ArrowDown) {
+        if pressed(egui::Key::ArrowDown) {
             app.key_state.down = true;
             any_key_pressed = true;
         }
-        if i.key_pressed(egui::Key::ArrowLeft) {
+        if pressed(egui::Key::ArrowLeft) {
             app.key_state.left = true;
             any_key_pressed = true;
         }
-        if i.key_pressed(egui::Key::ArrowRight) {
+        if pressed(egui::Key::ArrowRight) {
             app.key_state.right = true;
             any_key_pressed = true;
         }
-        if i.key_down(egui::Key::Space) {
+        let directions = [
+            &mut app.key_state.up,
+            &mut app.key_state.down,
+            &mut app.key_state.left,
+            &mut app.key_state.right,
+        ];
+        for ((direction, held), was_held) in directions
+            .into_iter()
+            .zip(gamepad.directions())
+            .zip(previous_gamepad.directions())
+        {
+            if held && !was_held {
+                *direction = true;
+                any_key_pressed = true;
+            }
+        }
+        let direction_pressed = any_key_pressed;
+        if i.key_down(egui::Key::Space) || gamepad.run {
             app.key_state.space = true;
             any_key_pressed = true;
         }
-        if i.key_pressed(egui::Key::Enter) {
+        if i.key_pressed(egui::Key::Enter) || (gamepad.use_tile && !previous_gamepad.use_tile) {
             app.key_state.enter = true;
             any_key_pressed = true;
         }
 
         // Tad hacky but should work. If any key was pressed this frame also check for keys down (to allow multidirectional input)
         if any_key_pressed {
-            if i.key_down(egui::Key::ArrowUp) {
+            if i.key_down(egui::Key::ArrowUp) || gamepad.up {
                 app.key_state.up = true;
             }
-            if i.key_down(egui::Key::ArrowDown) {
+            if i.key_down(egui::Key::ArrowDown) || gamepad.down {
                 app.key_state.down = true;
             }
-            if i.key_down(egui::Key::ArrowLeft) {
+            if i.key_down(egui::Key::ArrowLeft) || gamepad.left {
                 app.key_state.left = true;
             }
-            if i.key_down(egui::Key::ArrowRight) {
+            if i.key_down(egui::Key::ArrowRight) || gamepad.right {
                 app.key_state.right = true;
             }
         }
+
+        // Held directions move again after the repeat delay, then every repeat interval, but
+        // only between moves so a repeat never queues up behind an animation
+        let arrow_keys = [
+            egui::Key::ArrowUp,
+            egui::Key::ArrowDown,
+            egui::Key::ArrowLeft,
+            egui::Key::ArrowRight,
+        ];
+        let gamepad_held = gamepad.directions();
+        let held: [bool; 4] =
+            std::array::from_fn(|index| i.key_down(arrow_keys[index]) || gamepad_held[index]);
+        if !repeat || !held.contains(&true) {
+            app.key_state.repeat_at = None;
+        } else if direction_pressed {
+            app.key_state.repeat_at = Some(current_time + app.settings.key_repeat_delay);
+        } else if app
+            .key_state
+            .repeat_at
+            .is_some_and(|repeat_at| current_time >= repeat_at)
+            && app.playing_model.animation_state.is_none()
+        {
+            let [up, down, left, right] = held;
+            app.key_state.up = up;
+            app.key_state.down = down;
+            app.key_state.left = left;
+            app.key_state.right = right;
+            any_key_pressed = true;
+            app.key_state.repeat_at = Some(current_time + app.settings.key_repeat_interval);
+        }
     });
 
     if any_key_pressed {
@@ -449,85 +1388,173 @@ fn update_key_state(ui: &mut egui::Ui, app: &mut App) {
     Draw tile
 */
 
-fn draw_tile_and_key(
+/// Shapes drawing a tile and its key into `rect`, everything but the interactive overlays.
+/// Built as shapes rather than painted, so the editing board can keep them between frames
+fn tile_shapes(
+    ctx: &egui::Context,
     tile: &Tile,
     key: &KeyItem,
-    ui: &mut egui::Ui,
     app: &App,
-    player: bool,
-) -> egui::Response {
-    let (rect, mut response) =
-        ui.allocate_exact_size(egui::Vec2 { x: 32.0, y: 32.0 }, egui::Sense::click());
-    let painter = ui.painter_at(rect);
+    palette: &OverlayPalette,
+    rect: egui::Rect,
+    background: Option<egui::Color32>, // Painted in place of the empty tile texture
+) -> Vec<egui::Shape> {
+    let mut shapes = Vec::new();
+    let scale = rect.width() / TILE_PX; // Overlays are laid out for 32px tiles
+    let full_uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::Pos2::new(1.0, 1.0));
+    let text = |shapes: &mut Vec<egui::Shape>, pos, text: String, size: f32, color| {
+        let galley =
+            ctx.fonts(|fonts| fonts.layout_no_wrap(text, egui::FontId::monospace(size), color));
+        let rect = egui::Align2::CENTER_CENTER.anchor_size(pos, galley.size());
+        shapes.push(egui::Shape::galley(rect.min, galley, color));
+    };
+    // Same as `Painter::arrow`
+    let arrow = |shapes: &mut Vec<egui::Shape>, origin: egui::Pos2, vec: egui::Vec2, stroke| {
+        let rot = egui::emath::Rot2::from_angle(std::f32::consts::TAU / 10.0);
+        let tip_length = vec.length() / 4.0;
+        let tip = origin + vec;
+        let dir = vec.normalized();
+        shapes.push(egui::Shape::line_segment([origin, tip], stroke));
+        shapes.push(egui::Shape::line_segment(
+            [tip, tip - tip_length * (rot * dir)],
+            stroke,
+        ));
+        shapes.push(egui::Shape::line_segment(
+            [tip, tip - tip_length * (rot.inverse() * dir)],
+            stroke,
+        ));
+    };
 
-    if let Some(texture) = app.texture_cache.get(tile.file_name()) {
-        painter.image(
+    if let Some(background) = background {
+        shapes.push(egui::Shape::rect_filled(rect, 0.0, background));
+    }
+    if let Some(texture) = app.texture_cache.get(tile.file_name())
+        && (background.is_none() || *tile != Tile::Empty)
+    {
+        shapes.push(egui::Shape::image(
             texture.id(),
             rect,
-            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::Pos2::new(1.0, 1.0)),
+            full_uv,
             egui::Color32::WHITE,
-        );
+        ));
     }
 
-    // Draw overlays
+    // Draw overlays. Movement arrows can be hidden in play for a cleaner look
+    let show_move_arrows = app.settings.show_move_arrows || !matches!(app.mode, AppMode::Playing);
     match &tile {
-        Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+        Tile::MoveCardinal(directions) | Tile::Cloud(directions) if show_move_arrows => {
             let center = rect.center();
-            let offset = 10.0;
-            let arrow_color = egui::Stroke::new(2.0, egui::Color32::BLACK);
+            let offset = 10.0 * scale;
+            let arrow_color = egui::Stroke::new(2.0 * scale, palette.move_arrow);
 
             if directions.up {
-                painter.arrow(center, egui::vec2(0.0, -offset), arrow_color);
+                arrow(&mut shapes, center, egui::vec2(0.0, -offset), arrow_color);
             }
             if directions.right {
-                painter.arrow(center, egui::vec2(offset, 0.0), arrow_color);
+                arrow(&mut shapes, center, egui::vec2(offset, 0.0), arrow_color);
             }
             if directions.down {
-                painter.arrow(center, egui::vec2(0.0, offset), arrow_color);
+                arrow(&mut shapes, center, egui::vec2(0.0, offset), arrow_color);
             }
             if directions.left {
-                painter.arrow(center, egui::vec2(-offset, 0.0), arrow_color);
+                arrow(&mut shapes, center, egui::vec2(-offset, 0.0), arrow_color);
             }
         }
-        Tile::MoveDiagonal(directions) => {
+        Tile::MoveDiagonal(directions) if show_move_arrows => {
             let center = rect.center();
-            let offset = 10.0;
-            let arrow_color = egui::Stroke::new(2.0, egui::Color32::BLACK);
+            let offset = 10.0 * scale;
+            let arrow_color = egui::Stroke::new(2.0 * scale, palette.move_arrow);
 
             if directions.up_right {
-                painter.arrow(center, egui::vec2(offset, -offset), arrow_color);
+                arrow(
+                    &mut shapes,
+                    center,
+                    egui::vec2(offset, -offset),
+                    arrow_color,
+                );
             }
             if directions.down_right {
-                painter.arrow(center, egui::vec2(offset, offset), arrow_color);
+                arrow(&mut shapes, center, egui::vec2(offset, offset), arrow_color);
             }
             if directions.down_left {
-                painter.arrow(center, egui::vec2(-offset, offset), arrow_color);
+                arrow(
+                    &mut shapes,
+                    center,
+                    egui::vec2(-offset, offset),
+                    arrow_color,
+                );
             }
             if directions.up_left {
-                painter.arrow(center, egui::vec2(-offset, -offset), arrow_color);
+                arrow(
+                    &mut shapes,
+                    center,
+                    egui::vec2(-offset, -offset),
+                    arrow_color,
+                );
             }
         }
-        Tile::Bounce(val) => {
-            let text = if *val > 0 {
-                format!("+{val}")
-            } else {
-                val.to_string()
+        Tile::Bounce(_) | Tile::SpeedPad(_) | Tile::SpeedGate(_) | Tile::Switch(_) => {
+            let label = match tile {
+                Tile::Bounce(val) if *val > 0 => format!("+{val}"),
+                Tile::Bounce(val) => val.to_string(),
+                Tile::SpeedPad(speed) | Tile::SpeedGate(speed) => speed.to_string(),
+                Tile::Switch(count) => count.to_string(),
+                _ => unreachable!(),
             };
-            painter.text(
+            text(
+                &mut shapes,
                 rect.center(),
-                egui::Align2::CENTER_CENTER,
-                text,
-                egui::FontId::monospace(16.0),
-                egui::Color32::RED,
+                label,
+                16.0 * scale,
+                palette.number,
             );
         }
-        Tile::Portal(c, _) => {
-            painter.text(
-                rect.center(),
-                egui::Align2::CENTER_CENTER,
-                c.to_string(),
-                egui::FontId::monospace(30.0),
-                egui::Color32::GREEN,
+        Tile::Blinker(phase) => {
+            // Drawn for the first turn, the playing board passes in the phase for the current one
+            if *phase {
+                shapes.push(egui::Shape::rect_filled(
+                    rect.shrink(6.0 * scale),
+                    0.0,
+                    egui::Color32::from_black_alpha(180),
+                ));
+            } else {
+                shapes.push(egui::Shape::rect_stroke(
+                    rect.shrink(6.0 * scale),
+                    0.0,
+                    egui::Stroke::new(2.0 * scale, palette.blinker),
+                    egui::StrokeKind::Inside,
+                ));
+            }
+        }
+        Tile::ResetTrap(true) => {
+            // Takes the player's keys too
+            shapes.push(egui::Shape::circle_stroke(
+                rect.center(),
+                12.0 * scale,
+                egui::Stroke::new(2.0 * scale, palette.hazard),
+            ));
+        }
+        Tile::Portal(c, _) => {
+            // Tinted badge behind the letter, so each pair of portals is grouped by color
+            let color = palette.portal_color(*c);
+            let label = palette.portal_label(*c);
+            let size = if label.len() > 1 { 14.0 } else { 20.0 }; // Fit a prefix in the badge
+            shapes.push(egui::Shape::Circle(egui::epaint::CircleShape {
+                center: rect.center(),
+                radius: 12.0 * scale,
+                fill: egui::Color32::BLACK.gamma_multiply(0.5),
+                stroke: egui::Stroke::new(2.0 * scale, color),
+            }));
+            text(&mut shapes, rect.center(), label, size * scale, color);
+        }
+        Tile::Arrow(direction) => {
+            let (row_offset, col_offset) = direction.offset();
+            let offset = egui::vec2(col_offset as f32, row_offset as f32) * 12.0 * scale;
+            arrow(
+                &mut shapes,
+                rect.center() - offset,
+                offset * 2.0,
+                egui::Stroke::new(3.0 * scale, palette.hazard),
             );
         }
         _ => {}
@@ -535,59 +1562,449 @@ fn draw_tile_and_key(
 
     if *key != KeyItem::None {
         // Calculate 8x8 rect in lower right corner
-        let key_size = 12.0;
+        let key_size = 12.0 * scale;
         let key_rect = egui::Rect::from_min_size(
             egui::Pos2::new(rect.max.x - key_size, rect.max.y - key_size),
             egui::Vec2::splat(key_size),
         );
 
         if let Some(texture) = app.texture_cache.get(key.file_name()) {
-            painter.image(
+            shapes.push(egui::Shape::image(
                 texture.id(),
                 key_rect,
-                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::Pos2::new(1.0, 1.0)),
+                full_uv,
                 egui::Color32::WHITE,
-            );
+            ));
         }
 
         // Key overlay
-        if let Some(text) = key.overlay() {
-            painter.text(
+        if let Some(overlay) = key.overlay() {
+            text(
+                &mut shapes,
                 key_rect.center(),
-                egui::Align2::CENTER_CENTER,
-                text,
-                egui::FontId::monospace(16.0),
-                egui::Color32::RED,
+                overlay.to_string(),
+                16.0 * scale,
+                palette.key_letter,
             );
         }
     }
 
-    if *tile == Tile::Empty {
-        if *key == KeyItem::None {
-            ui.painter().rect_stroke(
-                response.rect,
-                0.0,
-                egui::Stroke::new(0.5, egui::Color32::from_white_alpha(64)),
-                egui::StrokeKind::Outside,
-            );
-            response = response.on_hover_text(tile.explanation());
-        } else {
-            response = response.on_hover_text(key.explanation());
-        }
+    shapes
+}
+
+/// Outline drawn around an empty tile, if the grid is shown
+fn grid_shape(tile: &Tile, key: &KeyItem, app: &App, rect: egui::Rect) -> Option<egui::Shape> {
+    let [r, g, b, a] = app.settings.grid_color;
+    (app.settings.show_grid && *tile == Tile::Empty && *key == KeyItem::None).then(|| {
+        egui::Shape::rect_stroke(
+            rect,
+            0.0,
+            egui::Stroke::new(0.5, egui::Color32::from_rgba_premultiplied(r, g, b, a)),
+            egui::StrokeKind::Outside,
+        )
+    })
+}
+
+/// What hovering a tile explains: the key on an otherwise empty tile, or else the tile
+fn tile_hover_text(tile: &Tile, key: &KeyItem, tile_explanation: Option<String>) -> String {
+    if *tile == Tile::Empty && *key != KeyItem::None {
+        key.explanation().to_string()
     } else {
-        response = response.on_hover_text(tile.explanation());
+        tile_explanation.unwrap_or_else(|| tile.explanation().to_string())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_tile_and_key(
+    tile: &Tile,
+    key: &KeyItem,
+    ui: &mut egui::Ui,
+    app: &App,
+    player: bool,
+    tile_px: f32,
+    tile_explanation: Option<String>, // Replaces the static tile explanation, e.g. with live state in play
+    highlight_goals: bool,            // Pulse a ring around the start and end spaces, in play
+    background: Option<egui::Color32>, // Painted in place of the empty tile texture
+) -> egui::Response {
+    let (rect, mut response) =
+        ui.allocate_exact_size(egui::Vec2::splat(tile_px), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    let scale = tile_px / TILE_PX; // Overlays are laid out for 32px tiles
+
+    let palette = app.settings.color_palette.overlay_palette();
+    painter.extend(tile_shapes(
+        ui.ctx(),
+        tile,
+        key,
+        app,
+        &palette,
+        rect,
+        background,
+    ));
+    if let Some(grid) = grid_shape(tile, key, app, rect) {
+        ui.painter().add(grid); // Outside the tile, so not clipped to it
+    }
+    response = response.on_hover_text(tile_hover_text(tile, key, tile_explanation));
+
+    if highlight_goals && matches!(tile, Tile::StartSpace | Tile::EndSpace) {
+        let color = if *tile == Tile::EndSpace {
+            egui::Color32::GREEN
+        } else {
+            egui::Color32::BLUE
+        };
+        let pulse = (ui.input(|i| i.time) * 4.0).sin() as f32 * 0.5 + 0.5;
+        painter.circle_stroke(
+            rect.center(),
+            (10.0 + 4.0 * pulse) * scale,
+            egui::Stroke::new(2.0 * scale, color.gamma_multiply(0.4 + 0.6 * pulse)),
+        );
+        ui.ctx().request_repaint_after(GOAL_PULSE_REPAINT); // Keep the pulse animating
     }
 
     if player {
-        // Draw player position indicator as a red circle in top right corner
-        let circle_radius = 8.0;
-        let circle_center = egui::Pos2::new(rect.max.x - circle_radius, rect.min.y + circle_radius);
-        painter.circle_filled(circle_center, circle_radius, egui::Color32::BLACK);
+        draw_player(&painter, player_rect(rect, scale), app, 1.0);
     }
 
     response
 }
 
+/// Where the player is drawn on their tile, in the top right corner
+fn player_rect(tile_rect: egui::Rect, scale: f32) -> egui::Rect {
+    let player_size = 16.0 * scale;
+    egui::Rect::from_min_size(
+        egui::Pos2::new(tile_rect.max.x - player_size, tile_rect.min.y),
+        egui::Vec2::splat(player_size),
+    )
+}
+
+/// The player as their sprite if they picked one, otherwise as their colored shape, with
+/// `opacity` from 0 to 1
+fn draw_player(painter: &egui::Painter, rect: egui::Rect, app: &App, opacity: f32) {
+    if app.settings.player_sprite.is_some()
+        && let Some(texture) = app.texture_cache.get(PLAYER_SPRITE_TEXTURE)
+    {
+        painter.image(
+            texture.id(),
+            rect,
+            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::Pos2::new(1.0, 1.0)),
+            egui::Color32::WHITE.gamma_multiply(opacity),
+        );
+        return;
+    }
+
+    let [r, g, b] = app.settings.player_color;
+    let color = egui::Color32::from_rgb(r, g, b).gamma_multiply(opacity);
+    match app.settings.player_shape {
+        PlayerShape::Circle => {
+            painter.circle_filled(rect.center(), rect.width() / 2.0, color);
+        }
+        PlayerShape::Square => {
+            painter.rect_filled(rect.shrink(rect.width() * 0.1), 0.0, color);
+        }
+        PlayerShape::Triangle => {
+            painter.add(egui::Shape::convex_polygon(
+                vec![rect.center_top(), rect.right_bottom(), rect.left_bottom()],
+                color,
+                egui::Stroke::NONE,
+            ));
+        }
+    }
+}
+
+/*
+    Settings
+*/
+
+fn display_settings_window(ctx: &egui::Context, app: &mut App) {
+    let mut open = app.show_settings;
+    let mut changed = false;
+
+    egui::Window::new("Settings")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Animation speed (seconds per tile):");
+                changed |= ui
+                    .add(egui::Slider::new(
+                        &mut app.settings.animation_speed,
+                        MIN_ANIMATION_SPEED..=MAX_ANIMATION_SPEED,
+                    ))
+                    .changed();
+            });
+            changed |= ui
+                .checkbox(&mut app.settings.show_grid, "Show grid")
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.show_coordinates,
+                    "Show tile coordinates in the editor",
+                )
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.collision_flash,
+                    "Flash the board when hitting a wall",
+                )
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.show_move_arrows,
+                    "Show movement tile arrows in play",
+                )
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.show_ice_momentum_in_play,
+                    "Show ice momentum arrows in play",
+                )
+                .changed();
+            changed |= ui
+                .checkbox(&mut app.settings.show_frame_rate, "Show frame rate")
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.key_repeat,
+                    "Keep moving while a direction key is held in play",
+                )
+                .changed();
+            ui.add_enabled_ui(app.settings.key_repeat, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Repeat after (seconds):");
+                    changed |= ui
+                        .add(egui::Slider::new(
+                            &mut app.settings.key_repeat_delay,
+                            KEY_REPEAT_DELAY_RANGE,
+                        ))
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Repeat every (seconds):");
+                    changed |= ui
+                        .add(egui::Slider::new(
+                            &mut app.settings.key_repeat_interval,
+                            KEY_REPEAT_INTERVAL_RANGE,
+                        ))
+                        .changed();
+                });
+            });
+            changed |= ui
+                .checkbox(
+                    &mut app.settings.warn_unwinnable,
+                    "Ask before a move that makes the level unwinnable",
+                )
+                .changed();
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .checkbox(&mut app.settings.sound_effects, "Sound effects")
+                    .changed();
+                changed |= ui
+                    .add_enabled(
+                        app.settings.sound_effects,
+                        egui::Slider::new(&mut app.settings.sound_volume, 0.0..=1.0).text("Volume"),
+                    )
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Grid color:");
+                let [r, g, b, a] = app.settings.grid_color;
+                let mut color = egui::Color32::from_rgba_premultiplied(r, g, b, a);
+                if egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    &mut color,
+                    egui::color_picker::Alpha::OnlyBlend,
+                )
+                .changed()
+                {
+                    app.settings.grid_color = color.to_array();
+                    changed = true;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Player:");
+                let mut color = app.settings.player_color;
+                if ui.color_edit_button_srgb(&mut color).changed() {
+                    app.settings.player_color = color;
+                    changed = true;
+                }
+                egui::ComboBox::from_id_salt("player_shape")
+                    .selected_text(format!("{:?}", app.settings.player_shape))
+                    .show_ui(ui, |ui| {
+                        for shape in ALL_PLAYER_SHAPES {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut app.settings.player_shape,
+                                    *shape,
+                                    format!("{shape:?}"),
+                                )
+                                .changed();
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Overlay colors:");
+                egui::ComboBox::from_id_salt("color_palette")
+                    .selected_text(format!("{:?}", app.settings.color_palette))
+                    .show_ui(ui, |ui| {
+                        for palette in ALL_COLOR_PALETTES {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut app.settings.color_palette,
+                                    *palette,
+                                    format!("{palette:?}"),
+                                )
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Colors for arrows, numbers and portals, with presets for color blindness",
+                    );
+            });
+            ui.horizontal(|ui| {
+                ui.label("On-screen d-pad in play:");
+                egui::ComboBox::from_id_salt("touch_controls")
+                    .selected_text(format!("{:?}", app.settings.touch_controls))
+                    .show_ui(ui, |ui| {
+                        for touch_controls in ALL_TOUCH_CONTROLS {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut app.settings.touch_controls,
+                                    *touch_controls,
+                                    format!("{touch_controls:?}"),
+                                )
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Automatic shows it once the screen has been touched");
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Choose Player Sprite").clicked()
+                    && let Ok(file_name) =
+                        open_file_dialog_with_filter(false, "PNG Image", &["png"])
+                {
+                    let path = PathBuf::from(file_name);
+                    match load_player_sprite(ui.ctx(), Some(&path), &mut app.texture_cache) {
+                        Ok(()) => {
+                            app.settings.player_sprite = Some(path);
+                            changed = true;
+                        }
+                        Err(err) => {
+                            app.popup_data = Some(PopupData {
+                                message: format!("Error loading player sprite: {err}"),
+                                popup_type: PopupType::Ok,
+                            });
+                        }
+                    }
+                }
+                if let Some(sprite) = &app.settings.player_sprite {
+                    ui.label(sprite.file_name().unwrap_or_default().to_string_lossy());
+                    if ui.button("Use Shape").clicked() {
+                        app.settings.player_sprite = None;
+                        app.texture_cache.remove(PLAYER_SPRITE_TEXTURE);
+                        changed = true;
+                    }
+                }
+            });
+
+            // Pick up edited texture files without restarting
+            if ui.button("Reload Textures").clicked() {
+                let mut errors = reload_textures(ui.ctx(), &app.theme_dir, &mut app.texture_cache);
+                if let Err(err) = load_player_sprite(
+                    ui.ctx(),
+                    app.settings.player_sprite.as_deref(),
+                    &mut app.texture_cache,
+                ) {
+                    errors.push(format!("player sprite: {err}"));
+                }
+                if !errors.is_empty() {
+                    app.popup_data = Some(PopupData {
+                        message: format!(
+                            "Some textures couldn't be reloaded, keeping their old versions:\n{}",
+                            errors.join("\n")
+                        ),
+                        popup_type: PopupType::Ok,
+                    });
+                }
+            }
+        });
+    app.show_settings = open;
+
+    if changed {
+        app.settings.clamp();
+        if let Err(err) = app.settings.save() {
+            eprintln!("Error saving settings: {err}");
+        }
+    }
+}
+
+/*
+    Help
+*/
+
+/// Legend of every tile and key, drawn as they appear on the board
+fn display_help_window(ctx: &egui::Context, app: &mut App) {
+    let mut open = app.show_help;
+
+    egui::Window::new("Tiles and Keys")
+        .open(&mut open)
+        .collapsible(true)
+        .default_height(400.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Tiles");
+                egui::Grid::new("help_tiles")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for tile in ALL_TILES {
+                            draw_tile_and_key(
+                                tile,
+                                &KeyItem::None,
+                                ui,
+                                app,
+                                false,
+                                TILE_PX,
+                                None,
+                                false,
+                                None,
+                            );
+                            ui.strong(tile.label());
+                            ui.label(tile.explanation());
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                ui.heading("Keys");
+                egui::Grid::new("help_keys")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for key in ALL_KEYS {
+                            draw_tile_and_key(
+                                &Tile::Empty,
+                                key,
+                                ui,
+                                app,
+                                false,
+                                TILE_PX,
+                                None,
+                                false,
+                                None,
+                            );
+                            ui.label(key.explanation());
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    app.show_help = open;
+}
+
 /*
     Startup mode
 */
@@ -608,9 +2025,29 @@ fn startup_screen(ui: &mut egui::Ui, app: &mut App) {
         ui.add(egui::Slider::new(&mut app.height_slider, 5..=20).integer());
     });
 
+    // Texture theme, the default assets or a subdirectory of the themes directory
+    let mut theme_dir = app.theme_dir.clone();
+    let theme_name = |theme_dir: &Path| match theme_dir.strip_prefix(THEMES_DIR) {
+        Ok(name) => name.to_string_lossy().to_string(),
+        Err(_) => "Default".to_string(),
+    };
+    egui::ComboBox::from_label("Theme")
+        .selected_text(theme_name(&theme_dir))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut theme_dir, PathBuf::from(DEFAULT_THEME_DIR), "Default");
+            for theme in list_themes() {
+                ui.selectable_value(&mut theme_dir, Path::new(THEMES_DIR).join(&theme), theme);
+            }
+        });
+    if theme_dir != app.theme_dir {
+        app.texture_cache = load_textures(ui.ctx(), &theme_dir);
+        app.theme_dir = theme_dir;
+    }
+
     if ui.button("Start Editing").clicked() {
         // Initialize the board with the selected size
-        app.editing_model = EditingModel::new((app.height_slider, app.width_slider));
+        *app.editing_model_mut() = EditingModel::new((app.height_slider, app.width_slider));
+        app.tabs[app.active_tab].path = None;
         app.mode = AppMode::Editing;
     }
 
@@ -621,19 +2058,130 @@ fn startup_screen(ui: &mut egui::Ui, app: &mut App) {
             return;
         }
 
-        let model = EditingModel::load_board(filename.unwrap().as_str());
+        let filename = filename.unwrap();
+        match EditingModel::load_board(filename.as_str()) {
+            Ok(model) => {
+                *app.editing_model_mut() = model;
+                app.set_board_path(PathBuf::from(filename));
+                app.mode = AppMode::Editing;
+            }
+            Err(err) => eprintln!("Error loading board: {err}"),
+        }
+    }
+
+    // Boards worked on recently, greyed out once their file is gone
+    if !app.settings.recent_files.is_empty() {
+        ui.add_space(10.0);
+        ui.label("Recent boards:");
+    }
+    for path in app.settings.recent_files.clone() {
+        let exists = path.is_file();
+        let mut text = egui::RichText::new(path.display().to_string());
+        if !exists {
+            text = text.weak();
+        }
+        if ui.button(text).clicked() {
+            open_recent_board(app, path);
+        }
+    }
+}
 
-        if model.is_ok() {
-            app.editing_model = model.unwrap();
+/// Load a board from the recent files list for editing, forgetting it if it can't be loaded
+fn open_recent_board(app: &mut App, path: PathBuf) {
+    match EditingModel::load_board(&path.to_string_lossy()) {
+        Ok(model) => {
+            *app.editing_model_mut() = model;
+            app.set_board_path(path);
             app.mode = AppMode::Editing;
-        } else {
-            eprintln!("Error loading board: {}", model.unwrap_err());
+        }
+        Err(err) => {
+            app.settings.remove_recent_file(&path);
+            if let Err(err) = app.settings.save() {
+                eprintln!("Error saving settings: {err}");
+            }
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading {}: {err}", path.display()),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
+/// Load a board file picked by the user in place of the board being edited
+fn load_board_dialog(app: &mut App) {
+    let Ok(file_name) = open_file_dialog(false) else {
+        return;
+    };
+    match EditingModel::load_board(&file_name) {
+        Ok(model) => {
+            *app.editing_model_mut() = model;
+            app.set_board_path(PathBuf::from(file_name));
+            app.reset_board_view();
+        }
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading board: {err}"),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
+/// Save the editing board to a file picked by the user, asking first if it would overwrite an
+/// existing file. `on_saved` runs only once the board has actually been saved
+fn save_board_dialog(app: &mut App, on_saved: fn(&mut App)) {
+    let Ok(file_name) = open_file_dialog(true) else {
+        return;
+    };
+    let path = PathBuf::from(file_name);
+    if !path.exists() {
+        save_board_to(app, path, on_saved);
+        return;
+    }
+
+    app.popup_data = Some(PopupData {
+        message: format!("{} already exists. Overwrite it?", path.display()),
+        popup_type: PopupType::YesNo {
+            on_yes: |app| {
+                if let Some((path, on_saved)) = app.pending_save.take() {
+                    save_board_to(app, path, on_saved);
+                }
+            },
+            on_no: Some(|app| app.pending_save = None),
+        },
+    });
+    app.pending_save = Some((path, on_saved));
+}
+
+fn save_board_to(app: &mut App, path: PathBuf, on_saved: fn(&mut App)) {
+    match app.editing_model_mut().save_board(&path.to_string_lossy()) {
+        Ok(()) => {
+            app.set_board_path(path);
+            on_saved(app);
+        }
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error saving board: {err}"),
+                popup_type: PopupType::Ok,
+            });
         }
     }
 }
 
 fn open_file_dialog(is_save: bool) -> Result<String, String> {
-    let dialog = FileDialog::new().add_filter("Foam Game Board", &["fg"]);
+    open_file_dialog_with_filter(is_save, "Foam Game Board", &["fg"])
+}
+
+fn open_ascii_file_dialog(is_save: bool) -> Result<String, String> {
+    open_file_dialog_with_filter(is_save, "Foam Game ASCII Board", &["txt"])
+}
+
+fn open_file_dialog_with_filter(
+    is_save: bool,
+    description: &str,
+    extensions: &[&str],
+) -> Result<String, String> {
+    let dialog = FileDialog::new().add_filter(description, extensions);
 
     let file_path = if is_save {
         dialog.set_title("Save Board").show_save_single_file()
@@ -654,165 +2202,1352 @@ fn open_file_dialog(is_save: bool) -> Result<String, String> {
 */
 
 fn editing_screen(ui: &mut egui::Ui, app: &mut App) {
-    ui.label("Editing Mode");
+    egui::SidePanel::right("tile_inspector")
+        .resizable(false)
+        .show_inside(ui, |ui| display_tile_inspector(ui, app));
+    display_tab_bar(ui, app);
+    ui.horizontal(|ui| {
+        ui.label(match app.editor_focus {
+            EditorFocus::EditTile => "Editing Mode (Tab: navigate with arrow keys)",
+            EditorFocus::Navigate => "Editing Mode (Tab: edit tile with arrow keys)",
+        });
+        ui.separator();
+        let solution = app.cached_solution();
+        ui.label(match solution.map(|stats| stats.moves) {
+            Some(1) => "Optimal: 1 move".to_string(),
+            Some(moves) => format!("Optimal: {moves} moves"),
+            None => "Optimal: no solution found".to_string(),
+        });
+        if let Some(stats) = solution {
+            ui.separator();
+            ui.label(format!("Difficulty: {}", stats.difficulty_score()))
+                .on_hover_text(format!(
+                    "Estimated from the {} moves to win, {} keys picked up on the way, and {} \
+                     states searched",
+                    stats.moves, stats.keys_picked_up, stats.states
+                ));
+        }
+    });
     display_editing_menu(ui, app);
-    ui.add_space(25.0);
+    ui.add_space(10.0);
+    display_minimap(ui, app);
+    ui.add_space(10.0);
     display_editing_board(ui, app);
+    display_key_warning(ui.ctx(), app);
+
+    // Tab switches the arrow keys between editing the selected tile and moving the cursor
+    if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+        app.editor_focus = match app.editor_focus {
+            EditorFocus::EditTile => {
+                app.cursor_pos = app.cursor_pos.or(app.selected_tile_pos).or(Some((0, 0)));
+                EditorFocus::Navigate
+            }
+            EditorFocus::Navigate => EditorFocus::EditTile,
+        };
+    }
+    // Keep the cursor on the board, e.g. after loading a smaller one
+    let board_size = app.editing_model().get_board_size();
+    app.cursor_pos = app
+        .cursor_pos
+        .map(|(row, col)| (row.min(board_size.0 - 1), col.min(board_size.1 - 1)));
+    if app.editor_focus == EditorFocus::Navigate {
+        app.selected_tile_pos = app.cursor_pos;
+    }
 
     if let Some(keypress) = app.get_movement_data() {
-        if let Some(KeyItem::OnUse(key_on_use)) = &mut app.selected_key {
-            let (key_up, _, key_down, _) = direction_key_into_bools(&keypress.direction);
-            match key_on_use {
-                KeyOnUse::TeleportKey(c) => {
-                    if key_up {
-                        *c = match *c {
-                            'A'..='Y' => (*c as u8 + 1) as char,
-                            'Z' => 'A',
-                            _ => 'A',
-                        };
-                    } else if key_down {
-                        *c = match *c {
-                            'B'..='Z' => (*c as u8 - 1) as char,
-                            'A' => 'Z',
-                            _ => 'Z',
-                        };
-                    }
+        if app.editor_focus == EditorFocus::Navigate {
+            if let Some(cursor_pos) = app.cursor_pos {
+                let (row_offset, col_offset) = keypress.direction.offset();
+                let new_pos = (
+                    cursor_pos
+                        .0
+                        .saturating_add_signed(row_offset)
+                        .min(board_size.0 - 1),
+                    cursor_pos
+                        .1
+                        .saturating_add_signed(col_offset)
+                        .min(board_size.1 - 1),
+                );
+                app.cursor_pos = Some(new_pos);
+                app.selected_tile_pos = Some(new_pos);
+
+                if keypress.use_tile {
+                    apply_brush(app, new_pos);
                 }
             }
+        } else if let Some(selected_key) = &mut app.selected_key
+            && selected_key.overlay().is_some()
+        {
+            // Up and down pick the letter of a teleport or door key brush
+            let (key_up, _, key_down, _) = direction_key_into_bools(&keypress.direction);
+            if key_up || key_down {
+                selected_key.cycle_letter(key_up);
+            }
         } else if let Some(selected_tile_pos) = app.selected_tile_pos {
-            app.editing_model.edit_tile(selected_tile_pos, &keypress);
+            app.editing_model_mut()
+                .edit_tile(selected_tile_pos, &keypress);
+        }
+    }
+
+    // Ctrl+C copies the selection, Ctrl+V pastes it at the cursor
+    if !ui.ctx().wants_keyboard_input() {
+        let (copy, paste) = ui.input(|i| {
+            (
+                i.events
+                    .iter()
+                    .any(|event| matches!(event, egui::Event::Copy)),
+                !i.modifiers.shift // Ctrl+Shift+V plays a pasted ASCII board instead
+                    && i.events
+                        .iter()
+                        .any(|event| matches!(event, egui::Event::Paste(_))),
+            )
+        });
+        if copy && let Some((start, end)) = app.selection {
+            app.clipboard = app.editing_model().copy_region(start, end);
+        }
+        if paste && let Some(selected_tile_pos) = app.selected_tile_pos {
+            app.tabs[app.active_tab]
+                .model
+                .paste_region(selected_tile_pos, &app.clipboard);
+        }
+    }
+
+    // Escape drops the selection and any branch preview, unless it's closing a popup
+    if app.popup_data.is_none()
+        && !ui.ctx().wants_keyboard_input()
+        && ui.input(|i| i.key_pressed(egui::Key::Escape))
+    {
+        app.selection = None;
+        app.branch_preview = None;
+    }
+
+    // X and Z swap back to the previous tile and key brushes
+    if !ui.ctx().wants_keyboard_input() {
+        let (swap_tile, swap_key) =
+            ui.input(|i| (i.key_pressed(egui::Key::X), i.key_pressed(egui::Key::Z)));
+        if swap_tile && let Some(previous) = app.previous_selected_type.clone() {
+            app.select_tile_brush(previous, false);
+        }
+        if swap_key && let Some(previous) = app.previous_selected_key.clone() {
+            app.select_key_brush(previous, false);
         }
     }
+
+    // R rotates the selected tile, unless typing in a text box
+    if !ui.ctx().wants_keyboard_input()
+        && ui.input(|i| i.key_pressed(egui::Key::R))
+        && let Some(selected_tile_pos) = app.selected_tile_pos
+    {
+        app.editing_model_mut().rotate_tile(selected_tile_pos);
+    }
 }
 
-fn display_editing_menu(ui: &mut egui::Ui, app: &mut App) {
-    // Display menus and buttons for editing the board
-    ui.vertical(|ui| {
-        ui.horizontal(|ui| {
-            // Add UI buttons to change modes and save/load the board
-            if ui.button("Switch to Playing Mode").clicked()
-                && app.editing_model.board_is_playable()
-            {
-                app.mode = AppMode::Playing;
-                app.playing_model = PlayingModel::new(&app.editing_model); // Initialize playing model
-            }
-            if ui.button("Save Board").clicked() {
-                let file_name = open_file_dialog(true);
-                if let Ok(file_name) = file_name {
-                    let _ = app.editing_model.save_board(file_name.as_str());
+/// Every property of the selected tile and its key as widgets, edited straight on the board
+fn display_tile_inspector(ui: &mut egui::Ui, app: &mut App) {
+    ui.heading("Tile Inspector");
+    let Some(pos) = app.selected_tile_pos else {
+        ui.label("Click a tile to inspect it.");
+        return;
+    };
+    let Some(tile_data) = app
+        .editing_model()
+        .get_board()
+        .get(pos.0)
+        .and_then(|row| row.get(pos.1))
+        .cloned()
+    else {
+        return;
+    };
+    ui.label(format!("Row {}, column {}", pos.0, pos.1));
+
+    let mut tile = tile_data.tile.clone();
+    egui::ComboBox::from_label("Tile")
+        .selected_text(tile.label())
+        .show_ui(ui, |ui| {
+            for option in ALL_TILES {
+                let same_kind = std::mem::discriminant(option) == std::mem::discriminant(&tile);
+                if ui.selectable_label(same_kind, option.label()).clicked() && !same_kind {
+                    tile = option.clone();
                 }
             }
-            if ui.button("Load Board").clicked() {
-                let file_name = open_file_dialog(false);
-                if let Ok(file_name) = file_name {
-                    let model = EditingModel::load_board(file_name.as_str());
-                    if model.is_ok() {
-                        app.editing_model = model.unwrap();
+        });
+
+    match &mut tile {
+        Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut directions.up, "Up");
+                ui.checkbox(&mut directions.right, "Right");
+                ui.checkbox(&mut directions.down, "Down");
+                ui.checkbox(&mut directions.left, "Left");
+            });
+        }
+        Tile::MoveDiagonal(directions) => {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut directions.up_right, "Up right");
+                ui.checkbox(&mut directions.down_right, "Down right");
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut directions.down_left, "Down left");
+                ui.checkbox(&mut directions.up_left, "Up left");
+            });
+        }
+        Tile::Bounce(amount) => {
+            ui.horizontal(|ui| {
+                ui.label("Bounce:");
+                ui.add(egui::DragValue::new(amount).range(BOUNCE_RANGE));
+            });
+        }
+        Tile::SpeedPad(speed) => {
+            ui.horizontal(|ui| {
+                ui.label("Speed:");
+                ui.add(egui::DragValue::new(speed).range(SPEED_PAD_RANGE));
+            });
+        }
+        Tile::SpeedGate(speed) => {
+            ui.horizontal(|ui| {
+                ui.label("Required speed:");
+                ui.add(egui::DragValue::new(speed).range(SPEED_GATE_RANGE));
+            });
+        }
+        Tile::Switch(count) => {
+            ui.horizontal(|ui| {
+                ui.label("Steps to open:");
+                ui.add(egui::DragValue::new(count).range(SWITCH_RANGE));
+            });
+        }
+        Tile::Portal(letter, _) => {
+            letter_combo(ui, "Portal letter", letter);
+        }
+        Tile::Blinker(phase) => {
+            ui.checkbox(phase, "Closed on the first turn");
+        }
+        Tile::ResetTrap(resets_keys) => {
+            ui.checkbox(resets_keys, "Also empties the inventory");
+        }
+        Tile::Arrow(direction) => {
+            egui::ComboBox::from_label("Pushes")
+                .selected_text(format!("{direction:?}"))
+                .show_ui(ui, |ui| {
+                    for option in ALL_DIRECTIONS {
+                        ui.selectable_value(direction, *option, format!("{option:?}"));
                     }
-                }
-            }
+                });
+        }
+        _ => {}
+    }
+    // Unchecking the last direction of a movement tile is ignored, as with the arrow keys
+    if tile != tile_data.tile && tile.is_valid() {
+        app.editing_model_mut().set_tile(pos, tile.clone());
+    }
 
-            ui.label("Selected Tile:");
-            draw_tile_and_key(
-                app.selected_type.as_ref().unwrap_or(&Tile::Empty),
-                &KeyItem::None,
-                ui,
-                app,
-                false,
-            );
+    display_branch_preview_controls(ui, app, pos);
 
-            ui.label("Selected Key:");
-            if app.selected_key.is_none() {
-                ui.label("None");
-            } else {
-                draw_tile_and_key(
-                    &Tile::Empty,
-                    app.selected_key.as_ref().unwrap(),
-                    ui,
-                    app,
-                    false,
-                );
+    ui.separator();
+    if tile == Tile::Empty {
+        ui.label("Empty tiles can't hold a key.");
+        return;
+    }
+    let mut key = tile_data.key.clone();
+    egui::ComboBox::from_label("Key")
+        .selected_text(key.label())
+        .show_ui(ui, |ui| {
+            for option in std::iter::once(&KeyItem::None).chain(ALL_KEYS) {
+                let same_kind = option.label() == key.label();
+                // Keys the player couldn't use on this tile are marked, placing them warns
+                let label = if option.is_compatible_with(&tile_data.tile) {
+                    option.label().to_string()
+                } else {
+                    format!("{} (doesn't fit)", option.label())
+                };
+                if ui.selectable_label(same_kind, label).clicked() && !same_kind {
+                    key = option.clone();
+                }
             }
         });
+    if let Some(letter) = key.letter_mut() {
+        letter_combo(ui, "Key letter", letter);
+    }
+    if key != tile_data.key && app.allow_key_placement(&key, &tile_data.tile) {
+        app.editing_model_mut().set_key(pos, key);
+    }
+}
 
-        ui.add_space(5.0);
-
-        ui.horizontal(|ui| {
-            // Tiles
-            ui.label("Tiles");
-            for tile in ALL_TILES {
-                let response = draw_tile_and_key(&tile.clone(), &KeyItem::None, ui, app, false);
-                if response.clicked() {
-                    app.selected_type = Some(tile.clone());
-                    app.selected_key = None; // Clear selected key when selecting a tile
-                }
-                if response.hovered() {
-                    ui.painter().rect_filled(
-                        response.rect,
-                        0.0,
-                        egui::Color32::from_black_alpha(100),
-                    );
-                }
-            }
+/// Brief note at the bottom of the window on why the last key placed didn't fit its tile
+fn display_key_warning(ctx: &egui::Context, app: &mut App) {
+    let Some((reason, shown)) = app.key_warning else {
+        return;
+    };
+    let elapsed = shown.elapsed();
+    if elapsed >= KEY_WARNING_DURATION {
+        app.key_warning = None;
+        return;
+    }
 
-            // Keys
-            ui.label("Keys");
-            for key in ALL_KEYS {
-                let response = draw_tile_and_key(&Tile::Empty, &key.clone(), ui, app, false);
-                if response.clicked() {
-                    app.selected_key = Some(key.clone());
-                    app.selected_type = None; // Clear selected tile when selecting a key
+    egui::Area::new(egui::Id::new("key_warning"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -20.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, reason);
+                if !app.allow_incompatible_keys {
+                    ui.label("Tick Allow Incompatible Keys to place it anyway.");
                 }
-                if response.hovered() {
-                    ui.painter().rect_filled(
-                        response.rect,
-                        0.0,
-                        egui::Color32::from_black_alpha(100),
-                    );
-                }
-            }
+            });
         });
-    });
+    ctx.request_repaint_after(KEY_WARNING_DURATION - elapsed);
 }
 
-fn display_editing_board(ui: &mut egui::Ui, app: &mut App) {
-    let mut edited_pos = None;
+/// Buttons tracing a move from the inspected tile, and where the traced move ends
+fn display_branch_preview_controls(ui: &mut egui::Ui, app: &mut App, pos: (usize, usize)) {
+    ui.separator();
+    ui.label("Preview a move from here:");
+    let mut movement = None;
+    egui::Grid::new("branch_preview_buttons").show(ui, |ui| {
+        for row in [
+            [
+                (DirectionKey::UpLeft, "↖"),
+                (DirectionKey::Up, "↑"),
+                (DirectionKey::UpRight, "↗"),
+            ],
+            [
+                (DirectionKey::Left, "←"),
+                (DirectionKey::None, "Use"),
+                (DirectionKey::Right, "→"),
+            ],
+            [
+                (DirectionKey::DownLeft, "↙"),
+                (DirectionKey::Down, "↓"),
+                (DirectionKey::DownRight, "↘"),
+            ],
+        ] {
+            for (direction, label) in row {
+                if ui.button(label).clicked() {
+                    movement = Some(PlayerMovementData {
+                        direction,
+                        move_speed: if app.preview_run {
+                            app.editing_model().get_run_speed()
+                        } else {
+                            1
+                        },
+                        use_tile: direction.is_none(),
+                    });
+                }
+            }
+            ui.end_row();
+        }
+    });
+    ui.checkbox(&mut app.preview_run, "Run");
 
-    // Display the board
-    egui::Grid::new("editing_board_grid")
-        .spacing(egui::vec2(1.0, 1.0))
-        .min_col_width(0.0)
-        .show(ui, |ui| {
-            for (row_idx, row) in app.editing_model.get_board().iter().enumerate() {
-                for (col_idx, tile) in row.iter().enumerate() {
-                    // Draw each tile and handle clicks
-                    let response =
-                        draw_tile_and_key(&tile.tile.clone(), &tile.key.clone(), ui, app, false);
+    if let Some(movement) = movement {
+        app.branch_preview = Some(BranchPreview {
+            from: pos,
+            movement,
+            dest: app.editing_model().trace_move(pos, movement),
+            board_revision: app.editing_model().get_revision(),
+        });
+    }
+    if let Some(preview) = app.branch_preview {
+        ui.label(match preview.dest {
+            Some(dest) => format!(
+                "From {},{} the move ends at {},{}",
+                preview.from.0, preview.from.1, dest.0, dest.1
+            ),
+            None => format!(
+                "From {},{} the move leaves the board",
+                preview.from.0, preview.from.1
+            ),
+        });
+        if ui.button("Clear Preview").clicked() {
+            app.branch_preview = None;
+        }
+    }
+}
+
+/// Dropdown picking a letter from A to Z
+fn letter_combo(ui: &mut egui::Ui, label: &str, letter: &mut char) {
+    egui::ComboBox::from_label(label)
+        .selected_text(letter.to_string())
+        .show_ui(ui, |ui| {
+            for option in 'A'..='Z' {
+                ui.selectable_value(letter, option, option.to_string());
+            }
+        });
+}
+
+fn display_editing_menu(ui: &mut egui::Ui, app: &mut App) {
+    // Display menus and buttons for editing the board
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            // Add UI buttons to change modes and save/load the board
+            if ui.button("Switch to Playing Mode").clicked() {
+                if app.editing_model_mut().board_is_playable() {
+                    app.start_playing(app.sandbox);
+                } else {
+                    let issues = app.editing_model().validation_report();
+                    app.popup_data = Some(PopupData {
+                        message: format!(
+                            "The board can't be played yet:\n{}",
+                            issues
+                                .iter()
+                                .map(|issue| format!("- {issue}"))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        ),
+                        popup_type: PopupType::Ok,
+                    });
+                }
+            }
+            ui.checkbox(&mut app.sandbox, "Sandbox")
+                .on_hover_text("Start play holding every key, to try out key behaviors.");
+            if ui.button("New Board").clicked() {
+                if app.editing_model().is_dirty() {
+                    app.popup_data = Some(PopupData {
+                        message:
+                            "The board has unsaved changes. Save them before starting a new board?"
+                                .to_string(),
+                        popup_type: PopupType::YesNo {
+                            on_yes: |app| {
+                                save_board_dialog(app, |app| app.mode = AppMode::Startup);
+                            },
+                            on_no: Some(|app| app.mode = AppMode::Startup),
+                        },
+                    });
+                } else {
+                    app.mode = AppMode::Startup;
+                }
+            }
+            if ui.button("Save Board").clicked() {
+                save_board_dialog(app, |_| {});
+            }
+            if ui.button("Load Board").clicked() {
+                if app.editing_model().is_dirty() {
+                    app.popup_data = Some(PopupData {
+                        message:
+                            "The board has unsaved changes. Save them before loading another board?"
+                                .to_string(),
+                        popup_type: PopupType::YesNo {
+                            on_yes: |app| save_board_dialog(app, load_board_dialog),
+                            on_no: Some(load_board_dialog),
+                        },
+                    });
+                } else {
+                    load_board_dialog(app);
+                }
+            }
+            if ui.button("Load Replay").clicked() {
+                load_replay(app);
+            }
+            if ui.button("Resume Progress").clicked() {
+                resume_progress(app);
+            }
+            if ui.button("Export ASCII").clicked()
+                && let Ok(file_name) = open_ascii_file_dialog(true)
+                && let Err(err) =
+                    write_atomically(Path::new(&file_name), &app.editing_model().to_ascii())
+            {
+                app.popup_data = Some(PopupData {
+                    message: format!("Error writing ASCII board: {err}"),
+                    popup_type: PopupType::Ok,
+                });
+            }
+            if ui.button("Import ASCII").clicked()
+                && let Ok(file_name) = open_ascii_file_dialog(false)
+            {
+                match std::fs::read_to_string(&file_name)
+                    .map_err(|err| format!("Error reading ASCII board: {err}"))
+                    .and_then(|text| EditingModel::from_ascii(&text))
+                {
+                    Ok(model) => app.open_tab(model),
+                    Err(err) => {
+                        app.popup_data = Some(PopupData {
+                            message: err,
+                            popup_type: PopupType::Ok,
+                        });
+                    }
+                }
+            }
+            if ui.button("Import Legacy Board").clicked()
+                && let Ok(file_name) =
+                    open_file_dialog_with_filter(false, "Foam Game Legacy Board", &["fgb"])
+            {
+                match EditingModel::import_fgb(&file_name) {
+                    Ok(model) => app.open_tab(model),
+                    Err(err) => {
+                        app.popup_data = Some(PopupData {
+                            message: format!("Error importing legacy board: {err}"),
+                            popup_type: PopupType::Ok,
+                        });
+                    }
+                }
+            }
+
+            if ui.button("Clear Board").clicked() {
+                app.popup_data = Some(PopupData {
+                    message: "Clear every tile and key from the board?".to_string(),
+                    popup_type: PopupType::YesNo {
+                        on_yes: |app| app.editing_model_mut().clear(),
+                        on_no: None,
+                    },
+                });
+            }
+            ui.add(egui::DragValue::new(&mut app.random_seed).prefix("Seed: "));
+            if ui.button("Randomize Board").clicked() {
+                app.popup_data = Some(PopupData {
+                    message: format!(
+                        "Replace the board with a random level from seed {}?",
+                        app.random_seed
+                    ),
+                    popup_type: PopupType::YesNo {
+                        on_yes: |app| {
+                            *app.editing_model_mut() = EditingModel::generate_random(
+                                app.editing_model().get_board_size(),
+                                app.random_seed,
+                            );
+                        },
+                        on_no: None,
+                    },
+                });
+            }
+            if ui.button("Clear Keys").clicked() {
+                app.popup_data = Some(PopupData {
+                    message: "Clear every key from the board?".to_string(),
+                    popup_type: PopupType::YesNo {
+                        on_yes: |app| app.editing_model_mut().clear_keys(),
+                        on_no: None,
+                    },
+                });
+            }
+
+            if ui.button("Flip Horizontal").clicked() {
+                app.editing_model_mut().flip_horizontal();
+            }
+            if ui.button("Flip Vertical").clicked() {
+                app.editing_model_mut().flip_vertical();
+            }
+            // Scroll the start or end into view and outline it for a moment
+            for (label, pos) in [
+                ("Go to Start", app.editing_model().get_start_pos()),
+                ("Go to End", app.editing_model().get_end_pos()),
+            ] {
+                if ui
+                    .add_enabled(pos.is_some(), egui::Button::new(label))
+                    .clicked()
+                    && let Some(pos) = pos
+                {
+                    app.scroll_target = Some(pos);
+                    app.jump_highlight = Some((pos, ui.input(|i| i.time)));
+                }
+            }
+            egui::ComboBox::from_label("Mirror Edits")
+                .selected_text(format!("{:?}", app.symmetry))
+                .show_ui(ui, |ui| {
+                    for symmetry in [
+                        Symmetry::None,
+                        Symmetry::Horizontal,
+                        Symmetry::Vertical,
+                        Symmetry::Both,
+                    ] {
+                        ui.selectable_value(&mut app.symmetry, symmetry, format!("{symmetry:?}"));
+                    }
+                });
+
+            ui.checkbox(&mut app.show_unreachable, "Show Unreachable");
+            ui.checkbox(&mut app.show_ice_momentum, "Show Ice Momentum");
+            ui.checkbox(&mut app.show_critical, "Show Critical Tiles")
+                .on_hover_text("Outline the tiles every shortest win lands on");
+            ui.checkbox(&mut app.allow_incompatible_keys, "Allow Incompatible Keys")
+                .on_hover_text(
+                    "Place keys where the player can't use them, instead of only warning",
+                );
+
+            let mut has_move_limit = app.editing_model().get_move_limit().is_some();
+            if ui.checkbox(&mut has_move_limit, "Move Limit").changed() {
+                app.editing_model_mut()
+                    .set_move_limit(has_move_limit.then_some(10));
+            }
+            if let Some(mut move_limit) = app.editing_model().get_move_limit()
+                && ui
+                    .add(egui::DragValue::new(&mut move_limit).range(1..=999))
+                    .changed()
+            {
+                app.editing_model_mut().set_move_limit(Some(move_limit));
+            }
+
+            ui.label("Level Name:");
+            let mut name = app.editing_model().get_name().to_string();
+            if ui
+                .add(egui::TextEdit::singleline(&mut name).desired_width(120.0))
+                .changed()
+            {
+                app.editing_model_mut().set_name(name);
+            }
+
+            ui.label("Next Level:");
+            let mut next_level = app
+                .editing_model()
+                .get_next_level()
+                .unwrap_or_default()
+                .to_string();
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut next_level)
+                        .hint_text("none")
+                        .desired_width(120.0),
+                )
+                .on_hover_text("Board file played after winning, relative to this board's file.")
+                .changed()
+            {
+                app.editing_model_mut()
+                    .set_next_level((!next_level.is_empty()).then_some(next_level));
+            }
+
+            display_background_menu(ui, app);
+
+            let mut gravity = app.editing_model().get_gravity();
+            egui::ComboBox::from_label("Gravity")
+                .selected_text(
+                    gravity.map_or("None".to_string(), |direction| format!("{direction:?}")),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut gravity, None, "None");
+                    for &direction in ALL_DIRECTIONS {
+                        ui.selectable_value(
+                            &mut gravity,
+                            Some(direction),
+                            format!("{direction:?}"),
+                        );
+                    }
+                });
+            if gravity != app.editing_model().get_gravity() {
+                app.editing_model_mut().set_gravity(gravity);
+            }
+
+            let mut run_speed = app.editing_model().get_run_speed();
+            if ui
+                .add(egui::Slider::new(&mut run_speed, RUN_SPEED_RANGE).text("Run Speed"))
+                .changed()
+            {
+                app.editing_model_mut().set_run_speed(run_speed);
+            }
+
+            ui.label("Selected Tile:");
+            let selected_response = draw_tile_and_key(
+                app.selected_type.as_ref().unwrap_or(&Tile::Empty),
+                &KeyItem::None,
+                ui,
+                app,
+                false,
+                TILE_PX,
+                None,
+                false,
+                None,
+            );
+            // Scroll wheel over the selected tile cycles the parameters of the brush
+            if selected_response.hovered()
+                && let Some(selected_type) = &mut app.selected_type
+            {
+                let scroll = ui.input(|i| (!i.modifiers.ctrl).then_some(i.raw_scroll_delta.y));
+                if let Some(scroll) = scroll
+                    && scroll != 0.0
+                {
+                    selected_type.cycle_variant(scroll > 0.0);
+                }
+            }
+
+            ui.label("Selected Key:");
+            if let Some(selected_key) = &app.selected_key {
+                draw_tile_and_key(
+                    &Tile::Empty,
+                    selected_key,
+                    ui,
+                    app,
+                    false,
+                    TILE_PX,
+                    None,
+                    false,
+                    None,
+                );
+            } else {
+                ui.label("None");
+            }
+        });
+
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut app.palette_filter);
+        });
+        let filter = app.palette_filter.to_lowercase();
+        let matches_filter = |explanation: &str| explanation.to_lowercase().contains(&filter);
+
+        // Shift-clicking a tile or key adds it to the brush, to place both together
+        let shift = ui.input(|i| i.modifiers.shift);
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(app.brush_is_eraser, "Eraser")
+                    .on_hover_text("Clears both the tile and the key.")
+                    .clicked()
+                {
+                    app.select_eraser_brush();
+                }
+
+                // Tiles
+                ui.label("Tiles");
+                for tile in ALL_TILES
+                    .iter()
+                    .filter(|tile| matches_filter(tile.explanation()))
+                {
+                    let response = draw_tile_and_key(
+                        &tile.clone(),
+                        &KeyItem::None,
+                        ui,
+                        app,
+                        false,
+                        TILE_PX,
+                        None,
+                        false,
+                        None,
+                    );
                     if response.clicked() {
-                        edited_pos = Some((row_idx, col_idx));
+                        app.select_tile_brush(tile.clone(), shift);
                     }
-                    // Highlight the selected tile
                     if response.hovered() {
                         ui.painter().rect_filled(
                             response.rect,
                             0.0,
                             egui::Color32::from_black_alpha(100),
                         );
-                        app.selected_tile_pos = Some((row_idx, col_idx));
                     }
                 }
-                ui.end_row();
+
+                // Keys
+                ui.label("Keys");
+                for key in ALL_KEYS
+                    .iter()
+                    .filter(|key| matches_filter(key.explanation()))
+                {
+                    let response = draw_tile_and_key(
+                        &Tile::Empty,
+                        &key.clone(),
+                        ui,
+                        app,
+                        false,
+                        TILE_PX,
+                        None,
+                        false,
+                        None,
+                    );
+                    if response.clicked() {
+                        app.select_key_brush(key.clone(), shift);
+                    }
+                    if response.hovered() {
+                        ui.painter().rect_filled(
+                            response.rect,
+                            0.0,
+                            egui::Color32::from_black_alpha(100),
+                        );
+                    }
+                }
+            });
+        });
+
+        display_tile_counts(ui, app);
+        display_move_simulation(ui, app);
+    });
+}
+
+/// A tab for each open board, marked while it has unsaved changes
+fn display_tab_bar(ui: &mut egui::Ui, app: &mut App) {
+    ui.horizontal(|ui| {
+        let mut clicked = None;
+        for (index, tab) in app.tabs.iter().enumerate() {
+            let hover = tab.path.as_ref().map_or_else(
+                || "Not saved yet".to_string(),
+                |path| path.display().to_string(),
+            );
+            if ui
+                .selectable_label(index == app.active_tab, tab.title())
+                .on_hover_text(hover)
+                .clicked()
+            {
+                clicked = Some(index);
+            }
+        }
+        if let Some(index) = clicked {
+            app.switch_tab(index);
+        }
+        ui.separator();
+        if ui
+            .button("New")
+            .on_hover_text("Open a blank board the same size in a new tab")
+            .clicked()
+        {
+            app.new_tab();
+        }
+        if ui.button("Close").on_hover_text("Close this tab").clicked() {
+            app.request_close_tab();
+        }
+    });
+    ui.separator();
+}
+
+/// Text box playing a typed sequence of moves from the start space, for checking a solution
+/// without playing it by hand
+fn display_move_simulation(ui: &mut egui::Ui, app: &mut App) {
+    egui::CollapsingHeader::new("Simulate Moves").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut app.simulation_text)
+                    .hint_text("U U R* D")
+                    .desired_width(160.0),
+            );
+            response.on_hover_text(
+                "Directions U, R, D, L, UR, DR, DL and UL separated by spaces. A * after a \
+                 direction runs it, and use uses the tile the player is on",
+            );
+            if ui.button("Simulate").clicked() {
+                app.simulation = Some(
+                    parse_moves(&app.simulation_text, app.editing_model().get_run_speed())
+                        .and_then(|moves| {
+                            app.editing_model()
+                                .simulate_moves(&moves)
+                                .map_err(|err| err.to_string())
+                        })
+                        .map(|(path, outcome)| MoveSimulation {
+                            start: app.editing_model().get_start_pos(),
+                            path,
+                            outcome,
+                        }),
+                );
+            }
+        });
+
+        match &app.simulation {
+            Some(Ok(simulation)) => {
+                let moves = simulation.path.len();
+                let end = simulation.path.last().copied().flatten();
+                ui.label(match (&simulation.outcome, end) {
+                    (MovementPopupData::Won, _) => format!("Won after {moves} moves"),
+                    (MovementPopupData::Lost, _) => format!("Lost on move {moves}"),
+                    (MovementPopupData::Stranded, _) => format!("Stranded on move {moves}"),
+                    (MovementPopupData::Wall, _) => format!("Hit a wall on move {moves}"),
+                    (MovementPopupData::None, Some(end)) => {
+                        format!("Ended at {},{} without winning", end.0, end.1)
+                    }
+                    (MovementPopupData::None, None) => "Ended off the board".to_string(),
+                });
+                let path = std::iter::once(simulation.start)
+                    .chain(simulation.path.iter().copied())
+                    .map(|pos| match pos {
+                        Some((row, col)) => format!("{row},{col}"),
+                        None => "off".to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                ui.label(format!("Path: {}", path.join(" → ")));
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+    });
+}
+
+/// Table of how many of each tile the board has, warning about variants over their limit
+fn display_tile_counts(ui: &mut egui::Ui, app: &App) {
+    let counts = app.editing_model().tile_counts();
+    egui::CollapsingHeader::new("Tile Counts").show(ui, |ui| {
+        egui::Grid::new("tile_counts_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for tile in ALL_TILES.iter() {
+                    let Some(&count) = counts.get(tile.label()) else {
+                        continue;
+                    };
+                    ui.label(tile.label());
+                    if tile.max_count().is_some_and(|max| count > max) {
+                        ui.colored_label(egui::Color32::RED, format!("{count} (too many)"));
+                    } else {
+                        ui.label(count.to_string());
+                    }
+                    ui.end_row();
+                }
+            });
+        // Identifies the level's content, e.g. to spot duplicates when sharing
+        ui.label(format!(
+            "Content hash: {:016x}",
+            app.editing_model().content_hash()
+        ));
+    });
+}
+
+/// Background style picker, with a color button for each of the style's colors
+fn display_background_menu(ui: &mut egui::Ui, app: &mut App) {
+    // Colors a newly picked style starts from, close to the empty tile texture
+    const DARK: [u8; 3] = [0x1b, 0x1b, 0x1b];
+    const LIGHT: [u8; 3] = [0x30, 0x30, 0x30];
+
+    let mut background = app.editing_model().get_background();
+    egui::ComboBox::from_label("Background")
+        .selected_text(match background {
+            BackgroundStyle::Texture => "Texture",
+            BackgroundStyle::Solid(_) => "Solid",
+            BackgroundStyle::Checkerboard(..) => "Checkerboard",
+        })
+        .show_ui(ui, |ui| {
+            // Picking the style already in use keeps its colors
+            let is_texture = background == BackgroundStyle::Texture;
+            if ui.selectable_label(is_texture, "Texture").clicked() && !is_texture {
+                background = BackgroundStyle::Texture;
+            }
+            let is_solid = matches!(background, BackgroundStyle::Solid(_));
+            if ui.selectable_label(is_solid, "Solid").clicked() && !is_solid {
+                background = BackgroundStyle::Solid(DARK);
+            }
+            let is_checkerboard = matches!(background, BackgroundStyle::Checkerboard(..));
+            if ui
+                .selectable_label(is_checkerboard, "Checkerboard")
+                .clicked()
+                && !is_checkerboard
+            {
+                background = BackgroundStyle::Checkerboard(DARK, LIGHT);
+            }
+        });
+    match &mut background {
+        BackgroundStyle::Texture => {}
+        BackgroundStyle::Solid(color) => {
+            ui.color_edit_button_srgb(color);
+        }
+        BackgroundStyle::Checkerboard(even, odd) => {
+            ui.color_edit_button_srgb(even);
+            ui.color_edit_button_srgb(odd);
+        }
+    }
+
+    if background != app.editing_model().get_background() {
+        app.editing_model_mut().set_background(background);
+    }
+}
+
+/// Overview of the whole board, one pixel block per tile. Clicking or dragging on it scrolls the
+/// editing board to that spot.
+fn display_minimap(ui: &mut egui::Ui, app: &mut App) {
+    const MINIMAP_TILE_PX: f32 = 4.0;
+
+    let (rows, cols) = app.editing_model().get_board_size();
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(cols as f32, rows as f32) * MINIMAP_TILE_PX,
+        egui::Sense::click_and_drag(),
+    );
+    let painter = ui.painter_at(rect);
+    let tile_rect = |row: f32, col: f32| {
+        egui::Rect::from_min_size(
+            rect.min + egui::vec2(col, row) * MINIMAP_TILE_PX,
+            egui::Vec2::splat(MINIMAP_TILE_PX),
+        )
+    };
+
+    for (row_idx, row) in app.editing_model().get_board().iter().enumerate() {
+        for (col_idx, tile) in row.iter().enumerate() {
+            painter.rect_filled(
+                tile_rect(row_idx as f32, col_idx as f32),
+                0.0,
+                tile.tile.minimap_color(),
+            );
+        }
+    }
+
+    // Outline the part of the board currently scrolled into view
+    if let Some(viewport) = app.editor_viewport {
+        let viewport = egui::Rect::from_min_max(
+            rect.min + viewport.min.to_vec2() * MINIMAP_TILE_PX,
+            rect.min + viewport.max.to_vec2() * MINIMAP_TILE_PX,
+        )
+        .intersect(rect);
+        painter.rect_stroke(
+            viewport,
+            0.0,
+            egui::Stroke::new(1.0, egui::Color32::WHITE),
+            egui::StrokeKind::Inside,
+        );
+    }
+
+    // Highlight the cursor and the selected tile
+    for pos in [app.cursor_pos, app.selected_tile_pos]
+        .into_iter()
+        .flatten()
+    {
+        painter.rect_stroke(
+            tile_rect(pos.0 as f32, pos.1 as f32).expand(1.0),
+            0.0,
+            egui::Stroke::new(1.0, egui::Color32::YELLOW),
+            egui::StrokeKind::Outside,
+        );
+    }
+
+    if (response.clicked() || response.dragged())
+        && let Some(pointer) = response.interact_pointer_pos()
+    {
+        let offset = (pointer - rect.min) / MINIMAP_TILE_PX;
+        let row = (offset.y.max(0.0) as usize).min(rows.saturating_sub(1));
+        let col = (offset.x.max(0.0) as usize).min(cols.saturating_sub(1));
+        app.scroll_target = Some((row, col));
+    }
+}
+
+/// What the cached editing board was drawn from: the board's revision, the textures and the
+/// settings that change how tiles look
+fn editing_board_cache_key(app: &App, board_revision: u64, pixels_per_point: f32) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board_revision.hash(&mut hasher);
+    pixels_per_point.to_bits().hash(&mut hasher); // Text is tessellated for the display's scale
+    app.settings.show_grid.hash(&mut hasher);
+    app.settings.grid_color.hash(&mut hasher);
+    app.settings.show_coordinates.hash(&mut hasher);
+    app.settings.color_palette.hash(&mut hasher);
+    for file_name in ALL_TILES
+        .iter()
+        .map(Tile::file_name)
+        .chain(ALL_KEYS.iter().map(KeyItem::file_name))
+    {
+        app.texture_cache
+            .get(file_name)
+            .map(egui::TextureHandle::id)
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Every tile of the editing board tessellated into meshes, relative to its top left corner
+fn editing_board_meshes(ctx: &egui::Context, app: &App, pitch: f32) -> Vec<egui::epaint::Mesh> {
+    let background = app.editing_model().get_background();
+    let palette = app.settings.color_palette.overlay_palette();
+    let mut shapes = Vec::new();
+    for (row_idx, row) in app.editing_model().get_board().iter().enumerate() {
+        for (col_idx, tile) in row.iter().enumerate() {
+            let rect = egui::Rect::from_min_size(
+                egui::pos2(col_idx as f32, row_idx as f32) * pitch,
+                egui::Vec2::splat(TILE_PX),
+            );
+            shapes.extend(tile_shapes(
+                ctx,
+                &tile.tile,
+                &tile.key,
+                app,
+                &palette,
+                rect,
+                background.color_at((row_idx, col_idx)),
+            ));
+            shapes.extend(grid_shape(&tile.tile, &tile.key, app, rect));
+            // Row then column, the order the board and the ASCII format use
+            if app.settings.show_coordinates {
+                let galley = ctx.fonts(|fonts| {
+                    fonts.layout_no_wrap(
+                        format!("{row_idx},{col_idx}"),
+                        egui::FontId::monospace(8.0),
+                        egui::Color32::WHITE,
+                    )
+                });
+                shapes.push(egui::Shape::galley(
+                    rect.left_top() + egui::vec2(1.0, 0.0),
+                    galley,
+                    egui::Color32::WHITE,
+                ));
+            }
+        }
+    }
+    let shapes = shapes
+        .into_iter()
+        .map(|shape| egui::epaint::ClippedShape {
+            clip_rect: egui::Rect::EVERYTHING,
+            shape,
+        })
+        .collect();
+    ctx.tessellate(shapes, ctx.pixels_per_point())
+        .into_iter()
+        .filter_map(|clipped| match clipped.primitive {
+            egui::epaint::Primitive::Mesh(mesh) => Some(mesh),
+            egui::epaint::Primitive::Callback(_) => None,
+        })
+        .collect()
+}
+
+fn display_editing_board(ui: &mut egui::Ui, app: &mut App) {
+    let mut edited_pos = None;
+    let reachable = (app.show_unreachable && app.editing_model().get_start_pos().is_some())
+        .then(|| app.cached_reachable_tiles());
+    let ice_momentum = app.show_ice_momentum.then(|| app.cached_ice_momentum());
+    let critical = app.show_critical.then(|| app.cached_critical_tiles());
+    // Trace the previewed move again once the board changes under it
+    let board_revision = app.editing_model().get_revision();
+    if let Some(preview) = &mut app.branch_preview
+        && preview.board_revision != board_revision
+    {
+        preview.dest = app.tabs[app.active_tab]
+            .model
+            .trace_move(preview.from, preview.movement);
+        preview.board_revision = board_revision;
+    }
+    let mut preview_rects = (None, None); // (from, destination)
+    let current_time = ui.input(|i| i.time);
+    if app
+        .jump_highlight
+        .is_some_and(|(_, time)| current_time - time > JUMP_HIGHLIGHT_SECONDS)
+    {
+        app.jump_highlight = None;
+    }
+    let (shift, pointer_pressed, pointer_down) = ui.input(|i| {
+        (
+            i.modifiers.shift,
+            i.pointer.primary_pressed(),
+            i.pointer.primary_down(),
+        )
+    });
+    // Keep the selection on the board, e.g. after loading a smaller one
+    let board_size = app.editing_model().get_board_size();
+    if app.selection.is_some_and(|(start, end)| {
+        start.0.max(end.0) >= board_size.0 || start.1.max(end.1) >= board_size.1
+    }) {
+        app.selection = None;
+    }
+
+    // Display the board, scrollable for boards bigger than the window. Drags are left to the
+    // selection
+    let pitch = TILE_PX + 1.0; // 1px between tiles
+    let board_px =
+        egui::vec2(board_size.1 as f32, board_size.0 as f32) * pitch - egui::Vec2::splat(1.0);
+    let scroll_output = egui::ScrollArea::both()
+        .drag_to_scroll(false)
+        .show(ui, |ui| {
+            // The whole board is one widget, the tile under the pointer is worked out from
+            // its position
+            let (board_rect, response) =
+                ui.allocate_exact_size(board_px.max(egui::Vec2::ZERO), egui::Sense::click());
+            let tile_rect = |(row, col): (usize, usize)| {
+                egui::Rect::from_min_size(
+                    board_rect.min + egui::vec2(col as f32, row as f32) * pitch,
+                    egui::Vec2::splat(TILE_PX),
+                )
+            };
+            let hovered_pos = response.hover_pos().map(|pos| {
+                let offset = (pos - board_rect.min) / pitch;
+                (
+                    (offset.y.max(0.0) as usize).min(board_size.0.saturating_sub(1)),
+                    (offset.x.max(0.0) as usize).min(board_size.1.saturating_sub(1)),
+                )
+            });
+
+            // Tiles are drawn from the cache, rebuilt only when the board or its look changes
+            let cache_key =
+                editing_board_cache_key(app, board_revision, ui.ctx().pixels_per_point());
+            if app
+                .editing_board_cache
+                .as_ref()
+                .is_none_or(|cache| cache.key != cache_key)
+            {
+                app.editing_board_cache = Some(EditingBoardCache {
+                    key: cache_key,
+                    meshes: editing_board_meshes(ui.ctx(), app, pitch),
+                });
+            }
+            if let Some(cache) = &app.editing_board_cache {
+                let offset = board_rect.min.to_vec2();
+                ui.painter()
+                    .extend(cache.meshes.iter().cloned().map(|mut mesh| {
+                        mesh.translate(offset);
+                        egui::Shape::mesh(mesh)
+                    }));
+            }
+
+            if let Some(preview) = &app.branch_preview {
+                preview_rects = (Some(tile_rect(preview.from)), preview.dest.map(tile_rect));
+            }
+            // Bring the tile clicked on the minimap into view
+            if let Some(target) = app.scroll_target {
+                ui.scroll_to_rect(tile_rect(target), Some(egui::Align::Center));
+            }
+            if response.clicked() && !shift {
+                edited_pos = hovered_pos;
+            }
+            // Shift-drag selects a rectangle of tiles
+            if shift && let Some(pos) = hovered_pos {
+                if pointer_pressed {
+                    app.selection = Some((pos, pos));
+                } else if pointer_down && let Some((_, end)) = &mut app.selection {
+                    *end = pos;
+                }
+            }
+            if let Some((start, end)) = app.selection {
+                let top_left = (start.0.min(end.0), start.1.min(end.1));
+                let bottom_right = (start.0.max(end.0), start.1.max(end.1));
+                ui.painter().rect_filled(
+                    tile_rect(top_left).union(tile_rect(bottom_right)),
+                    0.0,
+                    egui::Color32::from_rgba_unmultiplied(0, 128, 255, 80),
+                );
+            }
+            // Dotted arrows for the ways a slide can cross the ice
+            for (&pos, directions) in ice_momentum.iter().flatten() {
+                draw_momentum_arrows(ui, tile_rect(pos), directions);
+            }
+            // Shade tiles the player can never stand on
+            if let Some(reachable) = &reachable {
+                for (row_idx, row) in app.editing_model().get_board().iter().enumerate() {
+                    for (col_idx, tile) in row.iter().enumerate() {
+                        if tile.tile != Tile::Empty && !reachable.contains(&(row_idx, col_idx)) {
+                            ui.painter().rect_filled(
+                                tile_rect((row_idx, col_idx)),
+                                0.0,
+                                app.settings.color_palette.overlay_palette().unreachable,
+                            );
+                        }
+                    }
+                }
+            }
+            // Outline the tiles no shortest win gets around
+            for &pos in critical.iter().flatten() {
+                ui.painter().rect_stroke(
+                    tile_rect(pos),
+                    0.0,
+                    egui::Stroke::new(3.0, app.settings.color_palette.overlay_palette().critical),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            // Fading outline on the tile jumped to
+            if let Some((pos, time)) = app.jump_highlight {
+                let fade = 1.0 - (current_time - time) / JUMP_HIGHLIGHT_SECONDS;
+                ui.painter().rect_stroke(
+                    tile_rect(pos),
+                    0.0,
+                    egui::Stroke::new(
+                        3.0,
+                        egui::Color32::from_rgb(0, 200, 255).gamma_multiply(fade as f32),
+                    ),
+                    egui::StrokeKind::Inside,
+                );
+                ui.ctx().request_repaint();
+            }
+            // Outline the keyboard cursor
+            if app.editor_focus == EditorFocus::Navigate
+                && let Some(cursor_pos) = app.cursor_pos
+            {
+                ui.painter().rect_stroke(
+                    tile_rect(cursor_pos),
+                    0.0,
+                    egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            // Highlight the selected tile
+            if let Some(pos) = hovered_pos {
+                ui.painter()
+                    .rect_filled(tile_rect(pos), 0.0, egui::Color32::from_black_alpha(100));
+                app.selected_tile_pos = Some(pos);
+                let tile = &app.editing_model().get_board()[pos.0][pos.1];
+                response.on_hover_text_at_pointer(tile_hover_text(&tile.tile, &tile.key, None));
+            }
+
+            // Arrow from the previewed move's tile to where it ends up
+            let palette = app.settings.color_palette.overlay_palette();
+            let preview_color = palette.preview;
+            if let (Some(from_rect), Some(dest_rect)) = preview_rects {
+                ui.painter().arrow(
+                    from_rect.center(),
+                    dest_rect.center() - from_rect.center(),
+                    egui::Stroke::new(2.0, preview_color),
+                );
+                ui.painter().rect_stroke(
+                    dest_rect,
+                    0.0,
+                    egui::Stroke::new(3.0, preview_color),
+                    egui::StrokeKind::Inside,
+                );
+            } else if let (Some(from_rect), None) = preview_rects {
+                ui.painter().rect_stroke(
+                    from_rect,
+                    0.0,
+                    egui::Stroke::new(3.0, palette.hazard), // The move leaves the board
+                    egui::StrokeKind::Inside,
+                );
             }
         });
+    app.scroll_target = None;
+
+    // Visible part of the board in tiles, for the minimap
+    app.editor_viewport = Some(egui::Rect::from_min_size(
+        (scroll_output.state.offset / pitch).to_pos2(),
+        scroll_output.inner_rect.size() / pitch,
+    ));
 
     if let Some(edited_pos) = edited_pos {
-        if let Some(selected_type) = &app.selected_type {
-            // If a tile is selected, set it at the edited position
-            app.editing_model
-                .set_tile(edited_pos, selected_type.clone());
-        } else if let Some(selected_key) = &app.selected_key {
-            // If a key is selected, set it at the edited position
-            app.editing_model.set_key(edited_pos, selected_key.clone());
+        apply_brush(app, edited_pos);
+    }
+}
+
+/// Small dotted arrows across a tile, one per direction
+fn draw_momentum_arrows(ui: &egui::Ui, rect: egui::Rect, directions: &CardinalDirectionsAllowed) {
+    let stroke = egui::Stroke::new(1.5, egui::Color32::from_rgb(0, 90, 200));
+    for (allowed, direction) in [
+        (directions.up, egui::vec2(0.0, -1.0)),
+        (directions.right, egui::vec2(1.0, 0.0)),
+        (directions.down, egui::vec2(0.0, 1.0)),
+        (directions.left, egui::vec2(-1.0, 0.0)),
+    ] {
+        if !allowed {
+            continue;
+        }
+        let tail = rect.center() - direction * 12.0;
+        let tip = rect.center() + direction * 12.0;
+        ui.painter()
+            .extend(egui::Shape::dashed_line(&[tail, tip], stroke, 3.0, 2.0));
+        ui.painter()
+            .arrow(tip - direction * 5.0, direction * 5.0, stroke);
+    }
+}
+
+/// Place the selected tile or key at a position, and its mirror images in mirror edit mode
+fn apply_brush(app: &mut App, pos: (usize, usize)) {
+    if app.brush_is_eraser {
+        app.tabs[app.active_tab]
+            .model
+            .erase_tile_mirrored(pos, app.symmetry);
+    } else if let Some(selected_type) = app.selected_type.clone()
+        && selected_type != Tile::Empty
+        && let Some(selected_key) = app.selected_key.clone()
+        && selected_key != KeyItem::None
+    {
+        // A combined brush sets both the tile and its key
+        if app.allow_key_placement(&selected_key, &selected_type) {
+            app.tabs[app.active_tab].model.set_tile_data_mirrored(
+                pos,
+                TileData {
+                    tile: selected_type,
+                    key: selected_key,
+                },
+                app.symmetry,
+            );
+        }
+    } else if let Some(selected_type) = &app.selected_type {
+        // If a tile is selected, set it at the edited position
+        app.tabs[app.active_tab]
+            .model
+            .set_tile_mirrored(pos, selected_type.clone(), app.symmetry);
+    } else if let Some(selected_key) = app.selected_key.clone()
+        && let Some(tile) = app
+            .editing_model()
+            .get_board()
+            .get(pos.0)
+            .and_then(|row| row.get(pos.1))
+            .map(|tile_data| tile_data.tile.clone())
+    {
+        // If a key is selected, set it at the edited position
+        if app.allow_key_placement(&selected_key, &tile) {
+            app.tabs[app.active_tab]
+                .model
+                .set_key_mirrored(pos, selected_key, app.symmetry);
         }
     }
 }
@@ -821,20 +3556,89 @@ fn display_editing_board(ui: &mut egui::Ui, app: &mut App) {
     Play mode
 */
 
-const ANIMATION_SPEED: f64 = 0.1; // seconds per tile movement
-
 fn play_screen(ui: &mut egui::Ui, app: &mut App) {
     ui.label("Playing Mode");
+    if ui.input(|i| i.key_pressed(egui::Key::F3)) {
+        app.show_debug = !app.show_debug;
+    }
+    if app.show_debug {
+        display_debug_overlay(ui, app);
+    }
+    if app.step_mode {
+        display_step_overlay(ui, app);
+    }
     display_playing_board(ui, app);
+    if app.settings.touch_controls.shown(app.touch_detected) {
+        display_touch_controls(ui.ctx(), app);
+    }
+
+    // The ghost sets off with the live run's first move, then keeps to the replay's clock
+    if let Some(ghost) = &mut app.ghost {
+        let current_time = ui.input(|i| i.time);
+        if !app.playing_model.get_record().is_empty() {
+            ghost.start(current_time);
+        }
+        ghost.advance(current_time);
+    }
 
     if app.playing_model.animation_state.is_none() {
-        if let Some(keypress) = app.get_movement_data() {
-            app.playing_model.start_movement_animation(keypress);
-            app.last_animation_update = ui.input(|i| i.time);
+        let current_time = ui.input(|i| i.time);
+        if !app.replay_moves.is_empty() {
+            // Play back the loaded replay, one move per animation step
+            if current_time - app.last_animation_update > app.settings.animation_speed
+                && let Some(movement) = app.replay_moves.pop_front()
+            {
+                app.playing_model.start_movement_animation(movement);
+                app.last_animation_update = current_time;
+            }
+        } else if app.popup_data.is_none() && ui.input(|i| i.key_pressed(egui::Key::Backspace)) {
+            // Not while a popup is open, so a move waiting to be confirmed is played from the
+            // state it was checked in
+            app.input_queue.clear();
+            app.playing_model.undo_move(); // Backspace rewinds the last move
+        } else {
+            if let Some(keypress) = app.get_movement_data() {
+                app.input_queue.push(keypress, current_time);
+            }
+            // Moves wait while a popup, e.g. the unwinnable move warning, is open. Answering
+            // yes to the warning leaves its move pending, to be made here first
+            if app.popup_data.is_none() {
+                if let Some(movement) = app.pending_move.take() {
+                    app.playing_model.start_movement_animation(movement);
+                    app.last_animation_update = current_time;
+                } else if let Some(keypress) = app.input_queue.pop(current_time) {
+                    if app.settings.warn_unwinnable && !app.move_keeps_win(keypress) {
+                        app.input_queue.clear();
+                        app.pending_move = Some(keypress);
+                        app.popup_data = Some(PopupData {
+                            message: "This move will make the level unwinnable. Continue?"
+                                .to_string(),
+                            popup_type: PopupType::YesNo {
+                                on_yes: |_app| {},
+                                on_no: Some(|app| app.pending_move = None),
+                            },
+                        });
+                    } else {
+                        app.playing_model.start_movement_animation(keypress);
+                        app.last_animation_update = current_time;
+                    }
+                }
+            }
         }
     } else if app.popup_data.is_none() {
         let current_time = ui.input(|i| i.time);
-        if current_time - app.last_animation_update > ANIMATION_SPEED {
+        // Hold on to moves pressed mid-slide rather than dropping them
+        if app.replay_moves.is_empty()
+            && let Some(keypress) = app.get_movement_data()
+        {
+            app.input_queue.push(keypress, current_time);
+        }
+        let step_due = if app.step_mode {
+            ui.input(|i| i.key_pressed(egui::Key::Period))
+        } else {
+            current_time - app.last_animation_update > app.settings.animation_speed
+        };
+        if step_due {
             app.last_animation_update = current_time;
             match app.playing_model.step_animation(&KeyItem::None) {
                 MovementPopupData::None => {}
@@ -855,53 +3659,711 @@ fn play_screen(ui: &mut egui::Ui, app: &mut App) {
                     });
                 }
                 MovementPopupData::Won => {
+                    app.replay_moves.clear();
+                    app.input_queue.clear();
+                    let headline = match app.playing_model.get_name() {
+                        "" => "You won!".to_string(),
+                        name => format!("You beat {name}!"),
+                    };
                     app.popup_data = Some(PopupData {
-                        message: "You won! Congratulations!".to_string(),
-                        popup_type: PopupType::Ok,
+                        message: format!(
+                            "{headline} Congratulations!\n\
+                             Time: {:.1}s\n\
+                             Moves: {}\n\
+                             Keys used: {}",
+                            app.playing_model.get_elapsed().as_secs_f64(),
+                            app.playing_model.get_move_count(),
+                            app.playing_model.get_keys_used(),
+                        ),
+                        popup_type: PopupType::Action {
+                            actions: if app.editing_model().get_next_level().is_some() {
+                                vec![
+                                    ("Save Replay", save_replay),
+                                    ("Next Level", load_next_level),
+                                ]
+                            } else {
+                                vec![("Save Replay", save_replay)]
+                            },
+                        },
                     });
                     app.mode = AppMode::Editing; // Switch back to editing mode after winning
                 }
                 MovementPopupData::Lost => {
+                    app.replay_moves.clear();
+                    app.input_queue.clear();
                     app.popup_data = Some(PopupData {
                         message: "You lost! Better luck next time!".to_string(),
                         popup_type: PopupType::Ok,
                     });
                     app.mode = AppMode::Editing; // Switch back to editing mode after losing
                 }
+                MovementPopupData::Stranded => {
+                    app.replay_moves.clear();
+                    app.input_queue.clear();
+                    app.popup_data = Some(PopupData {
+                        message: "You're stranded, the end can't be reached anymore! Better luck next time!"
+                            .to_string(),
+                        popup_type: PopupType::Ok,
+                    });
+                    app.mode = AppMode::Editing; // Switch back to editing mode after losing
+                }
             }
         }
     }
 }
 
+/// On-screen d-pad in the bottom left corner for playing without a keyboard. A tap presses
+/// the keys for its direction, so the move is picked up by `get_movement_data` like a key press
+fn display_touch_controls(ctx: &egui::Context, app: &mut App) {
+    egui::Area::new(egui::Id::new("touch_controls"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -8.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                egui::Grid::new("touch_dpad").show(ui, |ui| {
+                    for row in [
+                        [
+                            (DirectionKey::UpLeft, "↖"),
+                            (DirectionKey::Up, "↑"),
+                            (DirectionKey::UpRight, "↗"),
+                        ],
+                        [
+                            (DirectionKey::Left, "←"),
+                            (DirectionKey::None, "Use"),
+                            (DirectionKey::Right, "→"),
+                        ],
+                        [
+                            (DirectionKey::DownLeft, "↙"),
+                            (DirectionKey::Down, "↓"),
+                            (DirectionKey::DownRight, "↘"),
+                        ],
+                    ] {
+                        for (direction, label) in row {
+                            let button = egui::Button::new(egui::RichText::new(label).size(20.0))
+                                .min_size(egui::vec2(TOUCH_BUTTON_SIZE, TOUCH_BUTTON_SIZE));
+                            if ui.add(button).clicked() {
+                                let (row_offset, col_offset) = direction.offset();
+                                app.key_state.up = row_offset < 0;
+                                app.key_state.down = row_offset > 0;
+                                app.key_state.left = col_offset < 0;
+                                app.key_state.right = col_offset > 0;
+                                app.key_state.space = app.touch_run;
+                                app.key_state.enter = direction.is_none();
+                                app.key_state.keys_pressed_this_frame = true;
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.toggle_value(&mut app.touch_run, "Run");
+            });
+        });
+}
+
+/// Developer overlay with the last movement input and where it took the player
+fn display_debug_overlay(ui: &mut egui::Ui, app: &App) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        match app.playing_model.get_last_movement() {
+            Some(movement) => {
+                ui.monospace(format!(
+                    "Last input: direction {:?}, move_speed {}, use_tile {}",
+                    movement.direction, movement.move_speed, movement.use_tile
+                ));
+            }
+            None => {
+                ui.monospace("Last input: none");
+            }
+        }
+        if let Some((row_delta, col_delta)) = app.playing_model.get_last_displacement() {
+            ui.monospace(format!(
+                "Displacement: {row_delta:+} rows, {col_delta:+} columns"
+            ));
+        }
+        ui.monospace(format!(
+            "Player position: {:?}",
+            app.playing_model.get_player_pos()
+        ));
+    });
+}
+
+/// Where a move in step mode has got to, and how fast it's still going
+fn display_step_overlay(ui: &mut egui::Ui, app: &App) {
+    egui::Frame::group(ui.style()).show(ui, |ui| match &app.playing_model.animation_state {
+        Some(state) => {
+            ui.monospace(format!(
+                "Step: position {:?}, speed {}, direction {:?}, on {}",
+                app.playing_model.get_player_pos(),
+                state.movement_speed,
+                state.direction,
+                state.current_tile.label()
+            ));
+            ui.monospace("Press . to advance one tile");
+        }
+        None => {
+            ui.monospace(format!(
+                "Step mode: waiting for a move, at {:?}",
+                app.playing_model.get_player_pos()
+            ));
+        }
+    });
+}
+
+/// Load the board set to follow the one just won, relative to the current board's file, and
+/// start playing it
+fn load_next_level(app: &mut App) {
+    let Some(next_level) = app.editing_model().get_next_level() else {
+        return;
+    };
+    let path = match app.board_path().and_then(|path| path.parent()) {
+        Some(board_dir) => board_dir.join(next_level),
+        None => PathBuf::from(next_level),
+    };
+
+    match EditingModel::load_board(&path.to_string_lossy()) {
+        Ok(model) => {
+            // Unsaved edits to the level just won are kept open in their own tab
+            if app.editing_model().is_dirty() {
+                app.open_tab(model);
+            } else {
+                *app.editing_model_mut() = model;
+                app.reset_board_view();
+            }
+            app.set_board_path(path);
+            app.ghost = None; // Raced on the level just won
+            if app.editing_model_mut().board_is_playable() {
+                app.start_playing(false);
+            } else {
+                app.popup_data = Some(PopupData {
+                    message: "The next level can't be played yet, it's open for editing."
+                        .to_string(),
+                    popup_type: PopupType::Ok,
+                });
+            }
+        }
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading the next level at {}: {err}", path.display()),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
+fn save_replay(app: &mut App) {
+    let Ok(file_name) = open_file_dialog_with_filter(true, "Foam Game Replay", &["fgr"]) else {
+        return;
+    };
+
+    let replay = Replay::new(
+        app.editing_model(),
+        app.playing_model.get_record(),
+        app.playing_model.get_record_times(),
+    );
+    if let Err(err) = replay.save_replay(&file_name) {
+        app.popup_data = Some(PopupData {
+            message: format!("Error saving replay: {err}"),
+            popup_type: PopupType::Ok,
+        });
+    }
+}
+
+fn load_replay(app: &mut App) {
+    let Ok(file_name) = open_file_dialog_with_filter(false, "Foam Game Replay", &["fgr"]) else {
+        return;
+    };
+
+    match Replay::load_replay(&file_name) {
+        Ok(replay) => {
+            app.open_tab(replay.metadata.board);
+            app.ghost = None;
+            if app.editing_model_mut().board_is_playable() && app.start_playing(false) {
+                app.replay_moves = replay.moves.into();
+            }
+        }
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading replay: {err}"),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
+/// Race a saved replay of the board being played, its ghost starting with the first move
+fn load_ghost(app: &mut App) {
+    let Ok(file_name) = open_file_dialog_with_filter(false, "Foam Game Replay", &["fgr"]) else {
+        return;
+    };
+
+    let ghost = Replay::load_replay(&file_name).and_then(|replay| {
+        if replay.metadata.board.content_hash() != app.editing_model().content_hash() {
+            return Err(FoamError::InvalidBoard(
+                "the replay was recorded on a different board".to_string(),
+            ));
+        }
+        Ghost::new(&replay)
+    });
+    match ghost {
+        Ok(ghost) => app.ghost = Some(ghost),
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading ghost: {err}"),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
+fn save_progress(app: &mut App) {
+    let Ok(file_name) = open_file_dialog_with_filter(true, "Foam Game Progress", &["fgp"]) else {
+        return;
+    };
+
+    let progress = Progress::new(app.editing_model(), &app.playing_model);
+    if let Err(err) = progress.save_progress(&file_name) {
+        app.popup_data = Some(PopupData {
+            message: format!("Error saving progress: {err}"),
+            popup_type: PopupType::Ok,
+        });
+    }
+}
+
+fn resume_progress(app: &mut App) {
+    let Ok(file_name) = open_file_dialog_with_filter(false, "Foam Game Progress", &["fgp"]) else {
+        return;
+    };
+
+    match Progress::load_progress(&file_name) {
+        Ok(progress) => {
+            app.open_tab(progress.board);
+            app.ghost = None;
+            app.playing_model = progress.state;
+            app.replay_moves.clear();
+            app.input_queue.clear();
+            app.mode = AppMode::Playing;
+            app.fit_board = true;
+            app.camera_pos = None;
+        }
+        Err(err) => {
+            app.popup_data = Some(PopupData {
+                message: format!("Error loading progress: {err}"),
+                popup_type: PopupType::Ok,
+            });
+        }
+    }
+}
+
 fn display_playing_board(ui: &mut egui::Ui, app: &mut App) {
     ui.vertical(|ui| {
-        if ui.button("Switch to Editing Mode").clicked() {
-            app.mode = AppMode::Editing;
+        ui.horizontal(|ui| {
+            if ui.button("Switch to Editing Mode").clicked() {
+                app.mode = AppMode::Editing;
+                app.replay_moves.clear();
+            }
+            if ui.button("Fit to Window").clicked() {
+                app.fit_board = true;
+            }
+            // Saved between moves, a move in progress can't be resumed
+            if ui
+                .add_enabled(
+                    app.playing_model.animation_state.is_none() && app.replay_moves.is_empty(),
+                    egui::Button::new("Save Progress"),
+                )
+                .clicked()
+            {
+                save_progress(app);
+            }
+            ui.checkbox(&mut app.step_mode, "Step Mode")
+                .on_hover_text("Advance each move one tile at a time by pressing the period key.");
+            if app.ghost.is_some() {
+                if ui.button("Clear Ghost").clicked() {
+                    app.ghost = None;
+                }
+            } else if ui
+                .button("Race Ghost")
+                .on_hover_text("Race a saved replay of this board, starting with your first move.")
+                .clicked()
+            {
+                load_ghost(app);
+            }
+        });
+
+        if let Some(move_limit) = app.playing_model.get_move_limit() {
+            ui.label(format!(
+                "Moves: {} / {}",
+                app.playing_model.get_move_count(),
+                move_limit
+            ));
+        }
+
+        if app.playing_model.get_shields() > 0 {
+            ui.label(format!("Shields: {}", app.playing_model.get_shields()));
+        }
+
+        if let Some((row, col)) = app.playing_model.get_bookmark() {
+            // Shown unpadded, the way the editor numbers tiles
+            ui.label(format!("Bookmark: row {}, column {}", row - 1, col - 1));
         }
 
+        if app.playing_model.get_control_rotation() > 0 {
+            ui.label(format!(
+                "Controls rotated {}° clockwise",
+                u32::from(app.playing_model.get_control_rotation()) * 90
+            ));
+        }
+
+        if app.playing_model.clouds_remaining() + app.playing_model.clouds_consumed() > 0 {
+            ui.label(format!(
+                "Clouds remaining: {}",
+                app.playing_model.clouds_remaining()
+            ));
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Inventory:");
+            if app.playing_model.get_inventory().is_empty() {
+                ui.label("None");
+            }
+            for key in app.playing_model.get_inventory() {
+                draw_tile_and_key(
+                    &Tile::Empty,
+                    key,
+                    ui,
+                    app,
+                    false,
+                    TILE_PX,
+                    None,
+                    false,
+                    None,
+                );
+            }
+        });
+
         ui.add_space(50.0);
 
+        // Ctrl+scroll to zoom
+        let zoom_delta = ui.input(|i| i.zoom_delta());
+        if zoom_delta != 1.0 {
+            app.zoom = (app.zoom * zoom_delta).clamp(0.25, 4.0);
+            app.fit_board = false;
+        }
+        // Fit the board to the space left, every frame so it follows window resizes. Boards
+        // too big even at the minimum zoom scroll instead
+        if app.fit_board {
+            let board = app.playing_model.get_board();
+            let (rows, cols) = (board.len() as f32, board.first().map_or(0, Vec::len) as f32);
+            let available = ui.available_size();
+            let fit_px = ((available.x / cols).min(available.y / rows) - 1.0).max(0.0); // 1px grid spacing
+            app.zoom = (fit_px / TILE_PX).clamp(MIN_FIT_ZOOM, 4.0);
+        }
+        let tile_px = TILE_PX * app.zoom;
+
         let grid_id = format!(
             "playing_board_grid_{}",
             app.playing_model.get_player_pos().0
         );
 
-        egui::Grid::new(grid_id)
-            .spacing(egui::vec2(1.0, 1.0))
-            .min_col_width(0.0)
-            .show(ui, |ui| {
-                for (row_idx, row) in app.playing_model.get_board().iter().enumerate() {
-                    for (col_idx, tile) in row.iter().enumerate() {
-                        draw_tile_and_key(
-                            &tile.tile,
-                            &tile.key,
-                            ui,
-                            app,
-                            (row_idx, col_idx) == app.playing_model.get_player_pos(),
-                        );
+        let current_time = ui.input(|i| i.time);
+        if app.playing_model.take_wall_hit() {
+            app.collision_time = Some(current_time);
+        }
+        if let Some(direction) = app.playing_model.take_blocked_move() {
+            app.blocked_move = Some((direction, current_time));
+        }
+        if let Some(teleport) = app.playing_model.take_teleport() {
+            app.teleport = Some((teleport, current_time));
+        }
+        for sound in app.playing_model.take_sounds() {
+            if app.settings.sound_effects {
+                app.audio.play_sound(sound, app.settings.sound_volume);
+            }
+        }
+
+        let ice_momentum = app
+            .settings
+            .show_ice_momentum_in_play
+            .then(|| app.cached_ice_momentum());
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            let mut tile_rects = HashMap::new();
+            let mut hovered_portal = None; // (portal rect, padded destination, letter)
+            let board = egui::Grid::new(grid_id)
+                .spacing(egui::vec2(1.0, 1.0))
+                .min_col_width(0.0)
+                .show(ui, |ui| {
+                    for (row_idx, row) in app.playing_model.get_board().iter().enumerate() {
+                        for (col_idx, tile) in row.iter().enumerate() {
+                            // Blinkers are drawn closed when they are closed this turn
+                            let drawn_tile = match tile.tile {
+                                Tile::Blinker(phase) => {
+                                    Tile::Blinker(!app.playing_model.blinker_is_open(phase))
+                                }
+                                _ => tile.tile.clone(),
+                            };
+                            let response =
+                                draw_tile_and_key(
+                                    &drawn_tile,
+                                    &tile.key,
+                                    ui,
+                                    app,
+                                    (row_idx, col_idx) == app.playing_model.get_player_pos(),
+                                    tile_px,
+                                    Some(tile.tile.dynamic_explanation(
+                                        (row_idx, col_idx),
+                                        &app.playing_model,
+                                    )),
+                                    true,
+                                    app.playing_model
+                                        .get_background()
+                                        .color_at((row_idx, col_idx)),
+                                );
+                            tile_rects.insert((row_idx, col_idx), response.rect);
+                            // offset by 1 to account for padding
+                            if let Some(directions) = ice_momentum.as_ref().and_then(|momentum| {
+                                momentum.get(&(row_idx.checked_sub(1)?, col_idx.checked_sub(1)?))
+                            }) {
+                                draw_momentum_arrows(ui, response.rect, directions);
+                            }
+                            if let Tile::Portal(letter, dest) = tile.tile
+                                && response.hovered()
+                            {
+                                // offset by 1 to account for padding
+                                hovered_portal =
+                                    Some((response.rect, (dest.0 + 1, dest.1 + 1), letter));
+                            }
+                        }
+                        ui.end_row();
                     }
-                    ui.end_row();
+                });
+
+            // Keep the player in view on boards bigger than the window. Only scrolling when the
+            // player moves leaves the view alone otherwise, and the scroll area clamps the
+            // offset so the view rests against the board's edge instead of centering past it
+            let player_pos = app.playing_model.get_player_pos();
+            if app.camera_pos != Some(player_pos)
+                && let Some(player_rect) = tile_rects.get(&player_pos)
+            {
+                ui.scroll_to_rect(*player_rect, Some(egui::Align::Center));
+                app.camera_pos = Some(player_pos);
+            }
+
+            // The ghost, faint and without any effects of its own
+            if let Some(ghost) = &app.ghost
+                && let Some(rect) = tile_rects.get(&ghost.get_player_pos())
+            {
+                draw_player(
+                    ui.painter(),
+                    player_rect(*rect, app.zoom),
+                    app,
+                    GHOST_OPACITY,
+                );
+            }
+
+            // Show where a hovered portal leads
+            if let Some((portal_rect, dest, letter)) = hovered_portal
+                && let Some(dest_rect) = tile_rects.get(&dest)
+            {
+                let color = app
+                    .settings
+                    .color_palette
+                    .overlay_palette()
+                    .portal_color(letter);
+                ui.painter().line_segment(
+                    [portal_rect.center(), dest_rect.center()],
+                    egui::Stroke::new(1.0, color.gamma_multiply(0.4)),
+                );
+                ui.painter().rect_stroke(
+                    *dest_rect,
+                    0.0,
+                    egui::Stroke::new(2.0, color),
+                    egui::StrokeKind::Inside,
+                );
+            }
+
+            // Red arrow fading out on the player's tile, pointing the way it didn't allow
+            if let Some((direction, blocked_time)) = app.blocked_move {
+                let elapsed = current_time - blocked_time;
+                if elapsed < BLOCKED_MOVE_SECONDS
+                    && let Some(rect) = tile_rects.get(&app.playing_model.get_player_pos())
+                {
+                    let alpha = 255.0 * (1.0 - elapsed / BLOCKED_MOVE_SECONDS);
+                    let (row_offset, col_offset) = direction.offset();
+                    ui.painter().arrow(
+                        rect.center(),
+                        egui::vec2(col_offset as f32, row_offset as f32) * rect.width() * 0.4,
+                        egui::Stroke::new(
+                            3.0,
+                            egui::Color32::from_rgba_unmultiplied(255, 0, 0, alpha as u8),
+                        ),
+                    );
+                    ui.ctx().request_repaint();
+                } else {
+                    app.blocked_move = None;
                 }
-            });
+            }
+
+            // Portal jump: a ring closes in on the portal left behind while another opens out
+            // from the one arrived at
+            if let Some(((from, to), teleport_time)) = app.teleport {
+                let progress = ((current_time - teleport_time) / TELEPORT_EFFECT_SECONDS) as f32;
+                if progress < 1.0
+                    && let (Some(from_rect), Some(to_rect)) =
+                        (tile_rects.get(&from), tile_rects.get(&to))
+                {
+                    let color =
+                        egui::Color32::from_rgb(200, 60, 200).gamma_multiply(1.0 - progress);
+                    let stroke = egui::Stroke::new(3.0, color);
+                    ui.painter().circle_stroke(
+                        from_rect.center(),
+                        from_rect.width() * 0.5 * (1.0 - progress),
+                        stroke,
+                    );
+                    ui.painter().circle_stroke(
+                        to_rect.center(),
+                        to_rect.width() * 0.5 * progress,
+                        stroke,
+                    );
+                    ui.ctx().request_repaint();
+                } else {
+                    app.teleport = None;
+                }
+            }
+
+            // Red flash fading out after running into a wall
+            if let Some(collision_time) = app.collision_time {
+                let elapsed = current_time - collision_time;
+                if elapsed < COLLISION_FLASH_SECONDS && app.settings.collision_flash {
+                    let alpha = 60.0 * (1.0 - elapsed / COLLISION_FLASH_SECONDS);
+                    ui.painter().rect_filled(
+                        board.response.rect,
+                        0.0,
+                        egui::Color32::from_rgba_unmultiplied(255, 0, 0, alpha as u8),
+                    );
+                    ui.ctx().request_repaint();
+                } else {
+                    app.collision_time = None;
+                }
+            }
+        });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasted_board_opens_in_new_tab() {
+        let mut app = App::with_textures(
+            HashMap::new(),
+            PathBuf::from(DEFAULT_THEME_DIR),
+            AppSettings::default(),
+        );
+
+        // From the startup screen it takes the place of the empty board
+        app.play_pasted_board("SCE\n");
+        assert_eq!(app.tabs.len(), 1);
+        assert!(matches!(app.mode, AppMode::Playing));
+
+        // Unsaved edits are left open in their own tab
+        app.mode = AppMode::Editing;
+        app.editing_model_mut().set_tile((0, 1), Tile::Ice);
+        app.play_pasted_board("SCCE\n");
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.active_tab, 1);
+        assert!(app.tabs[0].model.is_dirty());
+        assert!(app.popup_data.is_none());
+        assert!(matches!(app.mode, AppMode::Playing));
+    }
+
+    #[test]
+    fn tabs_switch_and_close() {
+        let mut app = App::with_textures(
+            HashMap::new(),
+            PathBuf::from(DEFAULT_THEME_DIR),
+            AppSettings::default(),
+        );
+        *app.editing_model_mut() = EditingModel::new((3, 4));
+        app.mode = AppMode::Editing;
+        app.new_tab();
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.active_tab, 1);
+        assert_eq!(app.editing_model().get_board_size(), (3, 4));
+
+        // Edits go to the active tab, and switching drops what belonged to it
+        app.editing_model_mut().set_tile((0, 0), Tile::Wall);
+        app.selection = Some(((0, 0), (1, 1)));
+        assert_eq!(app.tabs[1].title(), "Untitled •");
+        app.switch_tab(0);
+        assert_eq!(app.selection, None);
+        assert!(!app.editing_model().is_dirty());
+
+        // An imported board opens in its own tab, leaving unsaved edits and their selection
+        // behind
+        app.switch_tab(1);
+        app.tabs[1].path = Some(PathBuf::from("edited.fg"));
+        app.selection = Some(((0, 0), (1, 1)));
+        app.open_tab(EditingModel::from_ascii("SCE\n").unwrap());
+        assert_eq!(app.tabs.len(), 3);
+        assert_eq!(app.tabs[2].path, None);
+        assert_eq!(app.selection, None);
+        assert!(app.tabs[1].model.is_dirty());
+        app.close_tab();
+
+        // Closing a tab with unsaved changes asks first
+        app.switch_tab(1);
+        app.request_close_tab();
+        assert!(app.popup_data.is_some());
+        assert_eq!(app.tabs.len(), 2);
+
+        // Closing the last tab goes back to the startup screen
+        app.close_tab();
+        assert_eq!(app.active_tab, 0);
+        app.close_tab();
+        assert_eq!(app.tabs.len(), 1);
+        assert!(matches!(app.mode, AppMode::Startup));
+    }
+
+    /// Average time to build a frame of the editing board for a 40x20 board, with the tile
+    /// cache kept and with it rebuilt every frame to compare against. Run with
+    /// `cargo test --release -- --ignored --nocapture editing_board_frame_time`
+    #[test]
+    #[ignore]
+    fn editing_board_frame_time() {
+        const FRAMES: u32 = 200;
+
+        let ctx = egui::Context::default();
+        let theme_dir = PathBuf::from(DEFAULT_THEME_DIR);
+        let texture_cache = load_textures(&ctx, &theme_dir);
+        let mut app = App::with_textures(texture_cache, theme_dir, AppSettings::default());
+        *app.editing_model_mut() = EditingModel::generate_random((20, 40), 7);
+        app.mode = AppMode::Editing;
+
+        let input = || egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(1920.0, 1080.0),
+            )),
+            ..Default::default()
+        };
+        let mut frame = |cached: bool| {
+            if !cached {
+                app.editing_board_cache = None;
+            }
+            let output = ctx.run(input(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| display_editing_board(ui, &mut app));
+            });
+            ctx.tessellate(output.shapes, output.pixels_per_point)
+        };
+        frame(true); // Warm up the font and layout caches
+
+        for cached in [true, false] {
+            let start = Instant::now();
+            for _ in 0..FRAMES {
+                frame(cached);
+            }
+            let per_frame = start.elapsed() / FRAMES;
+            let label = if cached { "cached" } else { "uncached" };
+            println!("Editing board, 40x20, {label}: {per_frame:?} per frame");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//!
+//! Controller input for play. Reading controllers needs the `gamepad` feature, without it or
+//! without a connected controller only the keyboard moves the player.
+//!
+
+use std::f32::consts::FRAC_PI_4;
+
+/// How far the analog stick has to be pushed, out of 1, before it counts as a direction
+const STICK_DEADZONE: f32 = 0.5;
+
+/// Buttons held on the controllers, mapped onto the keyboard's controls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GamepadState {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub run: bool,      // Same as holding space
+    pub use_tile: bool, // Same as pressing enter
+}
+
+impl GamepadState {
+    /// Directions held in the same order as the arrow keys: up, down, left, right
+    pub fn directions(&self) -> [bool; 4] {
+        [self.up, self.down, self.left, self.right]
+    }
+}
+
+/// Directions an analog stick position points in, as up, down, left, right. The stick's
+/// circle is cut into eight equal slices, so it snaps to the nearest cardinal or diagonal
+/// and a slightly crooked push doesn't turn into a diagonal
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+fn stick_directions(x: f32, y: f32) -> [bool; 4] {
+    if x.hypot(y) < STICK_DEADZONE {
+        return [false; 4];
+    }
+
+    // Slice 0 is right, counting anticlockwise as y points up
+    let slice = ((y.atan2(x) / FRAC_PI_4).round() as i32).rem_euclid(8);
+    let up = matches!(slice, 1..=3);
+    let down = matches!(slice, 5..=7);
+    let left = matches!(slice, 3..=5);
+    let right = matches!(slice, 0 | 1 | 7);
+    [up, down, left, right]
+}
+
+#[cfg(feature = "gamepad")]
+mod input {
+    use super::{GamepadState, stick_directions};
+
+    use gilrs::{Axis, Button, Gilrs};
+
+    /// The controller library, tracking every connected controller
+    pub struct Input {
+        gilrs: Gilrs,
+    }
+
+    impl Input {
+        pub fn open() -> Option<Input> {
+            match Gilrs::new() {
+                Ok(gilrs) => Some(Input { gilrs }),
+                Err(err) => {
+                    eprintln!("Warning: no controller support, playing with the keyboard: {err}");
+                    None
+                }
+            }
+        }
+
+        pub fn is_connected(&self) -> bool {
+            self.gilrs.gamepads().next().is_some()
+        }
+
+        pub fn poll(&mut self) -> GamepadState {
+            // Events have to be drained for the controllers' states to update
+            while self.gilrs.next_event().is_some() {}
+
+            let mut state = GamepadState::default();
+            for (_, gamepad) in self.gilrs.gamepads() {
+                let [up, down, left, right] = stick_directions(
+                    gamepad.value(Axis::LeftStickX),
+                    gamepad.value(Axis::LeftStickY),
+                );
+                state.up |= up || gamepad.is_pressed(Button::DPadUp);
+                state.down |= down || gamepad.is_pressed(Button::DPadDown);
+                state.left |= left || gamepad.is_pressed(Button::DPadLeft);
+                state.right |= right || gamepad.is_pressed(Button::DPadRight);
+                state.run |= gamepad.is_pressed(Button::South);
+                state.use_tile |= gamepad.is_pressed(Button::East);
+            }
+            state
+        }
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+mod input {
+    use super::GamepadState;
+
+    /// Built without the `gamepad` feature, so there's never a controller to read
+    pub struct Input;
+
+    impl Input {
+        pub fn open() -> Option<Input> {
+            None
+        }
+
+        pub fn is_connected(&self) -> bool {
+            false
+        }
+
+        pub fn poll(&mut self) -> GamepadState {
+            GamepadState::default()
+        }
+    }
+}
+
+/// Reads the connected controllers, or nothing when controllers aren't supported
+pub struct Gamepad {
+    input: Option<input::Input>,
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        Gamepad {
+            input: input::Input::open(),
+        }
+    }
+
+    /// Whether there's a controller whose input needs polling for
+    pub fn is_connected(&self) -> bool {
+        self.input
+            .as_ref()
+            .is_some_and(|input| input.is_connected())
+    }
+
+    /// Buttons held across every connected controller
+    pub fn poll(&mut self) -> GamepadState {
+        self.input
+            .as_mut()
+            .map(|input| input.poll())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_snaps_to_eight_directions() {
+        // Inside the deadzone nothing is held
+        assert_eq!(stick_directions(0.2, -0.3), [false; 4]);
+
+        assert_eq!(stick_directions(0.0, 1.0), [true, false, false, false]);
+        assert_eq!(stick_directions(-0.9, 0.0), [false, false, true, false]);
+        // A slightly crooked push stays cardinal
+        assert_eq!(stick_directions(0.9, -0.2), [false, false, false, true]);
+        assert_eq!(stick_directions(0.7, 0.7), [true, false, false, true]);
+        assert_eq!(stick_directions(-0.6, -0.6), [false, true, true, false]);
+    }
+}
@@ -0,0 +1,94 @@
+//!
+//! Random level generation, for quick prototyping.
+//!
+
+use super::editing_model::EditingModel;
+use super::tile::{CardinalDirectionsAllowed, DiagonalDirectionsAllowed, Tile};
+
+/// Small seeded random number generator (SplitMix64), so a seed always gives the same level
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+impl EditingModel {
+    /// Random board with this many rows and columns that can always be won. Walls, ice and
+    /// movement tiles are scattered around a start and an end, and if that can't be won a
+    /// path of movement tiles is carved from the start to the end
+    pub fn generate_random(size: (usize, usize), seed: u64) -> EditingModel {
+        let size = (size.0.max(1), size.1.max(2)); // Room for a start and an end
+        let mut rng = SplitMix64(seed);
+        let mut model = EditingModel::new(size);
+
+        for row in 0..size.0 {
+            for col in 0..size.1 {
+                let tile = match rng.below(20) {
+                    0..=10 => Tile::MoveCardinal(CardinalDirectionsAllowed::from_mask(0b1111)),
+                    11..=13 => Tile::Wall,
+                    14..=16 => Tile::Ice,
+                    17..=18 => Tile::MoveDiagonal(DiagonalDirectionsAllowed::from_mask(0b1111)),
+                    _ => Tile::Empty,
+                };
+                model.set_tile((row, col), tile);
+            }
+        }
+
+        let cell_count = size.0 * size.1;
+        let start = rng.below(cell_count);
+        let end = (start + 1 + rng.below(cell_count - 1)) % cell_count; // Any other cell
+        let start = (start / size.1, start % size.1);
+        let end = (end / size.1, end % size.1);
+        model.set_tile(start, Tile::StartSpace);
+        model.set_tile(end, Tile::EndSpace);
+
+        if !model.board_is_playable() || model.solution_length().is_none() {
+            // Walk along the start's row, then down or up the end's column
+            let path = (start.1.min(end.1)..=start.1.max(end.1))
+                .map(|col| (start.0, col))
+                .chain((start.0.min(end.0)..=start.0.max(end.0)).map(|row| (row, end.1)));
+            for pos in path {
+                if pos != start && pos != end {
+                    model.set_tile(
+                        pos,
+                        Tile::MoveCardinal(CardinalDirectionsAllowed::from_mask(0b1111)),
+                    );
+                }
+            }
+        }
+
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_boards_are_solvable() {
+        for seed in 0..10 {
+            let mut model = EditingModel::generate_random((6, 8), seed);
+            assert!(model.board_is_playable(), "seed {seed}");
+            assert!(model.solution_length().is_some(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_board() {
+        let board = EditingModel::generate_random((6, 6), 42).to_ascii();
+        assert_eq!(EditingModel::generate_random((6, 6), 42).to_ascii(), board);
+        assert_ne!(EditingModel::generate_random((6, 6), 43).to_ascii(), board);
+    }
+}
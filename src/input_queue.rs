@@ -0,0 +1,85 @@
+//!
+//! Buffering of movement inputs pressed while the player is still moving.
+//!
+
+use super::game_ui::PlayerMovementData;
+
+use std::collections::VecDeque;
+
+/// Most inputs held at once, so mashing keys can't queue up a long string of moves
+const INPUT_QUEUE_CAPACITY: usize = 2;
+
+/// Seconds an input is kept before it's too old to act on
+const INPUT_MAX_AGE: f64 = 0.3;
+
+/// Movement inputs waiting for the current animation to finish, with the time each was pressed
+#[derive(Debug, Default)]
+pub struct InputQueue {
+    inputs: VecDeque<(PlayerMovementData, f64)>,
+}
+
+impl InputQueue {
+    /// Queue an input pressed at `time`. When full, the oldest input makes room for it
+    pub fn push(&mut self, movement: PlayerMovementData, time: f64) {
+        if self.inputs.len() == INPUT_QUEUE_CAPACITY {
+            self.inputs.pop_front();
+        }
+        self.inputs.push_back((movement, time));
+    }
+
+    /// Oldest input still fresh at `time`, dropping any that have gone stale
+    pub fn pop(&mut self, time: f64) -> Option<PlayerMovementData> {
+        while let Some((movement, pressed)) = self.inputs.pop_front() {
+            if time - pressed <= INPUT_MAX_AGE {
+                return Some(movement);
+            }
+        }
+        None
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.inputs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_ui::DirectionKey;
+
+    fn movement(direction: DirectionKey) -> PlayerMovementData {
+        PlayerMovementData {
+            direction,
+            move_speed: 1,
+            use_tile: false,
+        }
+    }
+
+    #[test]
+    fn queue_keeps_order_capacity_and_freshness() {
+        let mut queue = InputQueue::default();
+        queue.push(movement(DirectionKey::Up), 0.0);
+        queue.push(movement(DirectionKey::Right), 0.1);
+        assert_eq!(queue.pop(0.2), Some(movement(DirectionKey::Up)));
+        assert_eq!(queue.pop(0.2), Some(movement(DirectionKey::Right)));
+        assert_eq!(queue.pop(0.2), None);
+
+        // A third input pushes out the oldest
+        queue.push(movement(DirectionKey::Up), 0.0);
+        queue.push(movement(DirectionKey::Right), 0.0);
+        queue.push(movement(DirectionKey::Down), 0.0);
+        assert_eq!(queue.pop(0.1), Some(movement(DirectionKey::Right)));
+
+        // Stale inputs are skipped over
+        queue.clear();
+        queue.push(movement(DirectionKey::Left), 0.0);
+        queue.push(movement(DirectionKey::Down), 0.5);
+        assert_eq!(queue.pop(0.6), Some(movement(DirectionKey::Down)));
+        queue.push(movement(DirectionKey::Left), 1.0);
+        assert_eq!(queue.pop(2.0), None);
+    }
+}
@@ -2,36 +2,41 @@
 //! Game model for keys (single-use items).
 //!
 
+use super::tile::Tile;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Keys that activate on receiving them
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnGet {
     FinishKey, // Must get before going to finish
 }
 
 /// Keys that activate on use
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnUse {
     TeleportKey(char), // Teleport to a portal
+    Bookmark,          // Mark a position on the first use, return to it on the second
 }
 
 /// Keys that activate on movement
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnMovement {
     Cardinal, // Move in a (disallowed) cardinal direction
     Diagonal, // Move in a diagonal direction
 }
 
 /// Keys that activate on hitting a wall
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnWall {
     DoorKey(char), // Open a door
     Wall,          // Jump over a wall
 }
 
 /// Keys that activate mid-bounce
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnBounce {
     BounceLess,   // Bounce -1 less
     BounceMore,   // Bounce +1 more
@@ -39,13 +44,14 @@ pub enum KeyOnBounce {
 }
 
 /// Keys that activate on landing on an empty tile
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnEmpty {
     CloudKey, // Jump on air
 }
 
 /// Keys that are equiped
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyOnEquip {
     OnMovement(KeyOnMovement),
     OnWall(KeyOnWall),
@@ -53,7 +59,7 @@ pub enum KeyOnEquip {
     OnEmpty(KeyOnEmpty),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum KeyItem {
     None, // No key item
     OnGet(KeyOnGet),
@@ -70,27 +76,134 @@ use KeyOnUse::*;
 use KeyOnWall::*;
 
 impl KeyItem {
+    /// Texture path, relative to the theme directory
     pub fn file_name(&self) -> &str {
         match self {
-            KeyItem::None => "assets/keys/none.png",
-            KeyItem::OnGet(FinishKey) => "assets/keys/finish.png",
-            KeyItem::OnUse(TeleportKey(_c)) => "assets/keys/teleport.png",
-            KeyItem::OnEquip(OnMovement(Cardinal)) => "assets/keys/cardinal.png",
-            KeyItem::OnEquip(OnMovement(Diagonal)) => "assets/keys/diagonal.png",
-            KeyItem::OnEquip(OnWall(DoorKey(_c))) => "assets/keys/door.png",
-            KeyItem::OnEquip(OnWall(Wall)) => "assets/keys/wall.png",
-            KeyItem::OnEquip(OnBounce(BounceLess)) => "assets/keys/bounce_less.png",
-            KeyItem::OnEquip(OnBounce(BounceMore)) => "assets/keys/bounce_more.png",
-            KeyItem::OnEquip(OnBounce(BounceChange)) => "assets/keys/bounce_change.png",
-            KeyItem::OnEquip(OnEmpty(CloudKey)) => "assets/keys/cloud.png",
+            KeyItem::None => "keys/none.png",
+            KeyItem::OnGet(FinishKey) => "keys/finish.png",
+            KeyItem::OnUse(TeleportKey(_c)) => "keys/teleport.png",
+            KeyItem::OnUse(Bookmark) => "keys/bookmark.png",
+            KeyItem::OnEquip(OnMovement(Cardinal)) => "keys/cardinal.png",
+            KeyItem::OnEquip(OnMovement(Diagonal)) => "keys/diagonal.png",
+            KeyItem::OnEquip(OnWall(DoorKey(_c))) => "keys/door.png",
+            KeyItem::OnEquip(OnWall(Wall)) => "keys/wall.png",
+            KeyItem::OnEquip(OnBounce(BounceLess)) => "keys/bounce_less.png",
+            KeyItem::OnEquip(OnBounce(BounceMore)) => "keys/bounce_more.png",
+            KeyItem::OnEquip(OnBounce(BounceChange)) => "keys/bounce_change.png",
+            KeyItem::OnEquip(OnEmpty(CloudKey)) => "keys/cloud.png",
         }
     }
 
     /// Overlay symbol to draw over the key, if any
     pub fn overlay(&self) -> Option<char> {
+        match *self {
+            KeyItem::OnUse(TeleportKey(c)) => Some(c),
+            KeyItem::OnEquip(OnWall(DoorKey(c))) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Letter of a teleport or door key, to change it in place
+    pub fn letter_mut(&mut self) -> Option<&mut char> {
         match self {
-            &KeyItem::OnUse(TeleportKey(c)) => Some(c),
-            &KeyItem::OnEquip(OnWall(DoorKey(c))) => Some(c),
+            KeyItem::OnUse(TeleportKey(c)) | KeyItem::OnEquip(OnWall(DoorKey(c))) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Step the letter of a teleport or door key, returning false for keys without a letter
+    pub fn cycle_letter(&mut self, forward: bool) -> bool {
+        match self.letter_mut() {
+            Some(c) => {
+                *c = step_letter(*c, forward);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Short display name
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyItem::None => "None",
+            KeyItem::OnGet(FinishKey) => "Finish",
+            KeyItem::OnUse(TeleportKey(_c)) => "Teleport",
+            KeyItem::OnUse(Bookmark) => "Bookmark",
+            KeyItem::OnEquip(OnMovement(Cardinal)) => "Cardinal",
+            KeyItem::OnEquip(OnMovement(Diagonal)) => "Diagonal",
+            KeyItem::OnEquip(OnWall(DoorKey(_c))) => "Door",
+            KeyItem::OnEquip(OnWall(Wall)) => "Wall",
+            KeyItem::OnEquip(OnBounce(BounceLess)) => "Bounce Less",
+            KeyItem::OnEquip(OnBounce(BounceMore)) => "Bounce More",
+            KeyItem::OnEquip(OnBounce(BounceChange)) => "Bounce Change",
+            KeyItem::OnEquip(OnEmpty(CloudKey)) => "Cloud",
+        }
+    }
+
+    /// Name used for this key in the ASCII board format
+    pub fn ascii_name(&self) -> String {
+        match self {
+            KeyItem::None => "none".to_string(),
+            KeyItem::OnGet(FinishKey) => "finish".to_string(),
+            KeyItem::OnUse(TeleportKey(c)) => format!("teleport:{c}"),
+            KeyItem::OnUse(Bookmark) => "bookmark".to_string(),
+            KeyItem::OnEquip(OnMovement(Cardinal)) => "cardinal".to_string(),
+            KeyItem::OnEquip(OnMovement(Diagonal)) => "diagonal".to_string(),
+            KeyItem::OnEquip(OnWall(DoorKey(c))) => format!("door:{c}"),
+            KeyItem::OnEquip(OnWall(Wall)) => "wall".to_string(),
+            KeyItem::OnEquip(OnBounce(BounceLess)) => "bounce_less".to_string(),
+            KeyItem::OnEquip(OnBounce(BounceMore)) => "bounce_more".to_string(),
+            KeyItem::OnEquip(OnBounce(BounceChange)) => "bounce_change".to_string(),
+            KeyItem::OnEquip(OnEmpty(CloudKey)) => "cloud".to_string(),
+        }
+    }
+
+    /// Key for a name in the ASCII board format
+    pub fn from_ascii_name(name: &str) -> Option<KeyItem> {
+        let (name, letter) = match name.split_once(':') {
+            Some((name, letter)) => {
+                let mut chars = letter.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c @ 'A'..='Z'), None) => (name, Some(c)),
+                    _ => return None,
+                }
+            }
+            None => (name, None),
+        };
+
+        match (name, letter) {
+            ("none", None) => Some(KeyItem::None),
+            ("teleport", Some(c)) => Some(KeyItem::OnUse(TeleportKey(c))),
+            ("door", Some(c)) => Some(KeyItem::OnEquip(OnWall(DoorKey(c)))),
+            (name, None) => ALL_KEYS
+                .iter()
+                .find(|key| key.overlay().is_none() && key.ascii_name() == name)
+                .cloned(),
+            _ => None,
+        }
+    }
+
+    /// Whether the key makes sense on a tile, see `incompatibility`
+    pub fn is_compatible_with(&self, tile: &Tile) -> bool {
+        self.incompatibility(tile).is_none()
+    }
+
+    /// Why the player could never make use of this key on a tile, None if it fits there
+    pub fn incompatibility(&self, tile: &Tile) -> Option<&'static str> {
+        match (self, tile) {
+            (KeyItem::None, _) => None,
+            (_, Tile::Empty) => Some("Keys can't go on empty tiles, there's nothing to stand on."),
+            (_, Tile::Wall) => Some("The player can never stand on a wall to pick up a key."),
+            (_, Tile::Gap) => Some("The player can never land in a gap to pick up a key."),
+            (_, Tile::EndSpace) => {
+                Some("A key on the end is only picked up once the level is already won.")
+            }
+            (_, Tile::ResetTrap(true)) => {
+                Some("This reset trap takes the player's keys, so it takes this one straight back.")
+            }
+            (KeyItem::OnEquip(OnWall(DoorKey(_))), Tile::Door) => {
+                Some("A door key on a door can only be picked up once a door is already open.")
+            }
             _ => None,
         }
     }
@@ -102,6 +215,9 @@ impl KeyItem {
             KeyItem::OnUse(TeleportKey(_c)) => {
                 "A key that teleports you to a portal with the same letter."
             }
+            KeyItem::OnUse(Bookmark) => {
+                "A key that marks where you stand the first time you use it, and takes you back there the second time."
+            }
             KeyItem::OnEquip(OnMovement(Cardinal)) => {
                 "A key that allows you to move in a disallowed cardinal direction."
             }
@@ -120,9 +236,20 @@ impl KeyItem {
     }
 }
 
+/// Next (or previous) portal or door letter, wrapping around between A and Z
+pub fn step_letter(c: char, forward: bool) -> char {
+    match (c, forward) {
+        ('A'..='Y', true) => (c as u8 + 1) as char,
+        ('B'..='Z', false) => (c as u8 - 1) as char,
+        (_, true) => 'A',
+        (_, false) => 'Z',
+    }
+}
+
 pub const ALL_KEYS: &[KeyItem] = &[
     KeyItem::OnGet(FinishKey),
     KeyItem::OnUse(TeleportKey('A')),
+    KeyItem::OnUse(Bookmark),
     KeyItem::OnEquip(OnMovement(Cardinal)),
     KeyItem::OnEquip(OnMovement(Diagonal)),
     KeyItem::OnEquip(OnWall(DoorKey('A'))),
@@ -132,3 +259,39 @@ pub const ALL_KEYS: &[KeyItem] = &[
     KeyItem::OnEquip(OnBounce(BounceChange)),
     KeyItem::OnEquip(OnEmpty(CloudKey)),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_letter_updates_overlay() {
+        let mut key = KeyItem::OnUse(TeleportKey('A'));
+        assert!(key.cycle_letter(true));
+        assert_eq!(key.overlay(), Some('B'));
+        key.cycle_letter(false);
+        key.cycle_letter(false);
+        assert_eq!(key.overlay(), Some('Z'));
+
+        let mut key = KeyItem::OnEquip(OnWall(DoorKey('Z')));
+        key.cycle_letter(true);
+        assert_eq!(key.overlay(), Some('A'));
+
+        let mut key = KeyItem::OnGet(FinishKey);
+        assert!(!key.cycle_letter(true));
+    }
+
+    #[test]
+    fn keys_fit_tiles_the_player_can_stand_on() {
+        assert!(KeyItem::OnGet(FinishKey).is_compatible_with(&Tile::Ice));
+        assert!(KeyItem::OnEquip(OnWall(Wall)).is_compatible_with(&Tile::Bounce(1)));
+        assert!(KeyItem::OnEquip(OnWall(DoorKey('A'))).is_compatible_with(&Tile::ResetTrap(false)));
+        assert!(KeyItem::None.is_compatible_with(&Tile::Empty));
+
+        assert!(!KeyItem::OnEquip(OnWall(DoorKey('A'))).is_compatible_with(&Tile::Wall));
+        assert!(!KeyItem::OnEquip(OnWall(DoorKey('A'))).is_compatible_with(&Tile::Door));
+        assert!(!KeyItem::OnUse(Bookmark).is_compatible_with(&Tile::Empty));
+        assert!(!KeyItem::OnGet(FinishKey).is_compatible_with(&Tile::EndSpace));
+        assert!(!KeyItem::OnEquip(OnEmpty(CloudKey)).is_compatible_with(&Tile::ResetTrap(true)));
+    }
+}
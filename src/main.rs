@@ -2,10 +2,19 @@
     Modules
 */
 
+mod audio;
+mod batch;
 mod editing_model;
+mod error;
 mod game_ui;
+mod gamepad;
+mod generator;
+mod input_queue;
 mod item;
 mod playing_model;
+mod progress;
+mod replay;
+mod settings;
 mod tile;
 
 /*
@@ -14,8 +23,45 @@ mod tile;
 
 use eframe::{self, NativeOptions};
 use game_ui::App;
+use std::path::{Path, PathBuf};
 
 fn main() -> Result<(), eframe::Error> {
+    // `foam_game --validate <dir>` checks every level in a directory instead of opening the game
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, flag, dir] = args.as_slice()
+        && flag == "--validate"
+    {
+        let all_passed = match batch::check_levels(Path::new(dir)) {
+            Ok(checks) => batch::print_summary(&checks),
+            Err(err) => {
+                eprintln!("Error reading {dir}: {err}");
+                false
+            }
+        };
+        std::process::exit(if all_passed { 0 } else { 1 });
+    }
+
+    // `foam_game --schema` prints the JSON Schema of board files, for validating them elsewhere
+    if let [_, flag] = args.as_slice()
+        && flag == "--schema"
+    {
+        match editing_model::EditingModel::json_schema() {
+            Ok(schema) => println!("{schema}"),
+            Err(err) => {
+                eprintln!("Error writing the schema: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // `foam_game [--play] <board>` opens a board straight away, for editing or playing
+    let (board_path, play) = match args.as_slice() {
+        [_, flag, path] if flag == "--play" => (Some(PathBuf::from(path)), true),
+        [_, path] => (Some(PathBuf::from(path)), false),
+        _ => (None, false),
+    };
+
     let mut options = NativeOptions::default();
     options.viewport.resizable = Some(true);
     options.viewport.inner_size = Some(egui::vec2(1600.0, 900.0));
@@ -23,6 +69,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Foam Game",
         options,
-        Box::new(|cc| Ok(Box::new(App::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(App::new(cc, board_path, play)))),
     )
 }
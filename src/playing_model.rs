@@ -2,19 +2,33 @@
 //! Logic for editing and playing the game
 //!
 
-use super::item::KeyItem;
-use super::tile::{Tile, TileData};
-use crate::{editing_model, game_ui::DirectionKey, game_ui::PlayerMovementData};
+use super::audio::SoundEffect;
+use super::error::FoamError;
+use super::item::{ALL_KEYS, KeyItem, KeyOnEquip, KeyOnGet, KeyOnUse, KeyOnWall};
+use super::tile::{CardinalDirectionsAllowed, Tile, TileData};
+
+use crate::{
+    editing_model::{self, MAX_BOARD_SIDE},
+    game_ui::{ALL_DIRECTIONS, DirectionKey, PlayerMovementData},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub enum MovementPopupData {
     None, // No popup
 
-    Lost, // Lost the game
-    Won,  // Won the game
-    Wall, // Hit a wall
+    Lost,     // Lost the game
+    Stranded, // Lost the game, the end can no longer be reached
+    Won,      // Won the game
+    Wall,     // Hit a wall
 }
 
+/// A jump between portals, (from, to) in padded board positions
+pub type Teleport = ((usize, usize), (usize, usize));
+
 #[derive(Debug, Clone)]
 pub struct PlayingAnimationState {
     pub current_tile: Tile,
@@ -24,22 +38,156 @@ pub struct PlayingAnimationState {
     pub use_tile: bool,
     pub finished: bool,
     pub waiting_on_item: bool, // whether the animation is waiting for the user to use a key
+    pub momentum: bool, // whether the player is sliding from ice, and keeps going over empty tiles
+    pub reversals: usize, // number of reverse tiles hit during this move
+    pub arrow_pushes: usize, // number of arrow tiles that pushed the player during this move
+}
+
+/// Most moves that can be undone
+const MAX_UNDO_HISTORY: usize = 200;
+
+/// Everything a move can change, saved before the move so it can be undone
+#[derive(Debug, Clone)]
+struct PlayingSnapshot {
+    board: Vec<Vec<TileData>>,
+    player_pos: (usize, usize),
+    move_count: usize,
+    record_len: usize,
+    consumed_clouds: HashSet<(usize, usize)>,
+    covered_tiles: HashMap<(usize, usize), Tile>,
+    inventory: Vec<KeyItem>,
+    activated_checkpoints: HashSet<(usize, usize)>,
+    respawn_pos: Option<(usize, usize)>,
+    turn_parity: bool,
+    keys_used: usize,
+    shields: u32,
+    collected_shields: HashSet<(usize, usize)>,
+    control_rotation: u8,
+    bookmark: Option<(usize, usize)>,
+}
+
+/// Reverse tiles hit in a single move before the player stops, so facing reverse tiles can't loop forever
+const MAX_REVERSALS_PER_MOVE: usize = 4;
+
+/// Arrow tiles that can push the player in a single move, so a loop of arrows ends
+const MAX_ARROW_PUSHES_PER_MOVE: usize = 64;
+
+/// Ground under a block that hasn't been pushed yet, left behind once it is
+const BLOCK_GROUND: Tile = Tile::MoveCardinal(CardinalDirectionsAllowed {
+    up: true,
+    right: true,
+    down: true,
+    left: true,
+});
+
+/// Play time is saved as the time played so far, and restored as a start that long ago
+mod elapsed_time {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        start_time: &Option<Instant>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        start_time
+            .map(|start_time| start_time.elapsed())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Instant>, D::Error> {
+        Ok(Option::<Duration>::deserialize(deserializer)?
+            .and_then(|elapsed| Instant::now().checked_sub(elapsed)))
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Tiles under pushed blocks are saved as a list, since positions can't be JSON object keys
+mod covered_tiles {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        covered_tiles: &HashMap<(usize, usize), Tile>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        covered_tiles
+            .iter()
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(usize, usize), Tile>, D::Error> {
+        Ok(Vec::<((usize, usize), Tile)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Saved between moves, so the undo history, a move in progress and the feedback flags for
+/// the last move aren't kept
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PlayingModel {
     board: Vec<Vec<TileData>>,
     board_size: (usize, usize), // size of the board, including padding
     player_pos: (usize, usize), // position of the player
+    start_pos: (usize, usize),  // where the player started, reset traps send them back here
+    move_count: usize,          // number of moves made so far
+    move_limit: Option<usize>,  // maximum number of moves before losing, if any
+    run_speed: usize,           // tiles moved per run move
+    background: editing_model::BackgroundStyle, // painted behind the tiles
+    gravity: Option<DirectionKey>, // direction the player is pulled one tile after every move
+    name: String,               // level name, empty if unnamed
+    #[serde(with = "elapsed_time")]
+    start_time: Option<Instant>, // when play started
+    keys_used: usize,           // keys used up so far
+    record: Vec<PlayerMovementData>, // every move started so far, for replays
+    #[serde(default)]
+    record_times: Vec<f64>, // seconds into play each recorded move started
+    consumed_clouds: HashSet<(usize, usize)>, // positions of clouds that have disappeared
+    #[serde(default, with = "covered_tiles")]
+    covered_tiles: HashMap<(usize, usize), Tile>, // tiles pushed blocks rest on, by block position
+    inventory: Vec<KeyItem>,    // keys picked up and not yet used
+    activated_checkpoints: HashSet<(usize, usize)>, // checkpoints opened with a finish key
+    respawn_pos: Option<(usize, usize)>, // last checkpoint reached, where falling respawns
+    solvable: bool, // whether the end could be reached at the start, so stranding is detectable
+    #[serde(skip)]
+    history: VecDeque<PlayingSnapshot>, // state before each move, most recent last
+    #[serde(skip)]
+    last_move_origin: Option<(usize, usize)>, // where the player was when the last move started
+    turn_parity: bool, // flips after every move, blinkers are open while it matches their phase
+    #[serde(skip)]
+    wall_hit: bool, // the player ran into a wall since this was last taken
+    #[serde(skip)]
+    blocked_move: Option<DirectionKey>, // direction the tile refused since this was last taken
+    #[serde(skip)]
+    teleport: Option<Teleport>, // portal jump (from, to) since this was last taken
+    #[serde(skip)]
+    sounds: HashSet<SoundEffect>, // sound effects of what happened since these were last taken
+    shields: u32,   // shields held, each carries the player through one wall
+    collected_shields: HashSet<(usize, usize)>, // shield tiles already picked up
+    control_rotation: u8, // quarter turns clockwise applied to every input, one per rotator landed on
+    bookmark: Option<(usize, usize)>, // position marked by the first use of a bookmark key
+    #[serde(skip)]
     pub animation_state: Option<PlayingAnimationState>,
 }
 
 impl PlayingModel {
-    pub fn new(editing_model: &editing_model::EditingModel) -> Self {
-        let board_size = (
-            editing_model.get_board_size().0 + 2,
-            editing_model.get_board_size().1 + 2,
-        );
+    /// Start playing a board. Fails instead of panicking if the board has no start, or its
+    /// size is out of bounds or doesn't match its tiles, which loading already rules out for
+    /// board files. A board without an end can still be played, it just can't be won
+    pub fn new(editing_model: &editing_model::EditingModel) -> Result<Self, FoamError> {
+        editing_model.check_size()?;
+        let (rows, cols) = editing_model.get_board_size();
+        let start_pos = editing_model
+            .get_start_pos()
+            .ok_or(FoamError::InvalidBoard("no start space".to_string()))?;
+        if start_pos.0 >= rows || start_pos.1 >= cols {
+            return Err(FoamError::InvalidBoard(
+                "start space is off the board".to_string(),
+            ));
+        }
+        let board_size = (rows + 2, cols + 2);
 
         // pad board with layer of empty tiles on outside
         let mut board = vec![vec![TileData::empty(); board_size.1]; board_size.0];
@@ -49,17 +197,82 @@ impl PlayingModel {
             }
         }
 
-        let player_pos = (
-            editing_model.get_start_pos().unwrap().0 + 1, // offset by 1 to account for padding
-            editing_model.get_start_pos().unwrap().1 + 1, // offset by 1 to account for padding
-        );
+        let player_pos = (start_pos.0 + 1, start_pos.1 + 1); // offset by 1 to account for padding
 
-        PlayingModel {
+        Ok(PlayingModel {
             board,
             board_size,
             player_pos,
+            start_pos: player_pos,
+            move_count: 0,
+            move_limit: editing_model.get_move_limit(),
+            run_speed: editing_model.get_run_speed(),
+            background: editing_model.get_background(),
+            gravity: editing_model.get_gravity(),
+            name: editing_model.get_name().to_string(),
+            start_time: Some(Instant::now()),
+            keys_used: 0,
+            record: Vec::new(),
+            record_times: Vec::new(),
+            consumed_clouds: HashSet::new(),
+            covered_tiles: HashMap::new(),
+            inventory: Vec::new(),
+            activated_checkpoints: HashSet::new(),
+            respawn_pos: None,
+            solvable: editing_model
+                .get_end_pos()
+                .is_some_and(|end_pos| editing_model.reachable_tiles().contains(&end_pos)),
+            history: VecDeque::new(),
+            last_move_origin: None,
+            turn_parity: false,
+            wall_hit: false,
+            blocked_move: None,
+            teleport: None,
+            sounds: HashSet::new(),
+            shields: 0,
+            collected_shields: HashSet::new(),
+            control_rotation: 0,
+            bookmark: None,
             animation_state: None,
+        })
+    }
+
+    /// Check a model restored from a save, so a corrupt file can't put the player off the board
+    pub fn check_restored(&self) -> Result<(), FoamError> {
+        let (rows, cols) = self.board_size;
+        if rows > MAX_BOARD_SIDE + 2 || cols > MAX_BOARD_SIDE + 2 {
+            return Err(FoamError::InvalidBoard(format!(
+                "board size {rows}x{cols} is over the limit"
+            )));
+        }
+        if self.board.len() != rows || self.board.iter().any(|row| row.len() != cols) {
+            return Err(FoamError::InvalidBoard(
+                "board size doesn't match its tiles".to_string(),
+            ));
+        }
+        let on_board = |pos: (usize, usize)| pos.0 < rows && pos.1 < cols;
+        if ![
+            Some(self.player_pos),
+            Some(self.start_pos),
+            self.respawn_pos,
+            self.bookmark,
+        ]
+        .into_iter()
+        .flatten()
+        .all(on_board)
+        {
+            return Err(FoamError::InvalidBoard(
+                "player position is off the board".to_string(),
+            ));
         }
+        Ok(())
+    }
+
+    /// Like `new`, but starting with every key in the inventory, for trying out key behaviors
+    pub fn new_sandbox(editing_model: &editing_model::EditingModel) -> Result<Self, FoamError> {
+        let mut model = PlayingModel::new(editing_model)?;
+        model.inventory = ALL_KEYS.to_vec();
+        Ok(model)
     }
 
     pub fn get_board(&self) -> &Vec<Vec<TileData>> {
@@ -70,15 +283,282 @@ impl PlayingModel {
         self.player_pos
     }
 
-    pub fn start_movement_animation(&mut self, movement: PlayerMovementData) {
-        if !self.board[self.player_pos.0][self.player_pos.1]
-            .tile
-            .can_move_in_direction(&movement.direction)
+    /// Where the player is standing, which for a move held up at a wall is the tile it
+    /// started from rather than the one it was heading for
+    pub fn get_stopped_pos(&self) -> (usize, usize) {
+        self.animation_state
+            .as_ref()
+            .filter(|state| state.waiting_on_item)
+            .map_or(self.player_pos, |state| state.old_pos)
+    }
+
+    pub fn get_move_count(&self) -> usize {
+        self.move_count
+    }
+
+    pub fn get_move_limit(&self) -> Option<usize> {
+        self.move_limit
+    }
+
+    pub fn get_background(&self) -> editing_model::BackgroundStyle {
+        self.background
+    }
+
+    pub fn get_run_speed(&self) -> usize {
+        self.run_speed
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Time since play started
+    pub fn get_elapsed(&self) -> Duration {
+        self.start_time
+            .map_or(Duration::ZERO, |start_time| start_time.elapsed())
+    }
+
+    /// Number of keys used up so far, e.g. finish keys spent opening checkpoints
+    pub fn get_keys_used(&self) -> usize {
+        self.keys_used
+    }
+
+    /// Move along one axis by `speed` tiles in the offset's direction, staying on the board
+    fn step_axis(pos: usize, offset: isize, speed: usize, size: usize) -> usize {
+        pos.saturating_add_signed(offset * speed as isize)
+            .min(size - 1)
+    }
+
+    /// Whether the position is in the layer of padding around the board
+    fn is_padding(board_size: (usize, usize), pos: (usize, usize)) -> bool {
+        pos.0 == 0 || pos.1 == 0 || pos.0 == board_size.0 - 1 || pos.1 == board_size.1 - 1
+    }
+
+    pub fn get_inventory(&self) -> &[KeyItem] {
+        &self.inventory
+    }
+
+    pub fn is_consumed_cloud(&self, pos: (usize, usize)) -> bool {
+        self.consumed_clouds.contains(&pos)
+    }
+
+    /// Number of clouds that have not disappeared yet
+    pub fn clouds_remaining(&self) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|tile_data| matches!(tile_data.tile, Tile::Cloud(_)))
+            .count()
+    }
+
+    pub fn clouds_consumed(&self) -> usize {
+        self.consumed_clouds.len()
+    }
+
+    /// Position marked with a bookmark key, waiting for the key's second use
+    pub fn get_bookmark(&self) -> Option<(usize, usize)> {
+        self.bookmark
+    }
+
+    /// Quarter turns clockwise the controls are rotated by
+    pub fn get_control_rotation(&self) -> u8 {
+        self.control_rotation
+    }
+
+    pub fn get_shields(&self) -> u32 {
+        self.shields
+    }
+
+    pub fn is_collected_shield(&self, pos: (usize, usize)) -> bool {
+        self.collected_shields.contains(&pos)
+    }
+
+    /// Whether the end can no longer be reached from where the player stands, using the same
+    /// search as the editor on the current board. Held keys count, and once any checkpoint
+    /// is open every checkpoint is treated as open, so this never reports a false strand
+    fn is_stranded(&self) -> bool {
+        if !self.solvable {
+            return false;
+        }
+
+        // The search knows nothing of shields, so it can't tell if one would get the player out
+        let shields_left = self.board.iter().enumerate().any(|(row_idx, row)| {
+            row.iter().enumerate().any(|(col_idx, tile_data)| {
+                tile_data.tile == Tile::Shield && !self.is_collected_shield((row_idx, col_idx))
+            })
+        });
+        if self.shields > 0 || shields_left {
+            return false;
+        }
+
+        // Blocks are searched as the ground under them, which the player reaches by pushing
+        // them away. That can only let the search reach more, never less
+        let board = self.board[1..self.board_size.0 - 1]
+            .iter()
+            .enumerate()
+            .map(|(row_idx, row)| {
+                row[1..self.board_size.1 - 1]
+                    .iter()
+                    .enumerate()
+                    .map(|(col_idx, tile_data)| match tile_data.tile {
+                        Tile::Block => TileData {
+                            tile: self
+                                .covered_tiles
+                                .get(&(row_idx + 1, col_idx + 1))
+                                .cloned()
+                                .unwrap_or(BLOCK_GROUND),
+                            key: tile_data.key.clone(),
+                        },
+                        _ => tile_data.clone(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut model = editing_model::EditingModel::from_board(board);
+        model.set_gravity(self.gravity);
+        let Some(end_pos) = model.get_end_pos() else {
+            return false;
+        };
+
+        let mut held_keys: HashSet<KeyItem> = self.inventory.iter().cloned().collect();
+        if !self.activated_checkpoints.is_empty() {
+            held_keys.insert(KeyItem::OnGet(KeyOnGet::FinishKey));
+        }
+        let unpadded = |pos: (usize, usize)| (pos.0 - 1, pos.1 - 1);
+
+        // A marked bookmark can still take the player back
+        ![Some(self.player_pos), self.respawn_pos, self.bookmark]
+            .into_iter()
+            .flatten()
+            .filter(|&pos| !Self::is_padding(self.board_size, pos))
+            .any(|pos| {
+                model
+                    .reachable_tiles_from(unpadded(pos), &held_keys)
+                    .contains(&end_pos)
+            })
+    }
+
+    /// Whether the player ran into a wall since the last call
+    pub fn take_wall_hit(&mut self) -> bool {
+        std::mem::take(&mut self.wall_hit)
+    }
+
+    /// Direction of a move the player's tile didn't allow, since the last call
+    pub fn take_blocked_move(&mut self) -> Option<DirectionKey> {
+        self.blocked_move.take()
+    }
+
+    /// Portal the player jumped from and where they landed, since the last call
+    pub fn take_teleport(&mut self) -> Option<Teleport> {
+        self.teleport.take()
+    }
+
+    /// Sound effects to play for what happened since the last call
+    pub fn take_sounds(&mut self) -> HashSet<SoundEffect> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    /// Whether a blinker with this phase can be moved onto this turn
+    pub fn blinker_is_open(&self, phase: bool) -> bool {
+        phase == self.turn_parity
+    }
+
+    /// Number of door keys still lying on the board
+    pub fn door_keys_on_board(&self) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|tile_data| {
+                matches!(
+                    tile_data.key,
+                    KeyItem::OnEquip(KeyOnEquip::OnWall(KeyOnWall::DoorKey(_)))
+                )
+            })
+            .count()
+    }
+
+    pub fn get_record(&self) -> &[PlayerMovementData] {
+        &self.record
+    }
+
+    /// Seconds into play each move of `get_record` started
+    pub fn get_record_times(&self) -> &[f64] {
+        &self.record_times
+    }
+
+    /// Most recent movement input, including ones that couldn't move the player
+    pub fn get_last_movement(&self) -> Option<&PlayerMovementData> {
+        self.record.last()
+    }
+
+    /// (row, column) change in the player's position since the last movement started
+    pub fn get_last_displacement(&self) -> Option<(isize, isize)> {
+        self.last_move_origin.map(|origin| {
+            (
+                self.player_pos.0 as isize - origin.0 as isize,
+                self.player_pos.1 as isize - origin.1 as isize,
+            )
+        })
+    }
+
+    pub fn start_movement_animation(&mut self, mut movement: PlayerMovementData) {
+        self.record.push(movement);
+        self.record_times.push(self.get_elapsed().as_secs_f64());
+        self.last_move_origin = Some(self.player_pos);
+
+        // Rotators turn the controls, the record keeps the keys as pressed
+        for _ in 0..self.control_rotation {
+            movement.direction = movement.direction.rotated_cw();
+        }
+
+        // Standing on an arrow pushes the player along it, whatever was pressed
+        let movement = match self.board[self.player_pos.0][self.player_pos.1].tile {
+            Tile::Arrow(direction) => PlayerMovementData {
+                direction,
+                move_speed: 1,
+                use_tile: false,
+            },
+            _ => movement,
+        };
+
+        // A held bookmark key can be used on any tile, not only where the tile has a use
+        let uses_bookmark = movement.direction.is_none()
+            && movement.use_tile
+            && self.inventory.contains(&KeyItem::OnUse(KeyOnUse::Bookmark));
+        if !uses_bookmark
+            && !self.board[self.player_pos.0][self.player_pos.1]
+                .tile
+                .can_move_in_direction_with_keys(&movement.direction, |key| {
+                    self.inventory.contains(&key)
+                })
         {
+            self.blocked_move = Some(movement.direction);
             self.animation_state = None;
             return;
         }
 
+        if self.history.len() == MAX_UNDO_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(PlayingSnapshot {
+            board: self.board.clone(),
+            player_pos: self.player_pos,
+            move_count: self.move_count,
+            record_len: self.record.len() - 1, // without this move
+            consumed_clouds: self.consumed_clouds.clone(),
+            covered_tiles: self.covered_tiles.clone(),
+            inventory: self.inventory.clone(),
+            activated_checkpoints: self.activated_checkpoints.clone(),
+            respawn_pos: self.respawn_pos,
+            turn_parity: self.turn_parity,
+            keys_used: self.keys_used,
+            shields: self.shields,
+            collected_shields: self.collected_shields.clone(),
+            control_rotation: self.control_rotation,
+            bookmark: self.bookmark,
+        });
+
+        self.move_count += 1;
         self.animation_state = Some(PlayingAnimationState {
             current_tile: self.board[self.player_pos.0][self.player_pos.1]
                 .clone()
@@ -89,13 +569,103 @@ impl PlayingModel {
             use_tile: movement.use_tile,
             finished: false,
             waiting_on_item: false,
+            momentum: false,
+            reversals: 0,
+            arrow_pushes: 0,
         });
     }
 
+    /// Go back to the state before the last move. Returns false if there is nothing to undo
+    /// or a move is still in progress
+    pub fn undo_move(&mut self) -> bool {
+        if self.animation_state.is_some() {
+            return false;
+        }
+        let Some(snapshot) = self.history.pop_back() else {
+            return false;
+        };
+
+        self.board = snapshot.board;
+        self.player_pos = snapshot.player_pos;
+        self.move_count = snapshot.move_count;
+        self.record.truncate(snapshot.record_len);
+        self.record_times.truncate(snapshot.record_len);
+        self.consumed_clouds = snapshot.consumed_clouds;
+        self.covered_tiles = snapshot.covered_tiles;
+        self.inventory = snapshot.inventory;
+        self.activated_checkpoints = snapshot.activated_checkpoints;
+        self.respawn_pos = snapshot.respawn_pos;
+        self.turn_parity = snapshot.turn_parity;
+        self.keys_used = snapshot.keys_used;
+        self.shields = snapshot.shields;
+        self.collected_shields = snapshot.collected_shields;
+        self.control_rotation = snapshot.control_rotation;
+        self.bookmark = snapshot.bookmark;
+        self.last_move_origin = None;
+        true
+    }
+
+    /// Play a whole move without animating, returning how it ended
+    pub fn play_move(&mut self, movement: PlayerMovementData) -> MovementPopupData {
+        self.start_movement_animation(movement);
+
+        let mut result = MovementPopupData::None;
+        while self.animation_state.is_some() && matches!(result, MovementPopupData::None) {
+            result = self.step_animation(&KeyItem::None);
+        }
+        result
+    }
+
+    /// Play a whole move like `play_move`, adding every tile the player lands on to `landed`
+    fn play_move_landing(
+        &mut self,
+        movement: PlayerMovementData,
+        landed: &mut HashSet<(usize, usize)>,
+    ) -> MovementPopupData {
+        self.start_movement_animation(movement);
+
+        let mut result = MovementPopupData::None;
+        while self.animation_state.is_some() && matches!(result, MovementPopupData::None) {
+            result = self.step_animation(&KeyItem::None);
+            landed.insert(self.player_pos);
+        }
+        result
+    }
+
+    /// Play a sequence of moves headlessly, stopping at the first one that ends the game or
+    /// waits on the player (e.g. hitting a wall), and return how the last played move ended
+    pub fn simulate(&mut self, moves: &[PlayerMovementData]) -> MovementPopupData {
+        let mut result = MovementPopupData::None;
+        for &movement in moves {
+            result = self.play_move(movement);
+            if !matches!(result, MovementPopupData::None) {
+                break;
+            }
+        }
+        result
+    }
+
     pub fn step_animation(&mut self, _keys: &KeyItem) -> MovementPopupData {
         if let Some(state) = &mut self.animation_state {
             if state.finished {
                 self.animation_state = None;
+                self.turn_parity = !self.turn_parity;
+
+                if self.apply_gravity() {
+                    return MovementPopupData::Won;
+                }
+
+                // Out of moves without reaching the end
+                if self
+                    .move_limit
+                    .is_some_and(|limit| self.move_count >= limit)
+                    && self.board[self.player_pos.0][self.player_pos.1].tile != Tile::EndSpace
+                {
+                    return MovementPopupData::Lost;
+                }
+                if self.is_stranded() {
+                    return MovementPopupData::Stranded;
+                }
                 return MovementPopupData::None;
             }
 
@@ -107,62 +677,116 @@ impl PlayingModel {
                 state.old_pos = self.player_pos;
 
                 match state.direction {
-                    DirectionKey::Up => {
-                        self.player_pos.0 = self.player_pos.0.saturating_sub(state.movement_speed)
-                    }
-                    DirectionKey::Down => {
-                        self.player_pos.0 =
-                            (self.player_pos.0 + state.movement_speed).min(self.board_size.0 - 1);
-                    }
-                    DirectionKey::Left => {
-                        self.player_pos.1 = self.player_pos.1.saturating_sub(state.movement_speed)
-                    }
-                    DirectionKey::Right => {
-                        self.player_pos.1 =
-                            (self.player_pos.1 + state.movement_speed).min(self.board_size.1 - 1);
-                    }
-                    DirectionKey::UpLeft => {
-                        self.player_pos.0 = self.player_pos.0.saturating_sub(state.movement_speed);
-                        self.player_pos.1 = self.player_pos.1.saturating_sub(state.movement_speed);
-                    }
-                    DirectionKey::UpRight => {
-                        self.player_pos.0 = self.player_pos.0.saturating_sub(state.movement_speed);
-                        self.player_pos.1 =
-                            (self.player_pos.1 + state.movement_speed).min(self.board_size.1 - 1);
-                    }
-                    DirectionKey::DownLeft => {
-                        self.player_pos.0 =
-                            (self.player_pos.0 + state.movement_speed).min(self.board_size.0 - 1);
-                        self.player_pos.1 = self.player_pos.1.saturating_sub(state.movement_speed);
-                    }
-                    DirectionKey::DownRight => {
-                        self.player_pos.0 =
-                            (self.player_pos.0 + state.movement_speed).min(self.board_size.0 - 1);
-                        self.player_pos.1 =
-                            (self.player_pos.1 + state.movement_speed).min(self.board_size.1 - 1);
-                    }
                     DirectionKey::None => {
-                        if let Tile::Portal(_, pos) = state.current_tile {
-                            if state.use_tile {
-                                self.player_pos.0 = pos.0 + 1; // offset by 1 to account for padding
-                                self.player_pos.1 = pos.1 + 1; // offset by 1 to account for padding
+                        if let Tile::Portal(_, pos) = state.current_tile
+                            && state.use_tile
+                        {
+                            self.player_pos.0 = pos.0 + 1; // offset by 1 to account for padding
+                            self.player_pos.1 = pos.1 + 1; // offset by 1 to account for padding
+                            self.teleport = Some((state.old_pos, self.player_pos));
+                            self.sounds.insert(SoundEffect::Whoosh);
+                        } else if state.use_tile
+                            && let Some(index) = self
+                                .inventory
+                                .iter()
+                                .position(|key| *key == KeyItem::OnUse(KeyOnUse::Bookmark))
+                        {
+                            // The first use marks this position, the second goes back to it
+                            // and uses up the key
+                            match self.bookmark.take() {
+                                None => self.bookmark = Some(self.player_pos),
+                                Some(bookmark) => {
+                                    self.inventory.remove(index);
+                                    self.keys_used += 1;
+                                    self.player_pos = bookmark;
+                                    self.teleport = Some((state.old_pos, bookmark));
+                                    self.sounds.insert(SoundEffect::Whoosh);
+                                }
                             }
                         }
                         state.finished = true;
                         return MovementPopupData::None;
                     }
+                    // Every direction steps each axis the same way, clamped to the board
+                    direction => {
+                        let (row_offset, col_offset) = direction.offset();
+                        self.player_pos = (
+                            Self::step_axis(
+                                self.player_pos.0,
+                                row_offset,
+                                state.movement_speed,
+                                self.board_size.0,
+                            ),
+                            Self::step_axis(
+                                self.player_pos.1,
+                                col_offset,
+                                state.movement_speed,
+                                self.board_size.1,
+                            ),
+                        );
+                    }
                 }
             }
 
+            // The player stops at the first block in their way, which is pushed once nothing
+            // else has stopped them before reaching it
+            let (row_offset, col_offset) = state.direction.offset();
+            let pushed_block = (1..=state.movement_speed)
+                .map(|step| {
+                    (
+                        Self::step_axis(state.old_pos.0, row_offset, step, self.board_size.0),
+                        Self::step_axis(state.old_pos.1, col_offset, step, self.board_size.1),
+                    )
+                })
+                .find(|&(row, col)| {
+                    self.board[row][col].tile == Tile::Block && (row, col) != state.old_pos
+                });
+            if let Some(block_pos) = pushed_block {
+                self.player_pos = block_pos;
+            }
+
             // Check if there is a wall in between the old position and the new position
             let start_row = state.old_pos.0.min(self.player_pos.0);
             let end_row = state.old_pos.0.max(self.player_pos.0);
             let start_col = state.old_pos.1.min(self.player_pos.1);
             let end_col = state.old_pos.1.max(self.player_pos.1);
 
+            // Tiles the player moves over, up to and including where they land
+            let steps = (end_row - start_row).max(end_col - start_col);
+            let path: Vec<(usize, usize)> = (1..=steps)
+                .map(|step| {
+                    (
+                        Self::step_axis(state.old_pos.0, row_offset, step, self.board_size.0),
+                        Self::step_axis(state.old_pos.1, col_offset, step, self.board_size.1),
+                    )
+                })
+                .filter(|&pos| pos != state.old_pos)
+                .collect();
+
+            // Shields carry the player through the walls on their path, one each, and are only
+            // used up once nothing else has stopped the move
+            let path_walls = path
+                .iter()
+                .filter(|&&(row, col)| self.board[row][col].tile == Tile::Wall)
+                .count() as u32;
+            let shielded = self.shields > 0 && self.shields >= path_walls;
+
             for row in start_row..=end_row {
                 for col in start_col..=end_col {
-                    if self.board[row][col].tile == Tile::Wall {
+                    if self.board[row][col].tile == Tile::Wall && (row, col) != state.old_pos {
+                        if shielded {
+                            continue;
+                        }
+
+                        // A slide, or a move turned around by a reverse tile, stops in front of the wall
+                        self.wall_hit = true;
+                        self.sounds.insert(SoundEffect::Thud);
+                        if state.momentum || state.reversals > 0 {
+                            self.player_pos = state.old_pos;
+                            state.finished = true;
+                            return MovementPopupData::None;
+                        }
+
                         // TODO: update
                         //     if state.waiting_on_item {
                         //         // If the user is waiting for a KeyItem and the KeyItem is used, allow movement
@@ -194,15 +818,105 @@ impl PlayingModel {
                 }
             }
 
+            // A gap stops the player in front of it unless they're running, and can't be landed in
+            let blocked_by_gap = (start_row..=end_row).any(|row| {
+                (start_col..=end_col).any(|col| {
+                    self.board[row][col].tile == Tile::Gap
+                        && (row, col) != state.old_pos
+                        && (state.movement_speed < 2 || (row, col) == self.player_pos)
+                })
+            });
+            if blocked_by_gap {
+                self.player_pos = state.old_pos;
+                state.finished = true;
+                return MovementPopupData::None;
+            }
+
+            // A speed gate stops the player in front of it unless they're moving at its speed
+            let blocked_by_speed_gate = (start_row..=end_row).any(|row| {
+                (start_col..=end_col).any(|col| {
+                    matches!(self.board[row][col].tile, Tile::SpeedGate(speed) if speed != state.movement_speed)
+                        && (row, col) != state.old_pos
+                })
+            });
+            if blocked_by_speed_gate {
+                self.player_pos = state.old_pos;
+                state.finished = true;
+                return MovementPopupData::None;
+            }
+
             // No movement occurred
             if self.player_pos == state.old_pos {
                 state.finished = true;
                 return MovementPopupData::None;
             }
 
+            // A block that can't be pushed blocks like a wall
+            if let Some(block_pos) = pushed_block
+                && !Self::push_block(
+                    &mut self.board,
+                    &mut self.covered_tiles,
+                    block_pos,
+                    state.direction,
+                )
+            {
+                self.wall_hit = true;
+                self.sounds.insert(SoundEffect::Thud);
+                self.player_pos = state.old_pos;
+                state.finished = true;
+                return MovementPopupData::None;
+            }
+
+            // A checkpoint can only be entered by using up a finish key, otherwise it blocks like a wall
+            let landing = self.player_pos;
+            if self.board[landing.0][landing.1].tile == Tile::Checkpoint
+                && !self.activated_checkpoints.contains(&landing)
+            {
+                if let Some(index) = self
+                    .inventory
+                    .iter()
+                    .position(|key| *key == KeyItem::OnGet(KeyOnGet::FinishKey))
+                {
+                    self.inventory.remove(index);
+                    self.keys_used += 1;
+                    self.activated_checkpoints.insert(landing);
+                } else {
+                    self.player_pos = state.old_pos;
+                    state.finished = true;
+                    return MovementPopupData::None;
+                }
+            }
+
+            // A switch blocks until it has been stepped on enough times. A run counts a step on
+            // the first closed switch it meets and stops in front of it
+            if let Some(&(row, col)) = path
+                .iter()
+                .find(|&&(row, col)| matches!(self.board[row][col].tile, Tile::Switch(1..)))
+                && let Tile::Switch(count) = &mut self.board[row][col].tile
+            {
+                *count -= 1;
+                self.player_pos = state.old_pos;
+                state.finished = true;
+                return MovementPopupData::None;
+            }
+
+            // A closed blinker blocks like a wall
+            if let Tile::Blinker(phase) = self.board[landing.0][landing.1].tile
+                && phase != self.turn_parity
+            {
+                self.player_pos = state.old_pos;
+                state.finished = true;
+                return MovementPopupData::None;
+            }
+
+            if shielded {
+                self.shields -= path_walls;
+            }
+
             // If the current tile is a cloud, remove it
             if matches!(state.current_tile, Tile::Cloud(_)) {
                 self.board[state.old_pos.0][state.old_pos.1].tile = Tile::Empty;
+                self.consumed_clouds.insert(state.old_pos);
             }
 
             // Apply movement
@@ -211,6 +925,15 @@ impl PlayingModel {
                 .clone();
             state.old_pos = self.player_pos;
 
+            // Pick up any key on the new tile
+            let key = std::mem::replace(
+                &mut self.board[self.player_pos.0][self.player_pos.1].key,
+                KeyItem::None,
+            );
+            if key != KeyItem::None {
+                self.inventory.push(key);
+            }
+
             match state.current_tile {
                 Tile::EndSpace => {
                     state.finished = true;
@@ -219,15 +942,78 @@ impl PlayingModel {
                 Tile::Bounce(amount) => {
                     state.movement_speed =
                         state.movement_speed.checked_add_signed(amount).unwrap_or(0);
+                    state.momentum = false;
+                    self.sounds.insert(SoundEffect::Boing);
+                }
+                Tile::SpeedPad(speed) => {
+                    state.movement_speed = speed;
+                    state.momentum = false;
                 }
                 Tile::Ice => {
+                    if !state.momentum {
+                        self.sounds.insert(SoundEffect::Slide); // Once per slide, not per tile
+                    }
                     state.movement_speed = 1;
+                    state.momentum = true;
+                }
+                Tile::Empty
+                    if state.momentum && !Self::is_padding(self.board_size, self.player_pos) =>
+                {
+                    // Keep sliding across empty ground
                 }
                 Tile::Empty => {
+                    // Respawn at the last checkpoint instead of losing
+                    if let Some(respawn_pos) = self.respawn_pos {
+                        self.player_pos = respawn_pos;
+                        state.momentum = false;
+                        state.finished = true;
+                        return MovementPopupData::None;
+                    }
                     return MovementPopupData::Lost; // End game
                 }
+                Tile::Arrow(direction) if state.arrow_pushes < MAX_ARROW_PUSHES_PER_MOVE => {
+                    state.direction = direction;
+                    state.movement_speed = 1;
+                    state.momentum = false;
+                    state.arrow_pushes += 1;
+                }
+                Tile::ResetTrap(resets_keys) => {
+                    if resets_keys {
+                        self.inventory.clear();
+                    }
+                    self.player_pos = self.start_pos;
+                    state.momentum = false;
+                    state.finished = true;
+                    return MovementPopupData::None;
+                }
+                Tile::Reverse if state.reversals < MAX_REVERSALS_PER_MOVE => {
+                    state.direction = state.direction.reversed();
+                    state.reversals += 1;
+                }
+                Tile::Checkpoint => {
+                    self.respawn_pos = Some(self.player_pos);
+                    state.movement_speed = 0;
+                    state.momentum = false;
+                }
+                Tile::Wall => {
+                    // Shielded through onto a wall, keep going to the tile past it
+                    state.movement_speed = 1;
+                }
+                Tile::Shield => {
+                    if self.collected_shields.insert(self.player_pos) {
+                        self.shields += 1;
+                    }
+                    state.movement_speed = 0;
+                    state.momentum = false;
+                }
+                Tile::Rotator => {
+                    self.control_rotation = (self.control_rotation + 1) % 4;
+                    state.movement_speed = 0;
+                    state.momentum = false;
+                }
                 _ => {
                     state.movement_speed = 0;
+                    state.momentum = false;
                 }
             }
 
@@ -238,4 +1024,990 @@ impl PlayingModel {
 
         MovementPopupData::None
     }
+
+    /// Push a block one tile in a direction, onto an empty tile or ice on the board. A block
+    /// pushed onto ice slides on until something stops it or it drops into an empty tile.
+    /// Returns false, leaving the block where it is, if there's nothing it can move onto
+    fn push_block(
+        board: &mut [Vec<TileData>],
+        covered_tiles: &mut HashMap<(usize, usize), Tile>,
+        from: (usize, usize),
+        direction: DirectionKey,
+    ) -> bool {
+        let board_size = (board.len(), board[0].len());
+        let (row_offset, col_offset) = direction.offset();
+        // Blocks never reach the padding, so the next tile is always on the padded board
+        let next = |pos: (usize, usize)| {
+            (
+                pos.0.wrapping_add_signed(row_offset),
+                pos.1.wrapping_add_signed(col_offset),
+            )
+        };
+        let can_hold = |board: &[Vec<TileData>], pos: (usize, usize)| {
+            !Self::is_padding(board_size, pos)
+                && matches!(board[pos.0][pos.1].tile, Tile::Empty | Tile::Ice)
+        };
+
+        let mut to = next(from);
+        if !can_hold(board, to) {
+            return false;
+        }
+        while board[to.0][to.1].tile == Tile::Ice && can_hold(board, next(to)) {
+            to = next(to);
+        }
+
+        board[from.0][from.1].tile = covered_tiles.remove(&from).unwrap_or(BLOCK_GROUND);
+        let covered = std::mem::replace(&mut board[to.0][to.1].tile, Tile::Block);
+        covered_tiles.insert(to, covered);
+        true
+    }
+
+    /// Pull the player one tile in the gravity direction, if the board has gravity and the
+    /// tile is solid ground they could stand on. Only keys on the tile take effect, not the
+    /// tile itself, except that being pulled onto the end wins. Returns whether it won
+    fn apply_gravity(&mut self) -> bool {
+        let Some(gravity) = self.gravity else {
+            return false;
+        };
+        let (row_offset, col_offset) = gravity.offset();
+        let target = (
+            Self::step_axis(self.player_pos.0, row_offset, 1, self.board_size.0),
+            Self::step_axis(self.player_pos.1, col_offset, 1, self.board_size.1),
+        );
+        let pulled = match self.board[target.0][target.1].tile {
+            Tile::Empty | Tile::Wall | Tile::Gap | Tile::Door | Tile::Block => false,
+            Tile::Checkpoint => self.activated_checkpoints.contains(&target),
+            Tile::Switch(count) => count == 0,
+            Tile::Blinker(phase) => self.blinker_is_open(phase),
+            Tile::SpeedGate(speed) => speed == 1,
+            _ => true,
+        };
+        if !pulled || target == self.player_pos {
+            return false;
+        }
+
+        self.player_pos = target;
+        let key = std::mem::replace(&mut self.board[target.0][target.1].key, KeyItem::None);
+        if key != KeyItem::None {
+            self.inventory.push(key);
+        }
+        self.board[target.0][target.1].tile == Tile::EndSpace
+    }
+
+    /// Everything that decides how the rest of the game can play out, to spot repeated states
+    fn search_state(&self) -> SearchState {
+        let sorted = |positions: &HashSet<(usize, usize)>| {
+            let mut positions: Vec<_> = positions.iter().copied().collect();
+            positions.sort_unstable();
+            positions
+        };
+        SearchState {
+            board: self.board.clone(),
+            player_pos: self.player_pos,
+            consumed_clouds: sorted(&self.consumed_clouds),
+            covered_tiles: {
+                let mut covered_tiles: Vec<_> = self.covered_tiles.clone().into_iter().collect();
+                covered_tiles.sort_unstable_by_key(|&(pos, _)| pos);
+                covered_tiles
+            },
+            inventory: self.inventory.clone(),
+            activated_checkpoints: sorted(&self.activated_checkpoints),
+            respawn_pos: self.respawn_pos,
+            turn_parity: self.turn_parity,
+            shields: self.shields,
+            collected_shields: sorted(&self.collected_shields),
+            control_rotation: self.control_rotation,
+            bookmark: self.bookmark,
+        }
+    }
+
+    /// Every input the player can give in a turn: each direction walked or run, and using
+    /// the current tile (which waits a turn when there is nothing to use)
+    fn search_moves(&self) -> Vec<PlayerMovementData> {
+        let mut speeds = vec![1];
+        if self.run_speed != 1 {
+            speeds.push(self.run_speed);
+        }
+
+        let mut moves: Vec<PlayerMovementData> = ALL_DIRECTIONS
+            .iter()
+            .flat_map(|&direction| {
+                speeds.iter().map(move |&move_speed| PlayerMovementData {
+                    direction,
+                    move_speed,
+                    use_tile: false,
+                })
+            })
+            .collect();
+        moves.push(PlayerMovementData {
+            direction: DirectionKey::None,
+            move_speed: 1,
+            use_tile: true,
+        });
+        moves
+    }
+
+    /// Fewest moves that win from the current state, found by a breadth first search over
+    /// whole moves played headlessly, with how much searching that took and how many keys
+    /// the win picks up. Moves that stop on a wall prompt are treated as dead ends. Returns
+    /// None if the end can't be reached or more than `max_states` distinct states would need
+    /// to be explored
+    pub fn solve(&self, max_states: usize) -> Option<SolverStats> {
+        match self.search_win(max_states) {
+            SearchResult::Won(stats) => Some(stats),
+            SearchResult::NoWin | SearchResult::TooManyStates => None,
+        }
+    }
+
+    /// Whether the end can still be reached from here, remembering answers in `cache` by
+    /// game state. Gives the benefit of the doubt when finding out would take exploring more
+    /// than `max_states` states
+    pub fn is_winnable(&self, max_states: usize, cache: &mut HashMap<u64, bool>) -> bool {
+        let mut hasher = DefaultHasher::new();
+        self.search_state().hash(&mut hasher);
+        *cache
+            .entry(hasher.finish())
+            .or_insert_with(|| !matches!(self.search_win(max_states), SearchResult::NoWin))
+    }
+
+    /// Whether the end can still be reached after playing a move, see `is_winnable`. A move
+    /// that wins keeps it in reach, one that loses or strands the player doesn't
+    pub fn move_keeps_win(
+        &self,
+        movement: PlayerMovementData,
+        max_states: usize,
+        cache: &mut HashMap<u64, bool>,
+    ) -> bool {
+        let mut next = self.clone();
+        next.animation_state = None;
+        match next.play_move(movement) {
+            MovementPopupData::Won | MovementPopupData::Wall => true,
+            MovementPopupData::Lost | MovementPopupData::Stranded => false,
+            MovementPopupData::None => next.is_winnable(max_states, cache),
+        }
+    }
+
+    /// Breadth first search for the fewest moves that win, see `solve`
+    fn search_win(&self, max_states: usize) -> SearchResult {
+        let keys_on_board = |model: &PlayingModel| {
+            model
+                .board
+                .iter()
+                .flatten()
+                .filter(|tile_data| tile_data.key != KeyItem::None)
+                .count()
+        };
+        let mut root = self.clone();
+        root.history.clear();
+        root.record.clear();
+        root.record_times.clear();
+        root.animation_state = None;
+        root.solvable = false; // Stranding only prunes the search, and the check is costly
+
+        let mut visited = HashSet::from([root.search_state()]);
+        let mut queue = VecDeque::from([(root, 0)]);
+
+        while let Some((model, depth)) = queue.pop_front() {
+            for movement in model.search_moves() {
+                let mut next = model.clone();
+                match next.play_move(movement) {
+                    MovementPopupData::Won => {
+                        return SearchResult::Won(SolverStats {
+                            moves: depth + 1,
+                            states: visited.len(),
+                            keys_picked_up: keys_on_board(self)
+                                .saturating_sub(keys_on_board(&next)),
+                        });
+                    }
+                    MovementPopupData::None => {}
+                    _ => continue,
+                }
+
+                // Undo history and the replay record aren't part of the state
+                next.history.clear();
+                next.record.clear();
+                next.record_times.clear();
+                if visited.insert(next.search_state()) {
+                    if visited.len() > max_states {
+                        return SearchResult::TooManyStates;
+                    }
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        SearchResult::NoWin
+    }
+
+    /// Tiles the player lands on in every one of the fewest-move wins, including where they
+    /// start and the end, searched like `solve`. Each state keeps the tiles common to every
+    /// shortest way of reaching it, narrowed down whenever another one is found, so equally
+    /// short wins that share no tile but the start and end are all taken into account.
+    /// None whenever `solve` would be
+    pub fn critical_tiles(&self, max_states: usize) -> Option<HashSet<(usize, usize)>> {
+        let mut root = self.clone();
+        root.history.clear();
+        root.record.clear();
+        root.record_times.clear();
+        root.animation_state = None;
+        root.solvable = false;
+
+        let root_tiles = HashSet::from([root.player_pos]);
+        let mut visited = HashMap::from([(root.search_state(), (0, root_tiles))]);
+        let mut queue = VecDeque::from([(root, 0)]);
+        let mut wins: Option<(usize, HashSet<(usize, usize)>)> = None; // Depth and common tiles
+
+        while let Some((model, depth)) = queue.pop_front() {
+            // Every shortest win has been found once the search is past their depth
+            if wins
+                .as_ref()
+                .is_some_and(|(win_depth, _)| depth >= *win_depth)
+            {
+                break;
+            }
+            let common = visited[&model.search_state()].1.clone();
+
+            for movement in model.search_moves() {
+                let mut next = model.clone();
+                let mut landed = common.clone();
+                match next.play_move_landing(movement, &mut landed) {
+                    MovementPopupData::Won => {
+                        wins = Some(match wins.take() {
+                            None => (depth + 1, landed),
+                            Some((win_depth, tiles)) => (win_depth, &tiles & &landed),
+                        });
+                        continue;
+                    }
+                    MovementPopupData::None => {}
+                    _ => continue,
+                }
+
+                next.history.clear();
+                next.record.clear();
+                next.record_times.clear();
+                let state = next.search_state();
+                match visited.get_mut(&state) {
+                    // Another shortest way into a state of the next depth
+                    Some((state_depth, tiles)) if *state_depth == depth + 1 => {
+                        tiles.retain(|tile| landed.contains(tile));
+                    }
+                    Some(_) => {}
+                    None => {
+                        visited.insert(state, (depth + 1, landed));
+                        if visited.len() > max_states {
+                            return None;
+                        }
+                        queue.push_back((next, depth + 1));
+                    }
+                }
+            }
+        }
+
+        wins.map(|(_, tiles)| tiles)
+    }
+}
+
+/// What solving a board found out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverStats {
+    pub moves: usize,          // fewest moves that win
+    pub states: usize,         // distinct states explored before finding the win
+    pub keys_picked_up: usize, // keys the winning moves take off the board
+}
+
+impl SolverStats {
+    /// Estimated difficulty, the same for the same stats. Each part rewards something that
+    /// makes a level harder to figure out:
+    /// - 10 points per move of the shortest win, longer plans are harder to find
+    /// - 15 points per key the win picks up, each one is a detour to plan around
+    /// - 5 points per doubling of the states explored, a measure of how much the level
+    ///   branches, and so how many wrong turns it offers
+    pub fn difficulty_score(&self) -> u32 {
+        let moves = self.moves as u32 * 10;
+        let keys = self.keys_picked_up as u32 * 15;
+        let branching = self.states.max(1).ilog2() * 5;
+        moves + keys + branching
+    }
+}
+
+/// How a search for a win ended
+enum SearchResult {
+    Won(SolverStats),
+    NoWin,         // every reachable state was explored without winning
+    TooManyStates, // gave up before finding out
+}
+
+/// State of a game in progress, without anything that only records how it got there
+#[derive(PartialEq, Eq, Hash)]
+struct SearchState {
+    board: Vec<Vec<TileData>>,
+    player_pos: (usize, usize),
+    consumed_clouds: Vec<(usize, usize)>,
+    covered_tiles: Vec<((usize, usize), Tile)>,
+    inventory: Vec<KeyItem>,
+    activated_checkpoints: Vec<(usize, usize)>,
+    respawn_pos: Option<(usize, usize)>,
+    turn_parity: bool,
+    shields: u32,
+    collected_shields: Vec<(usize, usize)>,
+    control_rotation: u8,
+    bookmark: Option<(usize, usize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editing_model::EditingModel;
+
+    fn play_move(model: &mut PlayingModel, direction: DirectionKey) -> MovementPopupData {
+        model.simulate(&[PlayerMovementData {
+            direction,
+            move_speed: 1,
+            use_tile: false,
+        }])
+    }
+
+    fn playing_model(ascii: &str) -> PlayingModel {
+        PlayingModel::new(&EditingModel::from_ascii(ascii).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn unplayable_boards_are_errors() {
+        let startless = EditingModel::from_ascii("CCE\n").unwrap();
+        assert!(matches!(
+            PlayingModel::new(&startless),
+            Err(FoamError::InvalidBoard(_))
+        ));
+
+        // A board size far bigger than its tiles, as a corrupt file could have, doesn't load
+        assert!(matches!(
+            EditingModel::from_json(
+                r#"{"board":[[{"tile":"StartSpace","key":"None"}]],"board_size":[1000000,1000000],"start_pos":[0,0],"end_pos":null,"move_limit":null}"#,
+            ),
+            Err(FoamError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn diagonal_moves_are_symmetric() {
+        let board = "XXXXXXX\nXXXXXXX\nXXXXXXX\nSXXXXXX\nXXXXXXX\nXXXXXXX\nXXXXXXX\n";
+        for speed in [1, 2] {
+            let mut displacements = Vec::new();
+            for direction in ALL_DIRECTIONS
+                .iter()
+                .filter(|direction| direction.is_diagonal())
+            {
+                let mut model = playing_model(board);
+                play_move(&mut model, DirectionKey::Right);
+                model.simulate(&[PlayerMovementData {
+                    direction: *direction,
+                    move_speed: speed,
+                    use_tile: false,
+                }]);
+                displacements.push(model.get_last_displacement().unwrap());
+            }
+
+            let speed = speed as isize;
+            assert_eq!(
+                displacements,
+                [
+                    (-speed, speed),
+                    (speed, speed),
+                    (speed, -speed),
+                    (-speed, -speed)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn portal_jump_is_reported() {
+        let mut editing_model =
+            EditingModel::from_ascii("SPCPE\n\n0,1 portal=Q\n0,3 portal=Q\n").unwrap();
+        assert!(editing_model.board_is_playable());
+        let mut model = PlayingModel::new(&editing_model).unwrap();
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.take_teleport(), None);
+        model.simulate(&[PlayerMovementData {
+            direction: DirectionKey::None,
+            move_speed: 1,
+            use_tile: true,
+        }]);
+        assert_eq!(model.get_player_pos(), (1, 4)); // offset by 1 for padding
+        assert_eq!(model.take_teleport(), Some(((1, 2), (1, 4))));
+        assert_eq!(model.take_teleport(), None);
+    }
+
+    #[test]
+    fn cardinal_key_frees_one_way_tile() {
+        let board = "SCCE\n\n0,1 dirs=U\n";
+        let mut model = playing_model(board);
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2)); // offset by 1 for padding
+        assert_eq!(model.take_blocked_move(), Some(DirectionKey::Right));
+
+        let mut model = playing_model(&format!("{board}0,1 key=cardinal\n"));
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 3));
+        assert_eq!(model.take_blocked_move(), None);
+    }
+
+    #[test]
+    fn sandbox_starts_with_every_key() {
+        let editing_model = EditingModel::from_ascii("S!E\n").unwrap();
+        let mut model = PlayingModel::new_sandbox(&editing_model).unwrap();
+        assert_eq!(model.get_inventory(), ALL_KEYS);
+
+        // Keys are used up as in normal play
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2)); // offset by 1 for padding
+        assert_eq!(model.get_inventory().len(), ALL_KEYS.len() - 1);
+        assert_eq!(model.get_keys_used(), 1);
+    }
+
+    #[test]
+    fn gravity_pulls_after_each_move() {
+        let mut model = playing_model("SCC\nCCE\n\ngravity=D\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (2, 2)); // offset by 1 for padding
+
+        // Pulled onto the end wins
+        let mut model = playing_model("SC\n.E\n\ngravity=D\n");
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::Won
+        ));
+
+        // Walls and empty space hold the player up
+        let mut model = playing_model("SC\n.#\n\ngravity=D\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2));
+    }
+
+    #[test]
+    fn reset_trap_returns_to_start() {
+        let mut model = playing_model("SCTC\n\n0,1 key=finish\n0,2 reset_keys=0\n");
+        play_move(&mut model, DirectionKey::Right);
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 1)); // offset by 1 for padding
+        assert_eq!(model.get_inventory().len(), 1);
+
+        let mut model = playing_model("SCTC\n\n0,1 key=finish\n0,2 reset_keys=1\n");
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 1));
+        assert!(model.get_inventory().is_empty());
+    }
+
+    #[test]
+    fn solver_gives_up_past_state_limit() {
+        let model = playing_model("SCCCCE\n");
+        assert_eq!(model.solve(100).map(|stats| stats.moves), Some(3));
+        assert_eq!(model.solve(1), None);
+    }
+
+    #[test]
+    fn moves_that_lose_the_way_to_the_end_are_spotted() {
+        // Stepping off the cloud uses it up, leaving no way back to the end
+        let mut model = playing_model("SCE\nO..\nC..\n\nrun_speed=5\n");
+        play_move(&mut model, DirectionKey::Down);
+        let movement = |direction| PlayerMovementData {
+            direction,
+            move_speed: 1,
+            use_tile: false,
+        };
+
+        let mut cache = HashMap::new();
+        assert!(model.is_winnable(1000, &mut cache));
+        assert!(model.move_keeps_win(movement(DirectionKey::Up), 1000, &mut cache));
+        assert!(!model.move_keeps_win(movement(DirectionKey::Down), 1000, &mut cache));
+        assert!(!model.move_keeps_win(movement(DirectionKey::Left), 1000, &mut cache)); // off the board
+        assert!(cache.len() >= 2);
+
+        // Giving up on the search counts as winnable
+        assert!(playing_model("SCE\n").is_winnable(0, &mut HashMap::new()));
+    }
+
+    #[test]
+    fn solver_counts_keys_picked_up() {
+        let model = playing_model("SCCE\n\n0,1 key=finish\n0,2 key=wall\n");
+        let stats = model.solve(100).unwrap();
+        assert_eq!(stats.moves, 2);
+        assert_eq!(stats.keys_picked_up, 1); // the run skips the wall key
+    }
+
+    #[test]
+    fn ice_corridor_slides_to_wall() {
+        let mut model = playing_model("S~~~~~#E\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 6)); // offset by 1 for padding
+        assert!(model.take_wall_hit());
+        assert!(!model.take_wall_hit());
+
+        // One slide sound for the whole slide, then the thud
+        let sounds = model.take_sounds();
+        assert_eq!(
+            sounds,
+            HashSet::from([SoundEffect::Slide, SoundEffect::Thud])
+        );
+        assert!(model.take_sounds().is_empty());
+    }
+
+    #[test]
+    fn ice_slides_across_empty_tiles() {
+        let mut model = playing_model("S~..~.#E\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 6));
+    }
+
+    #[test]
+    fn ice_slide_stops_on_stopping_tile() {
+        let mut model = playing_model("S~.C..#E\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 4));
+    }
+
+    #[test]
+    fn ice_slide_off_board_loses() {
+        let mut model = playing_model("E.S~..\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::Lost));
+    }
+
+    #[test]
+    fn bounce_launches_extra_tiles() {
+        let mut model = playing_model("SB...C.#E\n\n0,1 bounce=3\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 6)); // 1 tile onto the bounce, then 1 + 3
+        assert_eq!(model.get_last_displacement(), Some((0, 5)));
+    }
+
+    #[test]
+    fn speed_pad_sets_exact_speed() {
+        let mut model = playing_model("SV...C#E\n\n0,1 speed=4\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 6)); // 1 tile onto the pad, then exactly 4
+    }
+
+    #[test]
+    fn switch_opens_after_enough_steps() {
+        let mut model = playing_model("SN.E\n\n0,1 count=2\n");
+        for remaining in [1, 0] {
+            play_move(&mut model, DirectionKey::Right);
+            assert_eq!(model.get_player_pos(), (1, 1));
+            assert_eq!(model.get_board()[1][2].tile, Tile::Switch(remaining));
+        }
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2));
+
+        // A run stops in front of the first closed switch on its way, counting a step on it
+        let mut model = playing_model("SCNNCE\n\n0,2 count=1\n0,3 count=1\n");
+        let run = PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 2,
+            use_tile: false,
+        };
+        play_move(&mut model, DirectionKey::Right);
+        model.simulate(&[run]);
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert_eq!(model.get_board()[1][3].tile, Tile::Switch(0));
+        assert_eq!(model.get_board()[1][4].tile, Tile::Switch(1));
+
+        model.simulate(&[run]);
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert_eq!(model.get_board()[1][4].tile, Tile::Switch(0));
+        model.simulate(&[run]);
+        assert_eq!(model.get_player_pos(), (1, 4));
+    }
+
+    #[test]
+    fn blinker_alternates_each_move() {
+        let mut model = playing_model("SLCE\n\n0,1 phase=1\n");
+        assert!(!model.blinker_is_open(true));
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 1)); // closed, blocked
+        assert!(model.blinker_is_open(true));
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2)); // open, moved onto it
+    }
+
+    #[test]
+    fn disallowed_direction_is_reported() {
+        let mut model = playing_model("SCE\n\n0,1 dirs=R\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.take_blocked_move(), None);
+
+        play_move(&mut model, DirectionKey::Down);
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert_eq!(model.take_blocked_move(), Some(DirectionKey::Down));
+        assert_eq!(model.take_blocked_move(), None);
+    }
+
+    #[test]
+    fn checkpoint_without_key_blocks() {
+        let mut model = playing_model("SC!CE\n");
+        play_move(&mut model, DirectionKey::Right);
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 2));
+    }
+
+    #[test]
+    fn checkpoint_with_key_sets_respawn() {
+        let mut model = playing_model("SC!.E\n\n0,1 key=finish\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(
+            model.get_inventory(),
+            &[KeyItem::OnGet(KeyOnGet::FinishKey)]
+        );
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 3));
+        assert!(model.get_inventory().is_empty());
+        assert_eq!(model.get_keys_used(), 1);
+
+        // Falling off respawns at the checkpoint
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 3));
+    }
+
+    #[test]
+    fn reverse_turns_player_around() {
+        let mut model = playing_model("C.SB.R.E\n\n0,3 bounce=1\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 1)); // back over the bounce, past the start, onto the C
+    }
+
+    #[test]
+    fn reverse_next_to_wall_stops() {
+        let mut model = playing_model("#RSE\n");
+        let result = play_move(&mut model, DirectionKey::Left);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 3)); // bounced back onto the start
+    }
+
+    #[test]
+    fn facing_reverse_tiles_stop() {
+        let mut model = playing_model("SR~RE\n");
+        let result = model.simulate(&[PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 2,
+            use_tile: false,
+        }]);
+
+        assert!(matches!(result, MovementPopupData::None));
+
+        assert_eq!(model.get_player_pos(), (1, 4)); // stopped on a reverse tile after the cap
+    }
+
+    #[test]
+    fn run_moves_at_board_run_speed() {
+        let mut model = playing_model("SCCCCE\n\nrun_speed=3\n");
+        let result = model.simulate(&[PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: model.get_run_speed(),
+            use_tile: false,
+        }]);
+
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 4)); // three tiles along the corridor
+    }
+
+    #[test]
+    fn only_a_run_clears_a_gap() {
+        let mut model = playing_model("SC_CCE\n\nrun_speed=2\n");
+        play_move(&mut model, DirectionKey::Right);
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 2)); // stopped in front of the gap
+
+        model.play_move(PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: model.get_run_speed(),
+            use_tile: false,
+        });
+        assert_eq!(model.get_player_pos(), (1, 4)); // landed beyond it
+    }
+
+    #[test]
+    fn speed_gates_need_an_exact_speed() {
+        let mut model = playing_model("SCGCCE\n\nrun_speed=2\n");
+        play_move(&mut model, DirectionKey::Right);
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 2)); // stopped in front of the gate
+
+        model.play_move(PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: model.get_run_speed(),
+            use_tile: false,
+        });
+        assert_eq!(model.get_player_pos(), (1, 4)); // passed through at speed 2
+    }
+
+    #[test]
+    fn pushed_block_moves_ahead_of_the_player() {
+        let mut model = playing_model("S$.E\n");
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::None
+        ));
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert_eq!(model.get_board()[1][2].tile, BLOCK_GROUND);
+        assert_eq!(model.get_board()[1][3].tile, Tile::Block);
+
+        assert!(model.undo_move());
+        assert_eq!(model.get_board()[1][2].tile, Tile::Block);
+        assert_eq!(model.get_board()[1][3].tile, Tile::Empty);
+
+        // A run stops at the first block in its way
+        let mut model = playing_model("S$..\n\nrun_speed=3\n");
+        model.simulate(&[PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 3,
+            use_tile: false,
+        }]);
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert_eq!(model.get_board()[1][3].tile, Tile::Block);
+    }
+
+    #[test]
+    fn block_with_nowhere_to_go_stops_the_player() {
+        for ascii in ["S$#\n", "S$C\n", "S$$.\n"] {
+            let mut model = playing_model(ascii);
+            play_move(&mut model, DirectionKey::Right);
+            assert_eq!(model.get_player_pos(), (1, 1), "{ascii}");
+            assert_eq!(model.get_board()[1][2].tile, Tile::Block, "{ascii}");
+            assert!(model.take_wall_hit(), "{ascii}");
+        }
+    }
+
+    #[test]
+    fn block_cant_be_pushed_off_the_board() {
+        let mut model = playing_model("S$\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 1));
+        assert_eq!(model.get_board()[1][2].tile, Tile::Block);
+        assert_eq!(model.get_board()[1][3].tile, Tile::Empty); // padding
+    }
+
+    #[test]
+    fn pushed_block_slides_on_ice() {
+        // Up to the last ice in front of a wall
+        let mut model = playing_model("S$~~#\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_board()[1][3].tile, Tile::Ice);
+        assert_eq!(model.get_board()[1][4].tile, Tile::Block);
+
+        // Or off the end of the ice onto an empty tile, uncovering the ice once pushed again
+        let mut model = playing_model("S$~.~\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_board()[1][4].tile, Tile::Block);
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_board()[1][4].tile, Tile::Empty);
+        assert_eq!(model.get_board()[1][5].tile, Tile::Block);
+    }
+
+    #[test]
+    fn rotators_turn_the_controls() {
+        let mut model = playing_model("...\n.CC\nSQC\n.C.\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_control_rotation(), 1);
+
+        play_move(&mut model, DirectionKey::Up); // moves right
+        assert_eq!(model.get_player_pos(), (3, 3));
+
+        // A second rotator turns them further, pressing up now moves down
+        let mut model = playing_model("SQQC\n..C.\n");
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Up); // moves right onto the second rotator
+        assert_eq!(model.get_control_rotation(), 2);
+        play_move(&mut model, DirectionKey::Up);
+        assert_eq!(model.get_player_pos(), (2, 3));
+    }
+
+    #[test]
+    fn bookmark_returns_to_marked_tile() {
+        let mut model = playing_model("SCCE\n\n0,1 key=bookmark\n");
+        let use_key = PlayerMovementData {
+            direction: DirectionKey::None,
+            move_speed: 1,
+            use_tile: true,
+        };
+        play_move(&mut model, DirectionKey::Right);
+        model.play_move(use_key);
+        assert_eq!(model.get_bookmark(), Some((1, 2)));
+
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 3));
+        model.play_move(use_key);
+        assert_eq!(model.get_player_pos(), (1, 2)); // back at the mark
+        assert_eq!(model.get_bookmark(), None);
+        assert!(model.get_inventory().is_empty()); // the key is used up
+    }
+
+    #[test]
+    fn shield_passes_one_wall() {
+        let mut model = playing_model("SH#C#CE\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_shields(), 1);
+
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 4)); // through the first wall
+        assert_eq!(model.get_shields(), 0);
+        assert!(!model.take_wall_hit());
+
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::Wall)); // the second wall stops the player
+        assert_eq!(model.get_shields(), 0);
+    }
+
+    #[test]
+    fn blocked_shielded_move_keeps_the_shield() {
+        let mut model = playing_model("SH#_CE\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_shields(), 1);
+
+        // A run through the wall can't land in the gap past it
+        let result = model.simulate(&[PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 2,
+            use_tile: false,
+        }]);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (1, 2)); // offset by 1 for padding
+        assert_eq!(model.get_shields(), 1);
+    }
+
+    #[test]
+    fn arrow_pushes_player_along_it() {
+        let mut model = playing_model("SA.\n.C.\n.N.\n.E.\n\n0,1 arrow=D\n2,1 count=1\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (2, 2)); // pushed down off the arrow
+
+        play_move(&mut model, DirectionKey::Up); // back onto the arrow, pushed down again
+        assert_eq!(model.get_player_pos(), (2, 2));
+    }
+
+    #[test]
+    fn arrow_ignores_pressed_direction() {
+        let mut model = playing_model("SA\n.N\n.C\n.E\n\n0,1 arrow=D\n1,1 count=1\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert_eq!(model.get_player_pos(), (1, 2)); // push blocked by the switch
+
+        let result = play_move(&mut model, DirectionKey::Up);
+        assert!(matches!(result, MovementPopupData::None));
+        assert_eq!(model.get_player_pos(), (2, 2)); // sent down despite pressing up
+    }
+
+    #[test]
+    fn using_needed_cloud_strands() {
+        let mut model = playing_model(".E.\nSOC\n");
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::None
+        ));
+        assert_eq!(model.clouds_remaining(), 1);
+
+        let result = play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(result, MovementPopupData::Stranded));
+        assert_eq!(model.clouds_remaining(), 0);
+    }
+
+    #[test]
+    fn undo_restores_consumed_cloud() {
+        let mut model = playing_model("SOCE\n\n0,2 key=wall\n");
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Right);
+        assert!(model.is_consumed_cloud((1, 2)));
+        assert_eq!(model.get_inventory().len(), 1);
+
+        assert!(model.undo_move());
+        assert_eq!(model.get_player_pos(), (1, 2));
+        assert!(matches!(model.get_board()[1][2].tile, Tile::Cloud(_)));
+        assert!(!model.is_consumed_cloud((1, 2)));
+        assert!(model.get_inventory().is_empty());
+        assert_eq!(model.get_move_count(), 1);
+        assert_eq!(model.get_record().len(), 1);
+
+        assert!(model.undo_move());
+        assert_eq!(model.get_player_pos(), (1, 1));
+        assert!(!model.undo_move());
+    }
+
+    #[test]
+    fn simulate_stops_at_outcome() {
+        let mut model = playing_model("S~~~E\n.....\n");
+        let moves = [DirectionKey::Right, DirectionKey::Down].map(|direction| PlayerMovementData {
+            direction,
+            move_speed: 1,
+            use_tile: false,
+        });
+
+        assert!(matches!(model.simulate(&moves), MovementPopupData::Won));
+        assert_eq!(model.get_record().len(), 1);
+    }
+
+    #[test]
+    fn empty_tile_without_momentum_loses() {
+        let mut model = playing_model("S.~~#E\n");
+        let result = play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(result, MovementPopupData::Lost));
+    }
+
+    #[test]
+    fn move_limit_reached_on_the_end_wins() {
+        let mut model = playing_model("SCCE\n\nmove_limit=3\n");
+        play_move(&mut model, DirectionKey::Right);
+        play_move(&mut model, DirectionKey::Right);
+
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::Won
+        ));
+    }
+
+    #[test]
+    fn move_limit_running_out_short_of_the_end_loses() {
+        let mut model = playing_model("SCCCE\n\nmove_limit=3\n");
+        play_move(&mut model, DirectionKey::Right);
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::None
+        ));
+
+        assert!(matches!(
+            play_move(&mut model, DirectionKey::Right),
+            MovementPopupData::Lost
+        ));
+        assert_eq!(model.get_player_pos(), (1, 4)); // offset by 1 for padding
+    }
 }
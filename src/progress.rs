@@ -0,0 +1,82 @@
+//!
+//! Saving a game part way through a level, to resume it later.
+//!
+
+use super::editing_model::EditingModel;
+use super::error::FoamError;
+use super::playing_model::PlayingModel;
+use serde::{Deserialize, Serialize};
+
+/// A game saved between moves. The playing state holds its own padded board, so it resumes
+/// without the level file, and the level is kept alongside for the editor and next levels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Progress {
+    pub board: EditingModel, // level being played
+    pub state: PlayingModel, // where the player has got to
+}
+
+impl Progress {
+    pub fn new(board: &EditingModel, state: &PlayingModel) -> Self {
+        Progress {
+            board: board.clone(),
+            state: state.clone(),
+        }
+    }
+
+    pub fn load_progress(file: &str) -> Result<Self, FoamError> {
+        let progress_raw = std::fs::read_to_string(file)?;
+        let progress: Progress = serde_json::from_str(&progress_raw)?;
+        progress.state.check_restored()?;
+        Ok(progress)
+    }
+
+    pub fn save_progress(&self, file: &str) -> Result<(), FoamError> {
+        let progress_data = serde_json::to_string(&self)?;
+        std::fs::write(file, progress_data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_ui::{DirectionKey, PlayerMovementData};
+    use crate::playing_model::MovementPopupData;
+
+    #[test]
+    fn resumed_game_plays_on_from_the_same_state() {
+        let board = EditingModel::from_ascii("SOCCE\n").unwrap();
+        let mut model = PlayingModel::new(&board).unwrap();
+        let right = PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 1,
+            use_tile: false,
+        };
+        model.simulate(&[right, right]);
+        assert_eq!(model.clouds_consumed(), 1);
+
+        let path = std::env::temp_dir().join("foam_game_progress_test.fgp");
+        let path = path.to_string_lossy();
+        Progress::new(&board, &model).save_progress(&path).unwrap();
+        let mut resumed = Progress::load_progress(&path).unwrap().state;
+        std::fs::remove_file(path.as_ref()).unwrap();
+
+        assert_eq!(resumed.get_board(), model.get_board());
+        assert_eq!(resumed.get_player_pos(), model.get_player_pos());
+        assert_eq!(resumed.get_move_count(), model.get_move_count());
+        assert_eq!(resumed.get_inventory(), model.get_inventory());
+        assert_eq!(resumed.clouds_consumed(), 1);
+        assert!(resumed.is_consumed_cloud((1, 2)));
+
+        // Both finish the level the same way
+        assert!(matches!(
+            model.simulate(&[right, right]),
+            MovementPopupData::Won
+        ));
+        assert!(matches!(
+            resumed.simulate(&[right, right]),
+            MovementPopupData::Won
+        ));
+        assert_eq!(resumed.get_record(), model.get_record());
+    }
+}
@@ -0,0 +1,263 @@
+//!
+//! Recording and playback of player moves.
+//!
+
+use super::editing_model::EditingModel;
+use super::error::FoamError;
+use super::game_ui::{DirectionKey, PlayerMovementData};
+use super::playing_model::{MovementPopupData, PlayingModel};
+use serde::{Deserialize, Serialize};
+
+/// Seconds between the moves of a ghost raced from a replay saved without move times
+const UNTIMED_MOVE_SECONDS: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayMetadata {
+    pub board: EditingModel, // board the moves were recorded on
+}
+
+/// Every move made in a playthrough, in order, played back against a fresh `PlayingModel`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub metadata: ReplayMetadata,
+    pub moves: Vec<PlayerMovementData>,
+    #[serde(default)]
+    pub times: Vec<f64>, // seconds into play each move started, empty in replays saved before moves were timed
+}
+
+impl Replay {
+    pub fn new(board: &EditingModel, moves: &[PlayerMovementData], times: &[f64]) -> Self {
+        Replay {
+            metadata: ReplayMetadata {
+                board: board.clone(),
+            },
+            moves: moves.to_vec(),
+            times: times.to_vec(),
+        }
+    }
+
+    pub fn load_replay(file: &str) -> Result<Self, FoamError> {
+        let replay_raw = std::fs::read_to_string(file)?;
+        let replay: Replay = serde_json::from_str(&replay_raw)?;
+        Ok(replay)
+    }
+
+    pub fn save_replay(&self, file: &str) -> Result<(), FoamError> {
+        let replay_data = serde_json::to_string(&self)?;
+        std::fs::write(file, replay_data)?;
+        Ok(())
+    }
+}
+
+/// A replay raced against a live run, as a game of its own playing each recorded move as long
+/// after the live run's first move as it came after the recorded first move. It never touches
+/// the live game, so it carries on the same however differently the live run goes
+pub struct Ghost {
+    start: PlayingModel, // before the first move, to race again from
+    model: PlayingModel,
+    moves: Vec<PlayerMovementData>,
+    times: Vec<f64>,      // seconds after the first move each move is played
+    next_move: usize,     // index of the next move to play
+    started: Option<f64>, // when the live run made its first move
+    board_hash: u64,      // content hash of the board the replay was recorded on
+}
+
+impl Ghost {
+    pub fn new(replay: &Replay) -> Result<Self, FoamError> {
+        let start = PlayingModel::new(&replay.metadata.board)?;
+        let times = if replay.times.len() == replay.moves.len() {
+            let first = replay.times.first().copied().unwrap_or_default();
+            replay.times.iter().map(|time| time - first).collect()
+        } else {
+            (0..replay.moves.len())
+                .map(|index| index as f64 * UNTIMED_MOVE_SECONDS)
+                .collect()
+        };
+        Ok(Ghost {
+            model: start.clone(),
+            start,
+            moves: replay.moves.clone(),
+            times,
+            next_move: 0,
+            started: None,
+            board_hash: replay.metadata.board.content_hash(),
+        })
+    }
+
+    pub fn board_hash(&self) -> u64 {
+        self.board_hash
+    }
+
+    /// Go back to the start, waiting for the live run's first move again
+    pub fn restart(&mut self) {
+        self.model = self.start.clone();
+        self.next_move = 0;
+        self.started = None;
+    }
+
+    /// Start racing at `time`, when the live run made its first move. Does nothing once started
+    pub fn start(&mut self, time: f64) {
+        self.started.get_or_insert(time);
+    }
+
+    /// When the next move is due, None before the race starts or once the moves run out
+    pub fn next_move_at(&self) -> Option<f64> {
+        Some(self.started? + self.times.get(self.next_move)?)
+    }
+
+    /// Play every move due by `time`. The ghost stops where its run ended, e.g. on a win or
+    /// on a wall it waited at
+    pub fn advance(&mut self, time: f64) {
+        while self.next_move_at().is_some_and(|due| due <= time) {
+            let result = self.model.play_move(self.moves[self.next_move]);
+            self.next_move = if matches!(result, MovementPopupData::None) {
+                self.next_move + 1
+            } else {
+                self.moves.len()
+            };
+        }
+    }
+
+    /// Where the ghost is, padded like the live game's positions
+    pub fn get_player_pos(&self) -> (usize, usize) {
+        self.model.get_player_pos()
+    }
+}
+
+/// Moves written as direction codes separated by spaces, e.g. `U U R* D`. A `*` after a
+/// direction runs it `run_speed` tiles, and `use` uses the tile the player is standing on
+pub fn parse_moves(text: &str, run_speed: usize) -> Result<Vec<PlayerMovementData>, String> {
+    let moves = text
+        .split_whitespace()
+        .enumerate()
+        .map(|(index, token)| {
+            let code = token.to_ascii_uppercase();
+            if code == "USE" {
+                return Ok(PlayerMovementData {
+                    direction: DirectionKey::None,
+                    move_speed: 1,
+                    use_tile: true,
+                });
+            }
+            let (code, run) = match code.strip_suffix('*') {
+                Some(code) => (code, true),
+                None => (code.as_str(), false),
+            };
+            let direction = DirectionKey::from_code(code).ok_or_else(|| {
+                format!(
+                    "Move {}: \"{token}\" isn't a direction like U or DR, a run like L*, or use",
+                    index + 1
+                )
+            })?;
+            Ok(PlayerMovementData {
+                direction,
+                move_speed: if run { run_speed } else { 1 },
+                use_tile: false,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if moves.is_empty() {
+        return Err("Type some moves, e.g. U U R* D".to_string());
+    }
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_ui::DirectionKey;
+
+    #[test]
+    fn parse_moves_reads_directions_runs_and_uses() {
+        let moves = parse_moves("U u  R* DL use", 3).unwrap();
+        let expected = [
+            (DirectionKey::Up, 1, false),
+            (DirectionKey::Up, 1, false),
+            (DirectionKey::Right, 3, false),
+            (DirectionKey::DownLeft, 1, false),
+            (DirectionKey::None, 1, true),
+        ]
+        .map(|(direction, move_speed, use_tile)| PlayerMovementData {
+            direction,
+            move_speed,
+            use_tile,
+        });
+        assert_eq!(moves, expected);
+
+        let error = parse_moves("U X D", 2).unwrap_err();
+        assert!(error.starts_with("Move 2:"), "{error}");
+        assert!(parse_moves("U *", 2).is_err());
+        assert!(parse_moves("   ", 2).is_err());
+    }
+
+    #[test]
+    fn replay_reaches_same_end_state() {
+        let mut board = EditingModel::from_ascii("S~.C.\n...O.\n...CE\n").unwrap();
+        assert!(board.board_is_playable());
+
+        let mut model = PlayingModel::new(&board).unwrap();
+        let moves = [
+            DirectionKey::Right,
+            DirectionKey::Down,
+            DirectionKey::Down,
+            DirectionKey::Right,
+        ]
+        .map(|direction| PlayerMovementData {
+            direction,
+            move_speed: 1,
+            use_tile: false,
+        });
+        model.simulate(&moves);
+
+        let replay = Replay::new(&board, model.get_record(), model.get_record_times());
+        let replay: Replay =
+            serde_json::from_str(&serde_json::to_string(&replay).unwrap()).unwrap();
+
+        let mut replayed = PlayingModel::new(&replay.metadata.board).unwrap();
+        let result = replayed.simulate(&replay.moves);
+
+        assert!(matches!(result, MovementPopupData::Won));
+        assert_eq!(replayed.get_player_pos(), model.get_player_pos());
+        assert_eq!(replayed.get_board(), model.get_board());
+        assert_eq!(replayed.get_record(), model.get_record());
+        assert_eq!(replay.times.len(), replay.moves.len());
+    }
+
+    #[test]
+    fn ghost_races_from_the_first_live_move() {
+        let board = EditingModel::from_ascii("SCCCE\n").unwrap();
+        let right = PlayerMovementData {
+            direction: DirectionKey::Right,
+            move_speed: 1,
+            use_tile: false,
+        };
+        let replay = Replay::new(&board, &[right, right, right], &[2.0, 3.0, 4.5]);
+        let mut ghost = Ghost::new(&replay).unwrap();
+
+        // Nothing happens until the live run moves
+        ghost.advance(100.0);
+        assert_eq!(ghost.get_player_pos(), (1, 1));
+
+        ghost.start(10.0);
+        ghost.advance(10.0);
+        assert_eq!(ghost.get_player_pos(), (1, 2));
+        ghost.advance(10.9);
+        assert_eq!(ghost.get_player_pos(), (1, 2));
+        assert_eq!(ghost.next_move_at(), Some(11.0));
+        ghost.advance(12.5);
+        assert_eq!(ghost.get_player_pos(), (1, 4));
+        assert_eq!(ghost.next_move_at(), None);
+
+        ghost.restart();
+        assert_eq!(ghost.get_player_pos(), (1, 1));
+        assert_eq!(ghost.next_move_at(), None);
+
+        // Replays saved without times move at a steady pace
+        let untimed = Replay::new(&board, &[right, right], &[]);
+        let mut ghost = Ghost::new(&untimed).unwrap();
+        ghost.start(0.0);
+        ghost.advance(UNTIMED_MOVE_SECONDS);
+        assert_eq!(ghost.get_player_pos(), (1, 3));
+    }
+}
@@ -0,0 +1,396 @@
+//!
+//! User settings, persisted between runs.
+//!
+
+use super::tile::portal_color;
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+const SETTINGS_FILE: &str = "foam_game_settings.json";
+
+/// Most board files remembered in the recent files list
+pub const MAX_RECENT_FILES: usize = 8;
+
+/// Fastest allowed animation step, so a speed of 0 can't spin the animation loop
+pub const MIN_ANIMATION_SPEED: f64 = 0.01;
+pub const MAX_ANIMATION_SPEED: f64 = 0.5;
+
+/// Seconds a direction key is held before it starts repeating, and between repeats
+pub const KEY_REPEAT_DELAY_RANGE: RangeInclusive<f64> = 0.1..=1.0;
+pub const KEY_REPEAT_INTERVAL_RANGE: RangeInclusive<f64> = 0.02..=0.5;
+
+/// Shape the player is drawn as, when not using a sprite
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PlayerShape {
+    #[default]
+    Circle,
+    Square,
+    Triangle,
+}
+
+pub const ALL_PLAYER_SHAPES: &[PlayerShape] = &[
+    PlayerShape::Circle,
+    PlayerShape::Square,
+    PlayerShape::Triangle,
+];
+
+/// When the on-screen d-pad for touchscreens is shown in play
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TouchControls {
+    #[default]
+    Automatic, // once the screen has been touched
+    Always,
+    Never,
+}
+
+pub const ALL_TOUCH_CONTROLS: &[TouchControls] = &[
+    TouchControls::Automatic,
+    TouchControls::Always,
+    TouchControls::Never,
+];
+
+impl TouchControls {
+    /// Whether the d-pad is shown, given whether a touch has been seen since the game started
+    pub fn shown(self, touch_detected: bool) -> bool {
+        match self {
+            TouchControls::Automatic => touch_detected,
+            TouchControls::Always => true,
+            TouchControls::Never => false,
+        }
+    }
+}
+
+/// Colors the tile overlays are drawn in, with presets for color blindness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ColorPalette {
+    #[default]
+    Standard,
+    Protanopia,   // red-blind
+    Deuteranopia, // green-blind
+    Tritanopia,   // blue-blind
+}
+
+pub const ALL_COLOR_PALETTES: &[ColorPalette] = &[
+    ColorPalette::Standard,
+    ColorPalette::Protanopia,
+    ColorPalette::Deuteranopia,
+    ColorPalette::Tritanopia,
+];
+
+/// Okabe-Ito colors, which stay apart under every kind of color blindness
+const OKABE_ITO: &[Color32] = &[
+    Color32::from_rgb(230, 159, 0),   // orange
+    Color32::from_rgb(86, 180, 233),  // sky blue
+    Color32::from_rgb(0, 158, 115),   // bluish green
+    Color32::from_rgb(240, 228, 66),  // yellow
+    Color32::from_rgb(0, 114, 178),   // blue
+    Color32::from_rgb(213, 94, 0),    // vermillion
+    Color32::from_rgb(204, 121, 167), // reddish purple
+];
+
+/// Colors of everything drawn over the tile textures
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayPalette {
+    pub number: Color32,               // bounce, speed pad and switch amounts
+    pub key_letter: Color32,           // letters of door and teleport keys
+    pub move_arrow: Color32,           // directions a movement tile allows
+    pub blinker: Color32,              // a blinker's open or closed marker
+    pub hazard: Color32,               // arrow tiles, and the ring of a reset trap that takes keys
+    pub unreachable: Color32,          // editor shading over tiles the player can't reach
+    pub preview: Color32,              // editor branch preview arrow
+    pub critical: Color32,             // editor outline of tiles every shortest win lands on
+    portal_colors: &'static [Color32], // cycled through by letter, or empty for a hue per letter
+    portal_prefix: Option<char>,       // marks portal letters apart from key letters without color
+}
+
+impl ColorPalette {
+    pub fn overlay_palette(self) -> OverlayPalette {
+        let standard = OverlayPalette {
+            number: Color32::RED,
+            key_letter: Color32::RED,
+            move_arrow: Color32::BLACK,
+            blinker: Color32::BLACK,
+            hazard: Color32::RED,
+            unreachable: Color32::from_rgba_unmultiplied(255, 0, 0, 80),
+            preview: Color32::from_rgb(255, 140, 0),
+            critical: Color32::from_rgb(0, 200, 80),
+            portal_colors: &[],
+            portal_prefix: None,
+        };
+        match self {
+            ColorPalette::Standard => standard,
+            // Reds and greens run together, so reds become blue and vermillion
+            ColorPalette::Protanopia | ColorPalette::Deuteranopia => OverlayPalette {
+                number: Color32::from_rgb(0, 114, 178),
+                key_letter: Color32::from_rgb(0, 114, 178),
+                hazard: Color32::from_rgb(213, 94, 0),
+                unreachable: Color32::from_rgba_unmultiplied(0, 60, 180, 90),
+                preview: Color32::from_rgb(230, 159, 0),
+                critical: Color32::from_rgb(86, 180, 233),
+                portal_colors: OKABE_ITO,
+                portal_prefix: Some('@'),
+                ..standard
+            },
+            // Blues and yellows run together, so the preview turns magenta
+            ColorPalette::Tritanopia => OverlayPalette {
+                number: Color32::from_rgb(200, 0, 0),
+                key_letter: Color32::from_rgb(200, 0, 0),
+                hazard: Color32::from_rgb(204, 121, 167),
+                unreachable: Color32::from_rgba_unmultiplied(200, 0, 0, 80),
+                preview: Color32::from_rgb(204, 0, 102),
+                critical: Color32::from_rgb(0, 158, 115),
+                portal_colors: OKABE_ITO,
+                portal_prefix: Some('@'),
+                ..standard
+            },
+        }
+    }
+}
+
+impl OverlayPalette {
+    /// Color shared by both portals of a letter
+    pub fn portal_color(&self, letter: char) -> Color32 {
+        match self.portal_colors {
+            [] => portal_color(letter),
+            colors => colors[(letter as usize) % colors.len()],
+        }
+    }
+
+    /// Text drawn on a portal: its letter, after the palette's prefix if it has one
+    pub fn portal_label(&self, letter: char) -> String {
+        match self.portal_prefix {
+            Some(prefix) => format!("{prefix}{letter}"),
+            None => letter.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub animation_speed: f64,            // seconds per tile movement
+    pub show_grid: bool,                 // outline empty tiles on the board
+    pub grid_color: [u8; 4],             // RGBA color of the grid lines, premultiplied
+    pub collision_flash: bool,           // flash the board red when the player runs into a wall
+    pub show_coordinates: bool,          // label editor tiles with their (row, column)
+    pub recent_files: Vec<PathBuf>,      // board files most recently loaded or saved, newest first
+    pub player_color: [u8; 3],           // RGB color of the player's shape
+    pub player_shape: PlayerShape,       // drawn when there's no player sprite
+    pub player_sprite: Option<PathBuf>,  // PNG drawn for the player instead of the shape
+    pub show_move_arrows: bool,          // draw the allowed-move arrows of movement tiles in play
+    pub show_ice_momentum_in_play: bool, // draw the ways slides carry the player over ice in play
+    pub show_frame_rate: bool, // show how often the window redraws, and how long a frame takes
+    pub color_palette: ColorPalette, // colors of the tile overlays
+    pub key_repeat: bool,      // holding a direction key in play keeps moving
+    pub key_repeat_delay: f64, // seconds held before the first repeat
+    pub key_repeat_interval: f64, // seconds between repeats after that
+    pub sound_effects: bool,   // play sounds for slides, bounces, portals and walls
+    pub sound_volume: f32,     // from 0 (silent) to 1
+    pub warn_unwinnable: bool, // ask before a move in play that leaves the end out of reach
+    pub touch_controls: TouchControls, // when the on-screen d-pad is shown in play
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            animation_speed: 0.1,
+            show_grid: true,
+            grid_color: [64, 64, 64, 64],
+            collision_flash: true,
+            show_coordinates: false,
+            recent_files: Vec::new(),
+            player_color: [0, 0, 0],
+            player_shape: PlayerShape::Circle,
+            player_sprite: None,
+            show_move_arrows: true,
+            show_ice_momentum_in_play: false,
+            show_frame_rate: false,
+            color_palette: ColorPalette::Standard,
+            key_repeat: false,
+            key_repeat_delay: 0.3,
+            key_repeat_interval: 0.1,
+            sound_effects: true,
+            sound_volume: 0.5,
+            warn_unwinnable: true,
+            touch_controls: TouchControls::Automatic,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Load the settings file, falling back to defaults if it is missing or invalid
+    pub fn load() -> Self {
+        let mut settings = std::fs::read_to_string(SETTINGS_FILE)
+            .map_err(|err| format!("Error reading settings file: {err}"))
+            .and_then(|settings_raw| {
+                serde_json::from_str::<AppSettings>(&settings_raw)
+                    .map_err(|err| format!("Error deserializing settings: {err}"))
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Warning: using default settings. {err}");
+                AppSettings::default()
+            });
+        settings.clamp();
+        settings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let settings_data = serde_json::to_string_pretty(&self)
+            .map_err(|err| format!("Error serializing settings: {err}"))?;
+        std::fs::write(SETTINGS_FILE, settings_data)
+            .map_err(|err| format!("Error writing settings file: {err}"))?;
+        Ok(())
+    }
+
+    /// Move a board file to the front of the recent files list, dropping the oldest if full
+    pub fn add_recent_file(&mut self, path: PathBuf) {
+        self.remove_recent_file(&path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    pub fn remove_recent_file(&mut self, path: &Path) {
+        self.recent_files.retain(|recent| recent != path);
+    }
+
+    /// Keep values within sane bounds
+    pub fn clamp(&mut self) {
+        self.animation_speed = if self.animation_speed.is_finite() {
+            self.animation_speed
+                .clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED)
+        } else {
+            AppSettings::default().animation_speed
+        };
+        let defaults = AppSettings::default();
+        for (value, range, default) in [
+            (
+                &mut self.key_repeat_delay,
+                KEY_REPEAT_DELAY_RANGE,
+                defaults.key_repeat_delay,
+            ),
+            (
+                &mut self.key_repeat_interval,
+                KEY_REPEAT_INTERVAL_RANGE,
+                defaults.key_repeat_interval,
+            ),
+        ] {
+            *value = if value.is_finite() {
+                value.clamp(*range.start(), *range.end())
+            } else {
+                default
+            };
+        }
+        self.sound_volume = if self.sound_volume.is_finite() {
+            self.sound_volume.clamp(0.0, 1.0)
+        } else {
+            defaults.sound_volume
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_keeps_animation_speed_sane() {
+        let mut settings = AppSettings {
+            animation_speed: 0.0,
+            ..Default::default()
+        };
+        settings.clamp();
+        assert_eq!(settings.animation_speed, MIN_ANIMATION_SPEED);
+
+        settings.animation_speed = f64::NAN;
+        settings.clamp();
+        assert_eq!(
+            settings.animation_speed,
+            AppSettings::default().animation_speed
+        );
+
+        // A zero repeat interval would move the player every frame
+        settings.key_repeat_interval = 0.0;
+        settings.key_repeat_delay = f64::INFINITY;
+        settings.clamp();
+        assert_eq!(
+            settings.key_repeat_interval,
+            *KEY_REPEAT_INTERVAL_RANGE.start()
+        );
+        assert_eq!(
+            settings.key_repeat_delay,
+            AppSettings::default().key_repeat_delay
+        );
+    }
+
+    #[test]
+    fn older_settings_get_default_player_appearance() {
+        let settings: AppSettings =
+            serde_json::from_str(r#"{"animation_speed": 0.2, "show_grid": false}"#).unwrap();
+        assert_eq!(settings.player_shape, PlayerShape::Circle);
+        assert_eq!(settings.player_color, [0, 0, 0]);
+        assert_eq!(settings.player_sprite, None);
+        assert!(settings.show_move_arrows);
+        assert!(!settings.show_grid);
+    }
+
+    #[test]
+    fn touch_controls_show_automatically_after_a_touch() {
+        let settings: AppSettings = serde_json::from_str(r#"{"show_grid": false}"#).unwrap();
+        assert!(!settings.touch_controls.shown(false));
+        assert!(settings.touch_controls.shown(true));
+
+        assert!(TouchControls::Always.shown(false));
+        assert!(!TouchControls::Never.shown(true));
+    }
+
+    #[test]
+    fn palettes_keep_overlays_apart() {
+        for palette in ALL_COLOR_PALETTES {
+            let overlay = palette.overlay_palette();
+            assert_ne!(overlay.number, overlay.move_arrow, "{palette:?}");
+            assert_ne!(overlay.hazard, overlay.move_arrow, "{palette:?}");
+            assert_ne!(overlay.critical, overlay.preview, "{palette:?}");
+            assert_ne!(
+                overlay.portal_color('A'),
+                overlay.portal_color('B'),
+                "{palette:?}"
+            );
+        }
+
+        // Color blind palettes don't lean on color alone to mark portals
+        assert_eq!(
+            ColorPalette::Deuteranopia
+                .overlay_palette()
+                .portal_label('A'),
+            "@A"
+        );
+        assert_eq!(
+            ColorPalette::Standard.overlay_palette().portal_label('A'),
+            "A"
+        );
+    }
+
+    #[test]
+    fn recent_files_are_unique_and_capped() {
+        let mut settings = AppSettings::default();
+        for i in 0..MAX_RECENT_FILES + 2 {
+            settings.add_recent_file(PathBuf::from(format!("board_{i}.fg")));
+        }
+        settings.add_recent_file(PathBuf::from("board_5.fg"));
+
+        assert_eq!(settings.recent_files.len(), MAX_RECENT_FILES);
+        assert_eq!(settings.recent_files[0], PathBuf::from("board_5.fg"));
+        assert_eq!(
+            settings
+                .recent_files
+                .iter()
+                .filter(|path| path.as_path() == Path::new("board_5.fg"))
+                .count(),
+            1
+        );
+        assert!(!settings.recent_files.contains(&PathBuf::from("board_0.fg")));
+    }
+}
@@ -2,12 +2,42 @@
 //! Game board tiles.
 //!
 
-use super::game_ui::DirectionKey;
-use super::item::KeyItem;
+use super::game_ui::{ALL_DIRECTIONS, DirectionKey};
+use super::item::{KeyItem, KeyOnEquip, KeyOnMovement, step_letter};
+use super::playing_model::PlayingModel;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+use std::ops::{Add, RangeInclusive, Sub};
+
+/// Allowed modifiers for bounce tiles
+pub const BOUNCE_RANGE: RangeInclusive<isize> = -5..=5;
+
+/// Allowed speeds for speed pads
+pub const SPEED_PAD_RANGE: RangeInclusive<usize> = 0..=10;
+
+/// Allowed required speeds for speed gates
+pub const SPEED_GATE_RANGE: RangeInclusive<usize> = 1..=10;
+
+/// Allowed starting counts for switches
+pub const SWITCH_RANGE: RangeInclusive<u8> = 1..=9;
+
+/// Next (or previous) value in one of the ranges above, wrapping around at its ends
+fn cycle_in<T>(value: T, range: RangeInclusive<T>, forward: bool) -> T
+where
+    T: Copy + PartialOrd + From<u8> + Add<Output = T> + Sub<Output = T>,
+{
+    let (start, end) = range.into_inner();
+    match forward {
+        true if value >= end => start,
+        true => value + T::from(1),
+        false if value <= start => end,
+        false => value - T::from(1),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct CardinalDirectionsAllowed {
     pub up: bool,
     pub right: bool,
@@ -16,6 +46,20 @@ pub struct CardinalDirectionsAllowed {
 }
 
 impl CardinalDirectionsAllowed {
+    /// Directions as a bitmask: up, right, down, left from the lowest bit
+    pub fn to_mask(&self) -> u8 {
+        self.up as u8 | (self.right as u8) << 1 | (self.down as u8) << 2 | (self.left as u8) << 3
+    }
+
+    pub fn from_mask(mask: u8) -> Self {
+        CardinalDirectionsAllowed {
+            up: mask & 1 != 0,
+            right: mask & 2 != 0,
+            down: mask & 4 != 0,
+            left: mask & 8 != 0,
+        }
+    }
+
     pub fn allows(&self, direction: &DirectionKey) -> bool {
         match direction {
             DirectionKey::Up => self.up,
@@ -27,7 +71,7 @@ impl CardinalDirectionsAllowed {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct DiagonalDirectionsAllowed {
     pub up_right: bool,
     pub down_right: bool,
@@ -36,6 +80,23 @@ pub struct DiagonalDirectionsAllowed {
 }
 
 impl DiagonalDirectionsAllowed {
+    /// Directions as a bitmask: up-right, down-right, down-left, up-left from the lowest bit
+    pub fn to_mask(&self) -> u8 {
+        self.up_right as u8
+            | (self.down_right as u8) << 1
+            | (self.down_left as u8) << 2
+            | (self.up_left as u8) << 3
+    }
+
+    pub fn from_mask(mask: u8) -> Self {
+        DiagonalDirectionsAllowed {
+            up_right: mask & 1 != 0,
+            down_right: mask & 2 != 0,
+            down_left: mask & 4 != 0,
+            up_left: mask & 8 != 0,
+        }
+    }
+
     pub fn allows(&self, direction: &DirectionKey) -> bool {
         match direction {
             DirectionKey::UpRight => self.up_right,
@@ -48,17 +109,29 @@ impl DiagonalDirectionsAllowed {
 }
 
 // Each tile occupies one space on the board, and has different rules for movement
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum Tile {
     Empty,
     MoveCardinal(CardinalDirectionsAllowed),
     MoveDiagonal(DiagonalDirectionsAllowed), // Move in specific directions, can be cardinal or diagonal
     Cloud(CardinalDirectionsAllowed),        // Clouds, disappear after one use
     Bounce(isize), // Bounce some amount of squares, +/- some amount of acceleration or deceleration
+    SpeedPad(usize), // Set the movement speed to an exact amount
     Portal(char, (usize, usize)), // Portal, teleport to other portal with same letter
     Ice,           // Ice
     Door,          // Doors
     Wall,          // Blocks movement
+    Gap,           // Blocks walking like a wall, but a run jumps over it
+    SpeedGate(usize), // Lets the player through only when moving at exactly this speed
+    Block,         // Pushed one tile ahead of the player, blocks movement if it can't move
+    Checkpoint,    // Consumes a finish key to set a respawn point, blocks movement without one
+    Reverse,       // Reverses the direction of the player's movement
+    Switch(u8),    // Blocks movement until stepped on this many times
+    Blinker(bool), // Open on turns whose parity matches, blocks movement on the others
+    Shield,        // Gives a shield on the first visit, which carries the player through one wall
+    Arrow(DirectionKey), // Pushes the player one tile in its direction, whatever they pressed
+    ResetTrap(bool), // Sends the player back to the start, also emptying their inventory if set
+    Rotator,       // Turns the player's controls a quarter turn clockwise for the rest of the level
     StartSpace,    // Start space, where the player starts
     EndSpace,      // End space, puzzle completion
 }
@@ -83,29 +156,54 @@ pub const ALL_TILES: &[Tile] = &[
         left: true,
     }),
     Tile::Bounce(0),
+    Tile::SpeedPad(2),
     Tile::Portal('A', (0, 0)),
     Tile::Ice,
     Tile::Door,
     Tile::Wall,
+    Tile::Gap,
+    Tile::SpeedGate(2),
+    Tile::Block,
+    Tile::Checkpoint,
+    Tile::Reverse,
+    Tile::Switch(2),
+    Tile::Blinker(false),
+    Tile::Shield,
+    Tile::Arrow(DirectionKey::Right),
+    Tile::ResetTrap(false),
+    Tile::Rotator,
     Tile::StartSpace,
     Tile::EndSpace,
     Tile::Empty,
 ];
 
 impl Tile {
+    /// Texture path, relative to the theme directory
     pub fn file_name(&self) -> &str {
         match self {
-            Tile::Empty => "assets/empty.png",
-            Tile::MoveCardinal(_) => "assets/move_cardinal.png",
-            Tile::MoveDiagonal(_) => "assets/move_diagonal.png",
-            Tile::Cloud(_) => "assets/cloud.png",
-            Tile::Bounce(_) => "assets/bounce.png",
-            Tile::Portal(..) => "assets/portal.png",
-            Tile::Ice => "assets/ice.png",
-            Tile::Door => "assets/door.png",
-            Tile::Wall => "assets/wall.png",
-            Tile::StartSpace => "assets/start_space.png",
-            Tile::EndSpace => "assets/end_space.png",
+            Tile::Empty => "empty.png",
+            Tile::MoveCardinal(_) => "move_cardinal.png",
+            Tile::MoveDiagonal(_) => "move_diagonal.png",
+            Tile::Cloud(_) => "cloud.png",
+            Tile::Bounce(_) => "bounce.png",
+            Tile::SpeedPad(_) => "speed_pad.png",
+            Tile::Portal(..) => "portal.png",
+            Tile::Ice => "ice.png",
+            Tile::Door => "door.png",
+            Tile::Wall => "wall.png",
+            Tile::Gap => "gap.png",
+            Tile::SpeedGate(_) => "speed_gate.png",
+            Tile::Block => "block.png",
+            Tile::Checkpoint => "checkpoint.png",
+            Tile::Reverse => "reverse.png",
+            Tile::Switch(_) => "switch.png",
+            Tile::Blinker(_) => "blinker.png",
+            Tile::Shield => "shield.png",
+            Tile::ResetTrap(_) => "reset_trap.png",
+            Tile::Arrow(_) => "arrow.png",
+            Tile::Rotator => "rotator.png",
+            Tile::StartSpace => "start_space.png",
+            Tile::EndSpace => "end_space.png",
         }
     }
 
@@ -113,17 +211,20 @@ impl Tile {
         match self {
             Tile::Empty => "An empty tile, no special properties.",
             Tile::MoveCardinal(_) => {
-                "A tile that allows moving up, down, left, right. Use arrow keys to toggle directions."
+                "A tile that allows moving up, down, left, right. Use arrow keys to toggle directions, R to rotate."
             }
             Tile::MoveDiagonal(_) => {
-                "A tile that allows moving up-right, down-right, down-left, up-left. Use arrow keys to toggle directions."
+                "A tile that allows moving up-right, down-right, down-left, up-left. Use arrow keys to toggle directions, R to rotate."
             }
             Tile::Cloud(_) => {
-                "A cloud tile that disappears after one use. Use arrow keys to toggle directions."
+                "A cloud tile that disappears after one use. Use arrow keys to toggle directions, R to rotate."
             }
             Tile::Bounce(_) => {
                 "A tile that bounces the player a certain distance. Use up and down to set the bounce modifier."
             }
+            Tile::SpeedPad(_) => {
+                "A speed pad, which sets the player's speed to an exact amount. Use up and down to set the speed."
+            }
             Tile::Portal(..) => {
                 "A portal tile that teleports the player to another location. Type a letter to identify the portal."
             }
@@ -132,11 +233,286 @@ impl Tile {
             }
             Tile::Ice => "An ice tile, which causes the player to slide.",
             Tile::Wall => "A wall tile, which blocks movement.",
+            Tile::Gap => {
+                "A gap, which stops you in front of it like a wall unless you run and jump over it."
+            }
+            Tile::SpeedGate(_) => {
+                "A speed gate, which only lets you through at exactly its speed and blocks movement otherwise. Use up and down to set the speed."
+            }
+            Tile::Block => {
+                "A block, which you push one tile ahead of you onto an empty tile or ice, where it slides. Blocks movement when it can't be pushed."
+            }
+            Tile::Checkpoint => {
+                "A checkpoint, which uses up a finish key to become your respawn point. Blocks movement without one."
+            }
+            Tile::Reverse => "A reverse tile, which turns the player around and keeps them moving.",
+            Tile::Switch(_) => {
+                "A switch, which blocks movement until stepped on enough times. Use up and down to set the count."
+            }
+            Tile::Blinker(_) => {
+                "A blinker, which blocks movement every other turn. Use up to change which turns it is open."
+            }
+            Tile::Shield => {
+                "A shield, picked up on the first visit. A held shield carries you through the next wall you hit."
+            }
+            Tile::Arrow(_) => {
+                "An arrow, which pushes you one tile in its direction whatever you press. Use arrow keys to set the direction, R to rotate."
+            }
+            Tile::ResetTrap(_) => {
+                "A reset trap, which sends you back to the start. Use up to also make it take your keys."
+            }
+            Tile::Rotator => {
+                "A rotator, which turns your controls a quarter turn clockwise each time you land on it, so up moves you right."
+            }
             Tile::StartSpace => "The starting space for the player.",
             Tile::EndSpace => "The end space for the puzzle completion.",
         }
     }
 
+    /// Rotate the allowed directions of a directional tile 90 degrees clockwise
+    pub fn rotate_cw(&mut self) {
+        match self {
+            Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+                *directions = CardinalDirectionsAllowed {
+                    up: directions.left,
+                    right: directions.up,
+                    down: directions.right,
+                    left: directions.down,
+                };
+            }
+            Tile::MoveDiagonal(directions) => {
+                *directions = DiagonalDirectionsAllowed {
+                    up_right: directions.up_left,
+                    down_right: directions.up_right,
+                    down_left: directions.down_right,
+                    up_left: directions.down_left,
+                };
+            }
+            Tile::Arrow(direction) => *direction = direction.rotated_cw(),
+            _ => {}
+        }
+    }
+
+    /// Mirror the allowed directions of a directional tile left to right
+    pub fn flip_horizontal(&mut self) {
+        match self {
+            Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+                std::mem::swap(&mut directions.left, &mut directions.right);
+            }
+            Tile::MoveDiagonal(directions) => {
+                std::mem::swap(&mut directions.up_left, &mut directions.up_right);
+                std::mem::swap(&mut directions.down_left, &mut directions.down_right);
+            }
+            Tile::Arrow(direction) => *direction = direction.flipped_horizontal(),
+            _ => {}
+        }
+    }
+
+    /// Mirror the allowed directions of a directional tile top to bottom
+    pub fn flip_vertical(&mut self) {
+        match self {
+            Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+                std::mem::swap(&mut directions.up, &mut directions.down);
+            }
+            Tile::MoveDiagonal(directions) => {
+                std::mem::swap(&mut directions.up_left, &mut directions.down_left);
+                std::mem::swap(&mut directions.up_right, &mut directions.down_right);
+            }
+            Tile::Arrow(direction) => *direction = direction.flipped_vertical(),
+            _ => {}
+        }
+    }
+
+    /// Step to the next (or previous) variant of a parametric tile, wrapping around at the ends
+    pub fn cycle_variant(&mut self, forward: bool) {
+        // Cycle through the non-empty direction sets, 1..=15
+        let next_mask = |mask: u8| {
+            if forward {
+                mask % 15 + 1
+            } else {
+                (mask + 13) % 15 + 1
+            }
+        };
+
+        match self {
+            Tile::MoveCardinal(directions) | Tile::Cloud(directions) => {
+                *directions = CardinalDirectionsAllowed::from_mask(next_mask(directions.to_mask()));
+            }
+            Tile::MoveDiagonal(directions) => {
+                *directions = DiagonalDirectionsAllowed::from_mask(next_mask(directions.to_mask()));
+            }
+            Tile::Bounce(amount) => *amount = cycle_in(*amount, BOUNCE_RANGE, forward),
+            Tile::SpeedPad(speed) => *speed = cycle_in(*speed, SPEED_PAD_RANGE, forward),
+            Tile::SpeedGate(speed) => *speed = cycle_in(*speed, SPEED_GATE_RANGE, forward),
+            Tile::Switch(count) => *count = cycle_in(*count, SWITCH_RANGE, forward),
+            Tile::Blinker(phase) | Tile::ResetTrap(phase) => *phase = !*phase,
+            Tile::Arrow(direction) => {
+                let index = ALL_DIRECTIONS
+                    .iter()
+                    .position(|other| other == direction)
+                    .unwrap_or(0);
+                let count = ALL_DIRECTIONS.len();
+                *direction = ALL_DIRECTIONS[if forward {
+                    (index + 1) % count
+                } else {
+                    (index + count - 1) % count
+                }];
+            }
+            Tile::Portal(c, _) => *c = step_letter(*c, forward),
+            _ => {}
+        }
+    }
+
+    /// Explanation including the live state of the tile at a (padded) position during play
+    pub fn dynamic_explanation(&self, pos: (usize, usize), model: &PlayingModel) -> String {
+        match self {
+            Tile::Portal(c, (row, col)) => {
+                format!("Portal {c}, linked to the other {c} portal at row {row}, column {col}.")
+            }
+            Tile::Cloud(_) => {
+                "A cloud tile that has not been used yet, it disappears once you leave it."
+                    .to_string()
+            }
+            Tile::Empty if model.is_consumed_cloud(pos) => {
+                "A cloud used to be here, but it has already disappeared.".to_string()
+            }
+            Tile::Door => match model.door_keys_on_board() {
+                0 => "A door tile, opened by a door key. No door keys are left on the board."
+                    .to_string(),
+                count => format!(
+                    "A door tile, opened by a door key. {count} door key(s) left on the board."
+                ),
+            },
+            Tile::Bounce(amount) => {
+                format!("A bounce tile, changes your speed by {amount:+} and keeps you moving.")
+            }
+            Tile::SpeedPad(speed) => {
+                format!("A speed pad, sets your speed to {speed} and keeps you moving.")
+            }
+            Tile::SpeedGate(speed) => {
+                format!("A speed gate, only lets you through when moving at speed {speed}.")
+            }
+            Tile::Switch(0) => "An open switch, which no longer blocks movement.".to_string(),
+            Tile::Switch(count) => {
+                format!("A switch, step on or run over it {count} more time(s) to open it.")
+            }
+            Tile::Blinker(phase) if model.blinker_is_open(*phase) => {
+                "A blinker, open this turn and closed the next.".to_string()
+            }
+            Tile::Blinker(_) => "A blinker, closed this turn and open the next.".to_string(),
+            Tile::Shield if model.is_collected_shield(pos) => {
+                "A shield tile, its shield has already been picked up.".to_string()
+            }
+            Tile::ResetTrap(true) => {
+                "A reset trap, which sends you back to the start and takes your keys.".to_string()
+            }
+            _ => self.explanation().to_string(),
+        }
+    }
+
+    /// Short name of the tile variant, ignoring its parameters
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tile::Empty => "Empty",
+            Tile::MoveCardinal(_) => "Cardinal",
+            Tile::MoveDiagonal(_) => "Diagonal",
+            Tile::Cloud(_) => "Cloud",
+            Tile::Bounce(_) => "Bounce",
+            Tile::SpeedPad(_) => "Speed Pad",
+            Tile::Portal(..) => "Portal",
+            Tile::Ice => "Ice",
+            Tile::Door => "Door",
+            Tile::Wall => "Wall",
+            Tile::Gap => "Gap",
+            Tile::SpeedGate(_) => "Speed Gate",
+            Tile::Block => "Block",
+            Tile::Checkpoint => "Checkpoint",
+            Tile::Reverse => "Reverse",
+            Tile::Switch(_) => "Switch",
+            Tile::Blinker(_) => "Blinker",
+            Tile::Shield => "Shield",
+            Tile::Arrow(_) => "Arrow",
+            Tile::ResetTrap(_) => "Reset Trap",
+            Tile::Rotator => "Rotator",
+            Tile::StartSpace => "Start",
+            Tile::EndSpace => "End",
+        }
+    }
+
+    /// Most tiles of this variant a board may have, if limited
+    pub fn max_count(&self) -> Option<usize> {
+        match self {
+            Tile::StartSpace | Tile::EndSpace => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Single pixel color for this tile on the editor minimap
+    pub fn minimap_color(&self) -> egui::Color32 {
+        match self {
+            Tile::Empty => egui::Color32::from_gray(30),
+            Tile::MoveCardinal(_) => egui::Color32::from_rgb(90, 140, 220),
+            Tile::MoveDiagonal(_) => egui::Color32::from_rgb(150, 110, 220),
+            Tile::Cloud(_) => egui::Color32::from_gray(200),
+            Tile::Bounce(_) => egui::Color32::from_rgb(230, 140, 40),
+            Tile::SpeedPad(_) => egui::Color32::from_rgb(240, 220, 60),
+            Tile::Portal(..) => egui::Color32::from_rgb(200, 60, 200),
+            Tile::Ice => egui::Color32::from_rgb(160, 220, 240),
+            Tile::Door => egui::Color32::from_rgb(140, 90, 40),
+            Tile::Wall => egui::Color32::from_gray(100),
+            Tile::Gap => egui::Color32::from_rgb(20, 15, 30),
+            Tile::SpeedGate(_) => egui::Color32::from_rgb(200, 170, 40),
+            Tile::Block => egui::Color32::from_rgb(150, 105, 60),
+            Tile::Checkpoint => egui::Color32::from_rgb(60, 200, 200),
+            Tile::Reverse => egui::Color32::from_rgb(220, 80, 120),
+            Tile::Switch(_) => egui::Color32::from_rgb(180, 180, 60),
+            Tile::Blinker(_) => egui::Color32::from_rgb(120, 160, 100),
+            Tile::Shield => egui::Color32::from_rgb(90, 170, 230),
+            Tile::Arrow(_) => egui::Color32::from_rgb(250, 120, 80),
+            Tile::ResetTrap(_) => egui::Color32::from_rgb(110, 50, 140),
+            Tile::Rotator => egui::Color32::from_rgb(230, 90, 200),
+            Tile::StartSpace => egui::Color32::from_rgb(60, 200, 60),
+            Tile::EndSpace => egui::Color32::from_rgb(220, 50, 50),
+        }
+    }
+
+    /// Character used for this tile in the ASCII board format
+    pub fn ascii_char(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::MoveCardinal(_) => 'C',
+            Tile::MoveDiagonal(_) => 'X',
+            Tile::Cloud(_) => 'O',
+            Tile::Bounce(_) => 'B',
+            Tile::SpeedPad(_) => 'V',
+            Tile::Portal(..) => 'P',
+            Tile::Ice => '~',
+            Tile::Door => 'D',
+            Tile::Wall => '#',
+            Tile::Gap => '_',
+            Tile::SpeedGate(_) => 'G',
+            Tile::Block => '$',
+            Tile::Checkpoint => '!',
+            Tile::Reverse => 'R',
+            Tile::Switch(_) => 'N',
+            Tile::Blinker(_) => 'L',
+            Tile::Shield => 'H',
+            Tile::Arrow(_) => 'A',
+            Tile::ResetTrap(_) => 'T',
+            Tile::Rotator => 'Q',
+            Tile::StartSpace => 'S',
+            Tile::EndSpace => 'E',
+        }
+    }
+
+    /// Tile for a character in the ASCII board format, parametric tiles get their default parameters
+    pub fn from_ascii_char(c: char) -> Option<Tile> {
+        ALL_TILES
+            .iter()
+            .find(|tile| tile.ascii_char() == c)
+            .cloned()
+    }
+
     /// Check if the tile is valid for the game rules - if not, will block playing
     pub fn is_valid(&self) -> bool {
         match self {
@@ -149,12 +525,24 @@ impl Tile {
                     || directions.down_left
                     || directions.up_left
             }
-            &Tile::Bounce(u) => (-1..=1).contains(&u),
+            &Tile::Bounce(u) => BOUNCE_RANGE.contains(&u),
+            Tile::SpeedPad(speed) => SPEED_PAD_RANGE.contains(speed),
+            Tile::SpeedGate(speed) => SPEED_GATE_RANGE.contains(speed),
+            Tile::Switch(count) => SWITCH_RANGE.contains(count),
+            Tile::Arrow(direction) => !direction.is_none(),
             Tile::Empty
             | Tile::Portal(..)
             | Tile::Ice
             | Tile::Door
             | Tile::Wall
+            | Tile::Gap
+            | Tile::Block
+            | Tile::Checkpoint
+            | Tile::Reverse
+            | Tile::Blinker(_)
+            | Tile::Shield
+            | Tile::ResetTrap(_)
+            | Tile::Rotator
             | Tile::StartSpace
             | Tile::EndSpace => true,
         }
@@ -166,16 +554,35 @@ impl Tile {
             Tile::Cloud(directions) => directions.allows(direction),
             Tile::MoveDiagonal(directions) => directions.allows(direction),
             Tile::Portal(..) => direction.is_cardinal() || direction.is_none(),
+            Tile::Arrow(arrow) => direction == arrow,
             _ => direction.is_cardinal(),
         }
     }
+
+    /// Like `can_move_in_direction`, but equipped movement keys also allow every direction
+    /// of their kind
+    pub fn can_move_in_direction_with_keys(
+        &self,
+        direction: &DirectionKey,
+        has_key: impl Fn(KeyItem) -> bool,
+    ) -> bool {
+        self.can_move_in_direction(direction)
+            || (direction.is_cardinal()
+                && has_key(KeyItem::OnEquip(KeyOnEquip::OnMovement(
+                    KeyOnMovement::Cardinal,
+                ))))
+            || (direction.is_diagonal()
+                && has_key(KeyItem::OnEquip(KeyOnEquip::OnMovement(
+                    KeyOnMovement::Diagonal,
+                ))))
+    }
 }
 
 /*
     TileData struct - title with associated item
 */
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct TileData {
     pub tile: Tile,
     // TBD: could be a vec of items later
@@ -196,3 +603,114 @@ impl Default for TileData {
         TileData::empty()
     }
 }
+
+/// Color shared by both portals of a letter. Hues step by the golden ratio from letter to
+/// letter, so neighbouring letters get far apart colors
+pub fn portal_color(letter: char) -> egui::Color32 {
+    let index = (letter as u32).wrapping_sub('A' as u32) as f32;
+    let hue = (index * 0.618_034).fract();
+    egui::ecolor::Hsva::new(hue, 0.8, 0.95, 1.0).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_cardinal_cw() {
+        let mut tile = Tile::MoveCardinal(CardinalDirectionsAllowed {
+            up: true,
+            right: false,
+            down: false,
+            left: false,
+        });
+        tile.rotate_cw();
+
+        assert_eq!(
+            tile,
+            Tile::MoveCardinal(CardinalDirectionsAllowed {
+                up: false,
+                right: true,
+                down: false,
+                left: false,
+            })
+        );
+    }
+
+    #[test]
+    fn rotate_diagonal_cw() {
+        let mut tile = Tile::MoveDiagonal(DiagonalDirectionsAllowed {
+            up_right: true,
+            down_right: false,
+            down_left: false,
+            up_left: true,
+        });
+        tile.rotate_cw();
+
+        assert_eq!(
+            tile,
+            Tile::MoveDiagonal(DiagonalDirectionsAllowed {
+                up_right: true,
+                down_right: true,
+                down_left: false,
+                up_left: false,
+            })
+        );
+    }
+
+    #[test]
+    fn cycle_variant_wraps() {
+        let mut tile = Tile::Bounce(*BOUNCE_RANGE.end());
+        tile.cycle_variant(true);
+        assert_eq!(tile, Tile::Bounce(*BOUNCE_RANGE.start()));
+        tile.cycle_variant(false);
+        assert_eq!(tile, Tile::Bounce(*BOUNCE_RANGE.end()));
+
+        let mut tile = Tile::Switch(*SWITCH_RANGE.start());
+        tile.cycle_variant(false);
+        assert_eq!(tile, Tile::Switch(*SWITCH_RANGE.end()));
+        tile.cycle_variant(false);
+        assert_eq!(tile, Tile::Switch(*SWITCH_RANGE.end() - 1));
+
+        let mut tile = Tile::MoveCardinal(CardinalDirectionsAllowed::from_mask(15));
+        tile.cycle_variant(true);
+        assert_eq!(
+            tile,
+            Tile::MoveCardinal(CardinalDirectionsAllowed::from_mask(1))
+        );
+        tile.cycle_variant(false);
+        assert_eq!(
+            tile,
+            Tile::MoveCardinal(CardinalDirectionsAllowed::from_mask(15))
+        );
+        assert!(tile.is_valid());
+    }
+
+    #[test]
+    fn rotate_non_directional_is_noop() {
+        let mut tile = Tile::Bounce(2);
+        tile.rotate_cw();
+
+        assert_eq!(tile, Tile::Bounce(2));
+    }
+
+    #[test]
+    fn portal_colors_are_distinct() {
+        let colors: Vec<_> = ('A'..='Z').map(portal_color).collect();
+        for (idx, color) in colors.iter().enumerate() {
+            assert!(
+                !colors[idx + 1..].contains(color),
+                "{}",
+                (b'A' + idx as u8) as char
+            );
+        }
+    }
+
+    #[test]
+    fn minimap_colors_are_distinct() {
+        let colors: Vec<_> = ALL_TILES.iter().map(Tile::minimap_color).collect();
+        for (idx, color) in colors.iter().enumerate() {
+            assert!(!colors[idx + 1..].contains(color), "{:?}", ALL_TILES[idx]);
+        }
+    }
+}